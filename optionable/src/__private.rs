@@ -0,0 +1,641 @@
+//! Helper functions used by the code generated from the [`derive@crate::Optionable`]-derive macro.
+//! Not part of the public API.
+use crate::field_mask::{FieldMaskTree, PathSegment, EMPTY};
+use crate::{Error, OptionableConvert, OptionableFieldPaths};
+use alloc::format;
+use alloc::string::ToString;
+use core::fmt::Display;
+
+pub use alloc::borrow::ToOwned;
+pub use alloc::string::String;
+pub use alloc::vec::Vec;
+
+/// Converts an optioned field that has to be set for the full type.
+/// Records the field (or its missing sub-fields) in `missing_fields` if the conversion fails.
+pub fn try_from_optioned_field<T: OptionableConvert>(
+    value: Option<T::Optioned>,
+    field: &str,
+    missing_fields: &mut Vec<String>,
+) -> Option<T> {
+    if let Some(value) = value {
+        try_from_optioned_value(value, field, missing_fields)
+    } else {
+        missing_fields.push(field.to_owned());
+        None
+    }
+}
+
+/// Converts an optioned field where the optioned type already expresses an unset value,
+/// e.g. because the full type is an `Option`.
+/// Records the missing sub-fields in `missing_fields` if the conversion fails.
+pub fn try_from_optioned_value<T: OptionableConvert>(
+    value: T::Optioned,
+    field: &str,
+    missing_fields: &mut Vec<String>,
+) -> Option<T> {
+    T::try_from_optioned(value)
+        .map_err(|err| missing_fields.extend(prefixed(field, err)))
+        .ok()
+}
+
+/// Merges an optioned field into the target if it is set.
+/// Records the missing sub-fields in `missing_fields` if the merge fails.
+pub fn merge_field<T: OptionableConvert>(
+    target: &mut T,
+    value: Option<T::Optioned>,
+    field: &str,
+    missing_fields: &mut Vec<String>,
+) {
+    if let Some(value) = value {
+        merge_value(target, value, field, missing_fields);
+    }
+}
+
+/// Merges an optioned field where the optioned type already expresses an unset value into the target.
+/// Records the missing sub-fields in `missing_fields` if the merge fails.
+pub fn merge_value<T: OptionableConvert>(
+    target: &mut T,
+    value: T::Optioned,
+    field: &str,
+    missing_fields: &mut Vec<String>,
+) {
+    if let Err(err) = target.merge(value) {
+        missing_fields.extend(prefixed(field, err));
+    }
+}
+
+/// Returns an error if any missing fields have been recorded.
+///
+/// # Errors
+/// - If `missing_fields` is non-empty.
+pub fn result(missing_fields: Vec<String>) -> Result<(), Error> {
+    if missing_fields.is_empty() {
+        Ok(())
+    } else {
+        Err(Error { missing_fields })
+    }
+}
+
+/// Prefixes the missing fields of the error with the name of the parent field.
+fn prefixed(field: &str, err: Error) -> impl Iterator<Item = String> + '_ {
+    err.missing_fields
+        .into_iter()
+        .map(move |missing| format!("{field}.{missing}"))
+}
+
+/// Returns the path of the sub-field `name` of the value at `prefix`.
+#[must_use]
+pub fn field_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{prefix}.{name}")
+    }
+}
+
+/// Returns the path of the entry `key` of the map at `prefix`.
+/// Backslashes and closing brackets in the key are escaped with a backslash.
+#[must_use]
+pub fn entry_path(prefix: &str, key: impl Display) -> String {
+    let key = key.to_string();
+    let mut path = String::with_capacity(prefix.len() + key.len() + 2);
+    path.push_str(prefix);
+    path.push('[');
+    for c in key.chars() {
+        if matches!(c, '\\' | ']') {
+            path.push('\\');
+        }
+        path.push(c);
+    }
+    path.push(']');
+    path
+}
+
+/// Records the path of a set (non-root) value itself if none of its sub-fields has been recorded
+/// since `paths` had the length `len`, e.g. for a nested struct set without any of its fields.
+pub fn set_value_path(prefix: &str, len: usize, paths: &mut Vec<String>) {
+    if paths.len() == len && !prefix.is_empty() {
+        paths.push(prefix.to_owned());
+    }
+}
+
+/// Merges a value as a whole: the value of the side that changed it relative to `base` is kept.
+/// Records the path in `conflicts` if both sides changed it to different values, `theirs` is kept in this case.
+pub fn three_way_merge_value<T: PartialEq>(
+    base: Option<&T>,
+    ours: T,
+    theirs: T,
+    path: &str,
+    conflicts: &mut Vec<String>,
+) -> T {
+    if ours == theirs || base == Some(&theirs) {
+        ours
+    } else if base == Some(&ours) {
+        theirs
+    } else {
+        conflicts.push(path.to_owned());
+        theirs
+    }
+}
+
+/// Applies the mask to an optioned field: the field is unset if it is not selected, kept if it is selected
+/// as a whole and masked recursively otherwise.
+pub fn mask_field<T: OptionableFieldPaths<Optioned = Option<O>>, O>(
+    optioned: &mut Option<O>,
+    mask: Option<&FieldMaskTree>,
+) {
+    match mask {
+        None => *optioned = None,
+        Some(mask) if mask.is_whole() => {}
+        Some(mask) => T::mask_optioned(optioned, mask),
+    }
+}
+
+/// Applies the mask to an optioned value that cannot be unset itself, e.g. an array element.
+/// All of its (sub-)fields are unset if it is not selected.
+pub fn mask_value<T: OptionableFieldPaths + ?Sized>(
+    optioned: &mut T::Optioned,
+    mask: Option<&FieldMaskTree>,
+) {
+    let mask = mask.unwrap_or(&EMPTY);
+    if !mask.is_whole() {
+        T::mask_optioned(optioned, mask);
+    }
+}
+
+/// Returns the mask of the sub-field `name`, the mask selecting nothing if it is not selected.
+#[must_use]
+pub fn field_mask<'a>(mask: &'a FieldMaskTree, name: &str) -> &'a FieldMaskTree {
+    mask.field(name).unwrap_or(&EMPTY)
+}
+
+/// Returns the remaining segments if the path starts with the sub-field `name`.
+#[must_use]
+pub fn strip_field<'a>(segments: &'a [PathSegment], name: &str) -> Option<&'a [PathSegment]> {
+    match segments.split_first() {
+        Some((PathSegment::Field(field), rest)) if field == name => Some(rest),
+        _ => None,
+    }
+}
+
+/// Converts an optional field into its tri-state optioned representation, `None` being an explicit null.
+#[cfg(feature = "async-graphql")]
+pub fn into_tri_state<T: OptionableConvert>(
+    value: Option<T>,
+) -> async_graphql::MaybeUndefined<T::Optioned> {
+    match value {
+        Some(value) => async_graphql::MaybeUndefined::Value(value.into_optioned()),
+        None => async_graphql::MaybeUndefined::Null,
+    }
+}
+
+/// Converts a tri-state optioned field, undefined and null both convert to `None`.
+/// Records the missing sub-fields in `missing_fields` if the conversion fails.
+#[cfg(feature = "async-graphql")]
+pub fn try_from_tri_state<T: OptionableConvert>(
+    value: async_graphql::MaybeUndefined<T::Optioned>,
+    field: &str,
+    missing_fields: &mut Vec<String>,
+) -> Option<Option<T>> {
+    match value {
+        async_graphql::MaybeUndefined::Value(value) => {
+            try_from_optioned_value::<T>(value, field, missing_fields).map(Some)
+        }
+        _ => Some(None),
+    }
+}
+
+/// Merges a tri-state optioned field into the target: undefined leaves the target untouched,
+/// null unsets it and a value is merged.
+/// Records the missing sub-fields in `missing_fields` if the merge fails.
+#[cfg(feature = "async-graphql")]
+pub fn merge_tri_state<T: OptionableConvert>(
+    target: &mut Option<T>,
+    value: async_graphql::MaybeUndefined<T::Optioned>,
+    field: &str,
+    missing_fields: &mut Vec<String>,
+) {
+    match value {
+        async_graphql::MaybeUndefined::Undefined => {}
+        async_graphql::MaybeUndefined::Null => *target = None,
+        async_graphql::MaybeUndefined::Value(value) => {
+            merge_value(target, Some(value), field, missing_fields);
+        }
+    }
+}
+
+/// Merges two tri-state optioned fields: undefined leaves the target untouched,
+/// null and values not mergeable into the target replace it.
+#[cfg(feature = "async-graphql")]
+pub fn merge_tri_state_optioned<T: OptionableConvert<Optioned = T>>(
+    target: &mut async_graphql::MaybeUndefined<T>,
+    value: async_graphql::MaybeUndefined<T>,
+) -> Result<(), Error> {
+    match (target, value) {
+        (_, async_graphql::MaybeUndefined::Undefined) => {}
+        (
+            async_graphql::MaybeUndefined::Value(target),
+            async_graphql::MaybeUndefined::Value(value),
+        ) => {
+            target.merge(value)?;
+        }
+        (target, value) => *target = value,
+    }
+    Ok(())
+}
+
+/// Records the set (sub-)fields of a tri-state optioned field, an explicit null is recorded as set.
+#[cfg(feature = "async-graphql")]
+pub fn set_tri_state_paths<T: OptionableFieldPaths>(
+    optioned: &async_graphql::MaybeUndefined<T::Optioned>,
+    prefix: &str,
+    paths: &mut Vec<String>,
+) {
+    match optioned {
+        async_graphql::MaybeUndefined::Undefined => {}
+        async_graphql::MaybeUndefined::Null => paths.push(prefix.to_owned()),
+        async_graphql::MaybeUndefined::Value(optioned) => {
+            T::set_field_paths_optioned(optioned, prefix, paths);
+        }
+    }
+}
+
+/// Applies the mask to a tri-state optioned field analogously to [`mask_field`],
+/// an unselected field becomes undefined.
+#[cfg(feature = "async-graphql")]
+pub fn mask_tri_state<T: OptionableFieldPaths>(
+    optioned: &mut async_graphql::MaybeUndefined<T::Optioned>,
+    mask: Option<&FieldMaskTree>,
+) {
+    match (optioned, mask) {
+        (optioned, None) => *optioned = async_graphql::MaybeUndefined::Undefined,
+        (_, Some(mask)) if mask.is_whole() => {}
+        (async_graphql::MaybeUndefined::Value(optioned), Some(mask)) => {
+            T::mask_optioned(optioned, mask);
+        }
+        _ => {}
+    }
+}
+
+/// Returns the `FieldsV1` object of the field `name`, inserting an empty one if it is missing.
+#[cfg(feature = "serde_json")]
+pub fn fields_v1_field<'a>(
+    fields: &'a mut serde_json::Map<String, serde_json::Value>,
+    name: &str,
+) -> &'a mut serde_json::Map<String, serde_json::Value> {
+    crate::fields_v1::entry(fields, format!("f:{name}"))
+}
+
+/// Inserts the `v:<value>` entries of the elements of a list with `list_type = "set"`.
+/// Elements that can't be serialized are skipped.
+#[cfg(feature = "serde_json")]
+pub fn fields_v1_list_set<'a, T: crate::Optionable + 'a>(
+    elements: impl IntoIterator<Item = &'a T::Optioned>,
+    fields: &mut serde_json::Map<String, serde_json::Value>,
+) where
+    T::Optioned: serde::Serialize,
+{
+    for element in elements {
+        if let Ok(value) = serde_json::to_string(element) {
+            crate::fields_v1::entry(fields, format!("v:{value}"));
+        }
+    }
+}
+
+/// Inserts the `k:<key fields>` entries of the elements of a list with `list_type = "map"`, the key fields
+/// being the set fields of the element with the given serialized names.
+/// Elements that can't be serialized are skipped.
+#[cfg(feature = "serde_json")]
+pub fn fields_v1_list_map<'a, T: crate::fields_v1::OptionableFieldsV1 + 'a>(
+    elements: impl IntoIterator<Item = &'a T::Optioned>,
+    keys: &[&str],
+    fields: &mut serde_json::Map<String, serde_json::Value>,
+) where
+    T::Optioned: serde::Serialize,
+{
+    for element in elements {
+        let Ok(serde_json::Value::Object(mut value)) = serde_json::to_value(element) else {
+            continue;
+        };
+        let key = keys
+            .iter()
+            .filter_map(|key| value.remove_entry(*key))
+            .filter(|(_, value)| !value.is_null())
+            .collect::<serde_json::Map<_, _>>();
+        let fields =
+            crate::fields_v1::entry(fields, format!("k:{}", serde_json::Value::Object(key)));
+        crate::fields_v1::entry(fields, ".".to_owned());
+        T::fields_v1_optioned(element, fields);
+    }
+}
+
+#[cfg(feature = "serde_json")]
+pub use serde::Serialize;
+#[cfg(feature = "serde_json")]
+pub type JsonMap = serde_json::Map<String, serde_json::Value>;
+
+/// Builds an `UPDATE` statement setting the columns of the set fields of an optioned struct.
+#[cfg(feature = "sqlx")]
+pub struct UpdateQuery<'args, DB: sqlx::Database> {
+    query: sqlx::QueryBuilder<'args, DB>,
+    columns: usize,
+    filters: usize,
+}
+
+#[cfg(feature = "sqlx")]
+impl<'args, DB: sqlx::Database> UpdateQuery<'args, DB> {
+    #[must_use]
+    pub fn new(table: &str) -> Self {
+        Self {
+            query: sqlx::QueryBuilder::new(format!("UPDATE {table} SET ")),
+            columns: 0,
+            filters: 0,
+        }
+    }
+
+    /// Sets the column to the value if the field is set. All columns have to be set before any filter is added.
+    pub fn set<T: 'args + sqlx::Encode<'args, DB> + sqlx::Type<DB>>(
+        &mut self,
+        column: &str,
+        value: Option<T>,
+    ) {
+        if let Some(value) = value {
+            if self.columns > 0 {
+                self.query.push(", ");
+            }
+            self.query.push(column).push(" = ").push_bind(value);
+            self.columns += 1;
+        }
+    }
+
+    /// Restricts the updated rows to those where the column equals the value.
+    pub fn filter<T: 'args + sqlx::Encode<'args, DB> + sqlx::Type<DB>>(
+        &mut self,
+        column: &str,
+        value: T,
+    ) {
+        self.query
+            .push(if self.filters == 0 {
+                " WHERE "
+            } else {
+                " AND "
+            })
+            .push(column)
+            .push(" = ")
+            .push_bind(value);
+        self.filters += 1;
+    }
+
+    /// Returns the query, `None` if no column is set.
+    #[must_use]
+    pub fn finish(self) -> Option<sqlx::QueryBuilder<'args, DB>> {
+        (self.columns > 0).then_some(self.query)
+    }
+}
+
+/// Returns the name of the environment variable of the field with the upper case `name`.
+#[cfg(feature = "env")]
+#[must_use]
+pub fn env_var(prefix: &str, name: &str) -> String {
+    format!("{prefix}{name}")
+}
+
+/// Parses the environment variable `var` if it is set.
+/// Records the variable in `errors` if it is not valid unicode or can't be parsed.
+#[cfg(feature = "env")]
+pub fn parse_env<T: std::str::FromStr>(
+    var: &str,
+    env: crate::env::EnvSource<'_>,
+    errors: &mut Vec<crate::env::EnvVarError>,
+) -> Option<T>
+where
+    T::Err: Display,
+{
+    let error = |message: String| crate::env::EnvVarError {
+        var: var.to_owned(),
+        message,
+    };
+    let value = env(var)?
+        .into_string()
+        .map_err(|_| errors.push(error("not valid unicode".to_owned())))
+        .ok()?;
+    value
+        .parse()
+        .map_err(|err: T::Err| errors.push(error(err.to_string())))
+        .ok()
+}
+
+/// Returns the value of a required field read from the environment variable `var`
+/// and records the variable in `errors` if it is not set.
+#[cfg(feature = "env")]
+pub fn require_env<T>(
+    value: Option<T>,
+    var: &str,
+    env: crate::env::EnvSource<'_>,
+    errors: &mut Vec<crate::env::EnvVarError>,
+) -> Option<T> {
+    if env(var).is_none() {
+        errors.push(crate::env::EnvVarError {
+            var: var.to_owned(),
+            message: "required field is not set".to_owned(),
+        });
+    }
+    value
+}
+
+/// Returns the name of the flag of the field `name` (in kebab-case) of the value with the flag `prefix`.
+#[cfg(feature = "clap")]
+#[must_use]
+pub fn arg_name(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{prefix}-{name}")
+    }
+}
+
+/// Returns the flag `--<name>` identified by its name.
+#[cfg(feature = "clap")]
+#[must_use]
+pub fn arg(name: &str, help: Option<&str>, required: bool) -> clap::Arg {
+    let arg = clap::Arg::new(name.to_owned())
+        .long(name.to_owned())
+        .value_name(name.to_uppercase().replace('-', "_"))
+        .required(required);
+    match help {
+        Some(help) => arg.help(help.to_owned()),
+        None => arg,
+    }
+}
+
+/// Returns the value of the required flag `--<name>`.
+///
+/// # Errors
+/// - If the flag is not set.
+#[cfg(feature = "clap")]
+pub fn required_arg_value<T: Clone + Send + Sync + 'static>(
+    matches: &clap::ArgMatches,
+    name: &str,
+) -> Result<T, clap::Error> {
+    matches.get_one::<T>(name).cloned().ok_or_else(|| {
+        clap::Error::raw(
+            clap::error::ErrorKind::MissingRequiredArgument,
+            format!("the argument '--{name}' is required\n"),
+        )
+    })
+}
+
+/// Records an error for the value at `path` if it is not within the bounds.
+pub fn validate_range<T: PartialOrd + Display>(
+    value: &T,
+    min: Option<T>,
+    max: Option<T>,
+    path: &str,
+    errors: &mut Vec<crate::validate::ValidationError>,
+) {
+    if let Some(min) = min.filter(|min| value < min) {
+        push_validation_error(path, format!("must be at least {min}"), errors);
+    }
+    if let Some(max) = max.filter(|max| value > max) {
+        push_validation_error(path, format!("must be at most {max}"), errors);
+    }
+}
+
+/// Records an error for the value at `path` if its length is not within the bounds.
+pub fn validate_length<T: crate::validate::Length + ?Sized>(
+    value: &T,
+    min: Option<usize>,
+    max: Option<usize>,
+    path: &str,
+    errors: &mut Vec<crate::validate::ValidationError>,
+) {
+    let length = value.length();
+    if let Some(min) = min.filter(|min| length < *min) {
+        push_validation_error(path, format!("length must be at least {min}"), errors);
+    }
+    if let Some(max) = max.filter(|max| length > *max) {
+        push_validation_error(path, format!("length must be at most {max}"), errors);
+    }
+}
+
+/// Records an error for the value at `path` if it doesn't match the regular expression.
+#[cfg(feature = "regex")]
+pub fn validate_regex<T: AsRef<str> + ?Sized>(
+    value: &T,
+    regex: &regex::Regex,
+    path: &str,
+    errors: &mut Vec<crate::validate::ValidationError>,
+) {
+    if !regex.is_match(value.as_ref()) {
+        push_validation_error(path, format!("must match {regex}"), errors);
+    }
+}
+
+#[cfg(feature = "regex")]
+pub use regex::Regex;
+#[cfg(feature = "regex")]
+pub use std::sync::LazyLock;
+
+/// Records the error returned by a custom validation function for the value at `path`.
+pub fn validate_custom(
+    result: Result<(), String>,
+    path: &str,
+    errors: &mut Vec<crate::validate::ValidationError>,
+) {
+    if let Err(message) = result {
+        push_validation_error(path, message, errors);
+    }
+}
+
+fn push_validation_error(
+    path: &str,
+    message: String,
+    errors: &mut Vec<crate::validate::ValidationError>,
+) {
+    errors.push(crate::validate::ValidationError {
+        path: path.to_owned(),
+        message,
+    });
+}
+
+#[cfg(feature = "proptest")]
+pub use proptest;
+
+/// Returns a strategy for patches of a field wrapped in an `Option` setting it with the probability `density`.
+#[cfg(feature = "proptest")]
+pub fn patch_field_strategy<T: crate::proptest::OptionableStrategy>(
+    value: &T,
+    density: f64,
+) -> proptest::strategy::BoxedStrategy<Option<T::Optioned>> {
+    crate::proptest::optional(density, T::patch_strategy(value, density))
+}
+
+/// Returns a strategy for tri-state optioned fields, the field is set (to null or a value)
+/// with the probability `density`.
+#[cfg(all(feature = "proptest", feature = "async-graphql"))]
+pub fn tri_state_strategy<T: crate::proptest::OptionableStrategy>(
+    density: f64,
+) -> proptest::strategy::BoxedStrategy<async_graphql::MaybeUndefined<T::Optioned>> {
+    use crate::proptest::OptionableStrategy;
+    use proptest::strategy::Strategy;
+    crate::proptest::optional(density, <Option<T>>::optioned_strategy(density))
+        .prop_map(async_graphql::MaybeUndefined::from)
+        .boxed()
+}
+
+/// Returns a strategy for patches of tri-state optioned fields, the field is set (to null or a patch
+/// of the present value) with the probability `density`.
+#[cfg(all(feature = "proptest", feature = "async-graphql"))]
+pub fn patch_tri_state_strategy<T: crate::proptest::OptionableStrategy>(
+    value: &Option<T>,
+    density: f64,
+) -> proptest::strategy::BoxedStrategy<async_graphql::MaybeUndefined<T::Optioned>> {
+    use proptest::strategy::Strategy;
+    patch_field_strategy(value, density)
+        .prop_map(async_graphql::MaybeUndefined::from)
+        .boxed()
+}
+
+#[cfg(feature = "arbitrary")]
+pub use arbitrary;
+
+/// Returns a patch of a field wrapped in an `Option` setting it with the probability `density`.
+///
+/// # Errors
+/// - If the data is not sufficient, see `arbitrary::Error`.
+#[cfg(feature = "arbitrary")]
+pub fn arbitrary_patch_field<T: crate::arbitrary::OptionableArbitrary>(
+    value: &T,
+    u: &mut arbitrary::Unstructured<'_>,
+    density: f64,
+) -> arbitrary::Result<Option<T::Optioned>> {
+    crate::arbitrary::optional(u, density, |u| T::arbitrary_patch(value, u, density))
+}
+
+/// Returns a tri-state optioned field, the field is set (to null or a value) with the probability `density`.
+///
+/// # Errors
+/// - If the data is not sufficient, see `arbitrary::Error`.
+#[cfg(all(feature = "arbitrary", feature = "async-graphql"))]
+pub fn arbitrary_tri_state<T: crate::arbitrary::OptionableArbitrary>(
+    u: &mut arbitrary::Unstructured<'_>,
+    density: f64,
+) -> arbitrary::Result<async_graphql::MaybeUndefined<T::Optioned>> {
+    use crate::arbitrary::OptionableArbitrary;
+    crate::arbitrary::optional(u, density, |u| <Option<T>>::arbitrary_optioned(u, density))
+        .map(async_graphql::MaybeUndefined::from)
+}
+
+/// Returns a patch of a tri-state optioned field, the field is set (to null or a patch of the present value)
+/// with the probability `density`.
+///
+/// # Errors
+/// - If the data is not sufficient, see `arbitrary::Error`.
+#[cfg(all(feature = "arbitrary", feature = "async-graphql"))]
+pub fn arbitrary_patch_tri_state<T: crate::arbitrary::OptionableArbitrary>(
+    value: &Option<T>,
+    u: &mut arbitrary::Unstructured<'_>,
+    density: f64,
+) -> arbitrary::Result<async_graphql::MaybeUndefined<T::Optioned>> {
+    arbitrary_patch_field(value, u, density).map(async_graphql::MaybeUndefined::from)
+}
@@ -0,0 +1,533 @@
+//! Random optioned values from unstructured bytes via [arbitrary](https://docs.rs/arbitrary/latest/arbitrary/),
+//! e.g. to fuzz the code applying patches with `cargo fuzz`.
+//!
+//! The `arbitrary` attribute of the derive macro implements [`OptionableArbitrary`] for the type and
+//! `arbitrary::Arbitrary` for its optioned type. Like the `proptest` strategies, every (sub-)field
+//! is set with a given probability (the density) and either arbitrary optioned values or patches that can
+//! be merged into a given value without error are produced.
+//! ```rust
+//! # use optionable::{Optionable, OptionableConvert};
+//! # use optionable::arbitrary::OptionableArbitrary;
+//! # use arbitrary::Unstructured;
+//! #[derive(Optionable, Clone)]
+//! #[optionable(arbitrary)]
+//! struct Deployment {
+//!     name: String,
+//!     replicas: Option<u32>,
+//! }
+//!
+//! let mut u = Unstructured::new(&[7; 64]);
+//! let patch = Deployment::arbitrary_optioned(&mut u, 0.2).unwrap();
+//! let _ = Deployment::try_from_optioned(patch);
+//!
+//! let mut deployment = Deployment { name: "a".to_owned(), replicas: None };
+//! let patch = Deployment::arbitrary_patch(&deployment, &mut u, 0.5).unwrap();
+//! assert!(deployment.merge(patch).is_ok());
+//! ```
+use crate::Optionable;
+use arbitrary::{Arbitrary, Result, Unstructured};
+use std::borrow::Cow;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::ffi::OsString;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping,
+};
+use std::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The density of the `arbitrary::Arbitrary` impls of derived optioned types.
+pub const DEFAULT_DENSITY: f64 = 0.5;
+
+/// The maximal number of elements of generated collections.
+const MAX_SIZE: usize = 8;
+
+/// Returns a value of `f` that is set with the probability `density`.
+/// The densities `0.0` (never set) and `1.0` (always set) consume no data, otherwise the value is unset
+/// once the data is exhausted.
+///
+/// # Errors
+/// - If `f` fails.
+pub fn optional<'a, T>(
+    u: &mut Unstructured<'a>,
+    density: f64,
+    f: impl FnOnce(&mut Unstructured<'a>) -> Result<T>,
+) -> Result<Option<T>> {
+    let set = if density <= 0.0 {
+        false
+    } else if density >= 1.0 {
+        true
+    } else {
+        f64::from(u.arbitrary::<u8>()?) >= 256.0 * (1.0 - density)
+    };
+    if set {
+        f(u).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns up to [`MAX_SIZE`] values of `f`, fewer once the data is exhausted.
+///
+/// # Errors
+/// - If `f` fails.
+pub(crate) fn collection<'a, T>(
+    u: &mut Unstructured<'a>,
+    mut f: impl FnMut(&mut Unstructured<'a>) -> Result<T>,
+) -> Result<Vec<T>> {
+    let len = u.int_in_range(0..=MAX_SIZE)?;
+    (0..len).map(|_| f(u)).collect()
+}
+
+/// Extension trait generating optioned values from unstructured bytes via `arbitrary`.
+pub trait OptionableArbitrary: Optionable {
+    /// Returns an optioned value setting every (sub-)field with the probability `density`.
+    /// With a density of `1.0` all (sub-)fields are set and the value can be converted into the full type.
+    ///
+    /// # Errors
+    /// - If the data is not sufficient, see `arbitrary::Error`.
+    fn arbitrary_optioned(u: &mut Unstructured<'_>, density: f64) -> Result<Self::Optioned>;
+
+    /// Returns a patch of `value` that can be merged into it without error, setting every (sub-)field
+    /// with the probability `density`.
+    ///
+    /// # Errors
+    /// - If the data is not sufficient, see `arbitrary::Error`.
+    fn arbitrary_patch(
+        value: &Self,
+        u: &mut Unstructured<'_>,
+        density: f64,
+    ) -> Result<Self::Optioned>;
+}
+
+/// Helper macro to generate an impl for `OptionableArbitrary` for types that are their own optioned type
+/// and implement `arbitrary::Arbitrary`, the values are replaced as a whole.
+macro_rules! impl_arbitrary_self {
+    ($($t:ty),* $(,)?) => {
+        $(impl OptionableArbitrary for $t {
+            fn arbitrary_optioned(u: &mut Unstructured<'_>, _density: f64) -> Result<$t> {
+                u.arbitrary()
+            }
+
+            fn arbitrary_patch(_value: &$t, u: &mut Unstructured<'_>, _density: f64) -> Result<$t> {
+                u.arbitrary()
+            }
+        })*
+    };
+}
+
+impl_arbitrary_self!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, char, bool, String
+);
+
+impl_arbitrary_self!(());
+
+impl_arbitrary_self!(
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize,
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize
+);
+
+impl_arbitrary_self!(Duration);
+
+impl_arbitrary_self!(
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6
+);
+
+impl_arbitrary_self!(PathBuf, OsString);
+
+/// Helper macro to generate an impl for `OptionableArbitrary` for types that are their own optioned type
+/// but don't implement `arbitrary::Arbitrary`. The `$arbitrary` expression returns a value from `$u`.
+macro_rules! impl_arbitrary_self_with {
+    ($($t:ty: |$u:ident| $arbitrary:expr),* $(,)?) => {
+        $(impl OptionableArbitrary for $t {
+            fn arbitrary_optioned($u: &mut Unstructured<'_>, _density: f64) -> Result<$t> {
+                $arbitrary
+            }
+
+            fn arbitrary_patch(_value: &$t, u: &mut Unstructured<'_>, density: f64) -> Result<$t> {
+                Self::arbitrary_optioned(u, density)
+            }
+        })*
+    };
+}
+
+impl_arbitrary_self_with!(
+    Ordering: |u| u.choose(&[Ordering::Less, Ordering::Equal, Ordering::Greater]).copied(),
+    RangeFull: |_u| Ok(..),
+    Instant: |u| {
+        let now = Instant::now();
+        Ok(now.checked_add(u.arbitrary()?).unwrap_or(now))
+    },
+    SystemTime: |u| Ok(UNIX_EPOCH.checked_add(u.arbitrary()?).unwrap_or(UNIX_EPOCH)),
+);
+
+impl<T: ?Sized> OptionableArbitrary for PhantomData<T> {
+    fn arbitrary_optioned(_u: &mut Unstructured<'_>, _density: f64) -> Result<PhantomData<T>> {
+        Ok(PhantomData)
+    }
+
+    fn arbitrary_patch(
+        _value: &PhantomData<T>,
+        _u: &mut Unstructured<'_>,
+        _density: f64,
+    ) -> Result<PhantomData<T>> {
+        Ok(PhantomData)
+    }
+}
+
+// An unset value is kept as is, a value that is not present yet has to be set completely.
+impl<T: OptionableArbitrary> OptionableArbitrary for Option<T> {
+    fn arbitrary_optioned(u: &mut Unstructured<'_>, density: f64) -> Result<Option<T::Optioned>> {
+        optional(u, density, |u| T::arbitrary_optioned(u, density))
+    }
+
+    fn arbitrary_patch(
+        value: &Option<T>,
+        u: &mut Unstructured<'_>,
+        density: f64,
+    ) -> Result<Option<T::Optioned>> {
+        match value {
+            Some(value) => optional(u, density, |u| T::arbitrary_patch(value, u, density)),
+            None => optional(u, density, |u| T::arbitrary_optioned(u, 1.0)),
+        }
+    }
+}
+
+impl<T: OptionableArbitrary, E: for<'a> Arbitrary<'a>> OptionableArbitrary for Result<T, E> {
+    fn arbitrary_optioned(
+        u: &mut Unstructured<'_>,
+        density: f64,
+    ) -> Result<Result<T::Optioned, E>> {
+        if u.arbitrary()? {
+            T::arbitrary_optioned(u, density).map(Ok)
+        } else {
+            u.arbitrary().map(Err)
+        }
+    }
+
+    fn arbitrary_patch(
+        value: &Result<T, E>,
+        u: &mut Unstructured<'_>,
+        density: f64,
+    ) -> Result<Result<T::Optioned, E>> {
+        match value {
+            Ok(value) if u.arbitrary()? => T::arbitrary_patch(value, u, density).map(Ok),
+            _ => Self::arbitrary_optioned(u, 1.0),
+        }
+    }
+}
+
+/// Helper macro to generate an impl for `OptionableArbitrary` for smart pointers and cells
+/// with a `new` constructor. The `$get` expression returns the pointed to value of `$value`.
+macro_rules! impl_arbitrary_pointer {
+    ($($t:ident: |$value:ident| $get:expr),* $(,)?) => {
+        $(impl<T: OptionableArbitrary> OptionableArbitrary for $t<T> {
+            fn arbitrary_optioned(
+                u: &mut Unstructured<'_>,
+                density: f64,
+            ) -> Result<$t<T::Optioned>> {
+                T::arbitrary_optioned(u, density).map($t::new)
+            }
+
+            fn arbitrary_patch(
+                $value: &$t<T>,
+                u: &mut Unstructured<'_>,
+                density: f64,
+            ) -> Result<$t<T::Optioned>> {
+                T::arbitrary_patch(&$get, u, density).map($t::new)
+            }
+        })*
+    };
+}
+
+impl_arbitrary_pointer!(
+    Box: |value| **value,
+    Rc: |value| **value,
+    Arc: |value| **value,
+    RefCell: |value| value.borrow(),
+    Mutex: |value| value.lock().unwrap_or_else(PoisonError::into_inner),
+    RwLock: |value| value.read().unwrap_or_else(PoisonError::into_inner),
+);
+
+impl<T: OptionableArbitrary + Copy> OptionableArbitrary for Cell<T>
+where
+    T::Optioned: Copy,
+{
+    fn arbitrary_optioned(u: &mut Unstructured<'_>, density: f64) -> Result<Cell<T::Optioned>> {
+        T::arbitrary_optioned(u, density).map(Cell::new)
+    }
+
+    fn arbitrary_patch(
+        value: &Cell<T>,
+        u: &mut Unstructured<'_>,
+        density: f64,
+    ) -> Result<Cell<T::Optioned>> {
+        T::arbitrary_patch(&value.get(), u, density).map(Cell::new)
+    }
+}
+
+impl<T: OptionableArbitrary + Unpin> OptionableArbitrary for Pin<Box<T>>
+where
+    T::Optioned: Unpin,
+{
+    fn arbitrary_optioned(u: &mut Unstructured<'_>, density: f64) -> Result<Pin<Box<T::Optioned>>> {
+        T::arbitrary_optioned(u, density).map(Box::pin)
+    }
+
+    fn arbitrary_patch(
+        value: &Pin<Box<T>>,
+        u: &mut Unstructured<'_>,
+        density: f64,
+    ) -> Result<Pin<Box<T::Optioned>>> {
+        T::arbitrary_patch(value, u, density).map(Box::pin)
+    }
+}
+
+/// Helper macro to generate an impl for `OptionableArbitrary` for cells that are initialized at most once.
+macro_rules! impl_arbitrary_once_cell {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableArbitrary> OptionableArbitrary for $t<T> {
+            fn arbitrary_optioned(
+                u: &mut Unstructured<'_>,
+                density: f64,
+            ) -> Result<$t<T::Optioned>> {
+                let value = <Option<T>>::arbitrary_optioned(u, density)?;
+                Ok(value.map_or_else($t::new, $t::from))
+            }
+
+            fn arbitrary_patch(
+                value: &$t<T>,
+                u: &mut Unstructured<'_>,
+                density: f64,
+            ) -> Result<$t<T::Optioned>> {
+                let value = match value.get() {
+                    Some(value) => optional(u, density, |u| T::arbitrary_patch(value, u, density))?,
+                    None => optional(u, density, |u| T::arbitrary_optioned(u, 1.0))?,
+                };
+                Ok(value.map_or_else($t::new, $t::from))
+            }
+        })*
+    };
+}
+
+impl_arbitrary_once_cell!(OnceCell, OnceLock);
+
+/// Helper macro to generate an impl for `OptionableArbitrary` for newtype wrapper around a single value.
+macro_rules! impl_arbitrary_newtype {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableArbitrary> OptionableArbitrary for $t<T> {
+            fn arbitrary_optioned(
+                u: &mut Unstructured<'_>,
+                density: f64,
+            ) -> Result<$t<T::Optioned>> {
+                T::arbitrary_optioned(u, density).map($t)
+            }
+
+            fn arbitrary_patch(
+                value: &$t<T>,
+                u: &mut Unstructured<'_>,
+                density: f64,
+            ) -> Result<$t<T::Optioned>> {
+                T::arbitrary_patch(&value.0, u, density).map($t)
+            }
+        })*
+    };
+}
+
+impl_arbitrary_newtype!(Reverse, Wrapping, Saturating);
+
+/// Helper macro to generate an impl for `OptionableArbitrary` for collections without keys.
+/// They are replaced as a whole when merging, hence the elements of patches are set completely.
+macro_rules! impl_arbitrary_collection {
+    ($($t:ident<T $(: $bound:path)?>),* $(,)?) => {
+        $(impl<T: OptionableArbitrary> OptionableArbitrary for $t<T>
+        $(where
+            T::Optioned: $bound,)?
+        {
+            fn arbitrary_optioned(
+                u: &mut Unstructured<'_>,
+                density: f64,
+            ) -> Result<$t<T::Optioned>> {
+                let values = collection(u, |u| T::arbitrary_optioned(u, density))?;
+                Ok(values.into_iter().collect())
+            }
+
+            fn arbitrary_patch(
+                _value: &$t<T>,
+                u: &mut Unstructured<'_>,
+                _density: f64,
+            ) -> Result<$t<T::Optioned>> {
+                Self::arbitrary_optioned(u, 1.0)
+            }
+        })*
+    };
+}
+
+impl_arbitrary_collection!(
+    Vec<T>,
+    VecDeque<T>,
+    LinkedList<T>,
+    BinaryHeap<T: Ord>,
+    BTreeSet<T: Ord>,
+);
+
+impl<T: OptionableArbitrary, S: BuildHasher + Default> OptionableArbitrary for HashSet<T, S>
+where
+    T::Optioned: Eq + Hash,
+{
+    fn arbitrary_optioned(
+        u: &mut Unstructured<'_>,
+        density: f64,
+    ) -> Result<HashSet<T::Optioned, S>> {
+        let values = collection(u, |u| T::arbitrary_optioned(u, density))?;
+        Ok(values.into_iter().collect())
+    }
+
+    fn arbitrary_patch(
+        _value: &HashSet<T, S>,
+        u: &mut Unstructured<'_>,
+        _density: f64,
+    ) -> Result<HashSet<T::Optioned, S>> {
+        Self::arbitrary_optioned(u, 1.0)
+    }
+}
+
+// Arrays are merged element-wise.
+impl<T: OptionableArbitrary, const N: usize> OptionableArbitrary for [T; N] {
+    fn arbitrary_optioned(u: &mut Unstructured<'_>, density: f64) -> Result<[T::Optioned; N]> {
+        let values = (0..N)
+            .map(|_| T::arbitrary_optioned(u, density))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(array(values))
+    }
+
+    fn arbitrary_patch(
+        value: &[T; N],
+        u: &mut Unstructured<'_>,
+        density: f64,
+    ) -> Result<[T::Optioned; N]> {
+        let values = value
+            .iter()
+            .map(|value| T::arbitrary_patch(value, u, density))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(array(values))
+    }
+}
+
+/// Returns the array of the `N` values.
+fn array<T, const N: usize>(values: Vec<T>) -> [T; N] {
+    values
+        .try_into()
+        .unwrap_or_else(|_| unreachable!("length is fixed to N"))
+}
+
+/// Helper macro to generate an impl for `OptionableArbitrary` for maps. The patches set some of the present
+/// entries and some new entries, the latter completely.
+/// The generic parameters of the impl (including the key type `K` and value type `T`) are passed in brackets.
+macro_rules! impl_arbitrary_map {
+    ($([$($generics:tt)*] $t:ty),* $(,)?) => {
+        $(impl<$($generics)*> OptionableArbitrary for $t {
+            fn arbitrary_optioned(u: &mut Unstructured<'_>, density: f64) -> Result<Self::Optioned> {
+                let entries = collection(u, |u| Ok((u.arbitrary()?, T::arbitrary_optioned(u, density)?)))?;
+                Ok(entries.into_iter().collect())
+            }
+
+            fn arbitrary_patch(
+                value: &$t,
+                u: &mut Unstructured<'_>,
+                density: f64,
+            ) -> Result<Self::Optioned> {
+                let mut patch = Self::Optioned::default();
+                for (key, value) in value {
+                    if let Some(value) = optional(u, density, |u| T::arbitrary_patch(value, u, density))? {
+                        patch.insert(key.clone(), value);
+                    }
+                }
+                patch.extend(Self::arbitrary_optioned(u, 1.0)?);
+                Ok(patch)
+            }
+        })*
+    };
+}
+
+impl_arbitrary_map!(
+    [K: for<'a> Arbitrary<'a> + Clone + Ord, T: OptionableArbitrary] BTreeMap<K, T>,
+    [K: for<'a> Arbitrary<'a> + Clone + Eq + Hash, T: OptionableArbitrary, S: BuildHasher + Default]
+        HashMap<K, T, S>,
+);
+
+/// Helper macro to generate an impl for `OptionableArbitrary` for ranges. They are replaced as a whole
+/// when merging, hence the bounds of patches are set completely.
+macro_rules! impl_arbitrary_range {
+    ($($t:ident: |$($bound:ident),*| $range:expr),* $(,)?) => {
+        $(impl<T: OptionableArbitrary> OptionableArbitrary for $t<T> {
+            fn arbitrary_optioned(
+                u: &mut Unstructured<'_>,
+                density: f64,
+            ) -> Result<$t<T::Optioned>> {
+                $(let $bound = T::arbitrary_optioned(u, density)?;)*
+                Ok($range)
+            }
+
+            fn arbitrary_patch(
+                _value: &$t<T>,
+                u: &mut Unstructured<'_>,
+                _density: f64,
+            ) -> Result<$t<T::Optioned>> {
+                Self::arbitrary_optioned(u, 1.0)
+            }
+        })*
+    };
+}
+
+impl_arbitrary_range!(
+    Range: |start, end| start..end,
+    RangeInclusive: |start, end| start..=end,
+    RangeFrom: |start| start..,
+    RangeTo: |end| ..end,
+    RangeToInclusive: |end| ..=end,
+);
+
+impl<B: ?Sized + ToOwned> OptionableArbitrary for Cow<'_, B>
+where
+    B::Owned: OptionableArbitrary,
+{
+    fn arbitrary_optioned(
+        u: &mut Unstructured<'_>,
+        density: f64,
+    ) -> Result<<B::Owned as Optionable>::Optioned> {
+        B::Owned::arbitrary_optioned(u, density)
+    }
+
+    fn arbitrary_patch(
+        value: &Cow<'_, B>,
+        u: &mut Unstructured<'_>,
+        density: f64,
+    ) -> Result<<B::Owned as Optionable>::Optioned> {
+        B::Owned::arbitrary_patch(&value.clone().into_owned(), u, density)
+    }
+}
@@ -0,0 +1,60 @@
+use crate::three_way::OptionableThreeWayMerge;
+use crate::{
+    impl_convert_self, impl_field_paths_whole, impl_three_way_whole, Error, Optionable,
+    OptionableConvert, OptionableFieldPaths,
+};
+use arrayvec::{ArrayString, ArrayVec};
+
+impl<T: Optionable, const CAP: usize> Optionable for ArrayVec<T, CAP> {
+    type Optioned = ArrayVec<T::Optioned, CAP>;
+}
+
+impl<T: OptionableConvert, const CAP: usize> OptionableConvert for ArrayVec<T, CAP> {
+    fn into_optioned(self) -> ArrayVec<T::Optioned, CAP> {
+        self.into_iter().map(T::into_optioned).collect()
+    }
+
+    fn try_from_optioned(value: ArrayVec<T::Optioned, CAP>) -> Result<Self, Error> {
+        value.into_iter().map(T::try_from_optioned).collect()
+    }
+
+    fn merge(&mut self, other: ArrayVec<T::Optioned, CAP>) -> Result<(), Error> {
+        *self = Self::try_from_optioned(other)?;
+        Ok(())
+    }
+}
+
+impl_field_paths_whole!([T: OptionableFieldPaths, const CAP: usize] ArrayVec<T, CAP>);
+impl_three_way_whole!([T: OptionableThreeWayMerge, const CAP: usize] ArrayVec<T, CAP>);
+#[cfg(feature = "serde_json")]
+crate::impl_fields_v1_whole!([T: crate::fields_v1::OptionableFieldsV1, const CAP: usize] ArrayVec<T, CAP>);
+#[cfg(feature = "serde_json")]
+crate::strategic_merge::impl_strategic_merge_elements!([T: crate::strategic_merge::OptionableStrategicMerge, const CAP: usize] ArrayVec<T, CAP> => T);
+
+impl<const CAP: usize> Optionable for ArrayString<CAP> {
+    type Optioned = Self;
+}
+
+impl_convert_self!([const CAP: usize] ArrayString<CAP>);
+impl_field_paths_whole!([const CAP: usize] ArrayString<CAP>);
+impl_three_way_whole!([const CAP: usize] ArrayString<CAP>);
+#[cfg(feature = "serde_json")]
+crate::impl_fields_v1_whole!([const CAP: usize] ArrayString<CAP>);
+#[cfg(feature = "serde_json")]
+crate::impl_strategic_merge_whole!([const CAP: usize] ArrayString<CAP>);
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use arrayvec::{ArrayString, ArrayVec};
+
+    #[test]
+    /// Check that `ArrayVec` and `ArrayString` implement optionable.
+    fn array_vec() {
+        let a = ArrayVec::from([1, 2]);
+        let _: <ArrayVec<i32, 2> as Optionable>::Optioned = a;
+
+        let a = ArrayString::from("a").unwrap();
+        let _: <ArrayString<2> as Optionable>::Optioned = a;
+    }
+}
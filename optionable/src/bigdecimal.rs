@@ -0,0 +1,17 @@
+use crate::{impl_optional_self, Optionable};
+use bigdecimal::num_bigint::{BigInt, BigUint};
+use bigdecimal::BigDecimal;
+
+impl_optional_self!(BigDecimal, BigInt, BigUint);
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use bigdecimal::BigDecimal;
+
+    #[test]
+    /// Check that `BigDecimal` implements optionable.
+    fn big_decimal() {
+        let _: <BigDecimal as Optionable>::Optioned = BigDecimal::from(1);
+    }
+}
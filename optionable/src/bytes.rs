@@ -0,0 +1,16 @@
+use crate::{impl_optional_self, Optionable};
+use bytes::{Bytes, BytesMut};
+
+impl_optional_self!(Bytes, BytesMut);
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use bytes::Bytes;
+
+    #[test]
+    /// Check that `Bytes` implements optionable.
+    fn bytes() {
+        let _: <Bytes as Optionable>::Optioned = Bytes::from_static(b"a");
+    }
+}
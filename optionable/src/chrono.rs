@@ -1,4 +1,6 @@
-use crate::{impl_optional_self, Optionable};
+use crate::{
+    impl_convert_self, impl_field_paths_whole, impl_optional_self, impl_three_way_whole, Optionable,
+};
 use chrono::{DateTime, Days, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone};
 
 #[cfg(feature = "chrono")]
@@ -6,4 +8,12 @@ impl<Tz: TimeZone> Optionable for DateTime<Tz> {
     type Optioned = Self;
 }
 
+impl_convert_self!([Tz: TimeZone] DateTime<Tz>);
+impl_field_paths_whole!([Tz: TimeZone] DateTime<Tz>);
+impl_three_way_whole!([Tz: TimeZone] DateTime<Tz>);
+#[cfg(feature = "serde_json")]
+crate::impl_fields_v1_whole!([Tz: TimeZone] DateTime<Tz>);
+#[cfg(feature = "serde_json")]
+crate::impl_strategic_merge_whole!([Tz: TimeZone] DateTime<Tz>);
+
 impl_optional_self!(Days, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta);
@@ -0,0 +1,139 @@
+//! Command line arguments for optioned types via [clap](https://docs.rs/clap/latest/clap/), e.g. as a layer of a
+//! [`crate::layered::Layered`] configuration.
+//!
+//! Forwarding `clap::Args` as derive implements it for the optioned struct: every non-required field
+//! becomes an optional flag named by the kebab-case field name, required fields become required flags.
+//! The flags of nested structs are flattened and prefixed by the name of their parent field.
+//! ```rust
+//! # use optionable::Optionable;
+//! # use clap::{Args, Parser};
+//! #[derive(Optionable)]
+//! #[optionable(derive(Args))]
+//! struct App {
+//!     name: String,
+//!     spec: Spec,
+//! }
+//!
+//! #[derive(Optionable)]
+//! #[optionable(derive(Args))]
+//! struct Spec {
+//!     max_replicas: u32,
+//! }
+//!
+//! #[derive(Parser)]
+//! struct Cli {
+//!     #[command(flatten)]
+//!     app: AppOpt,
+//! }
+//!
+//! let cli = Cli::parse_from(["app", "--spec-max-replicas", "3"]);
+//! assert_eq!(cli.app.name, None);
+//! assert_eq!(cli.app.spec.unwrap().max_replicas, Some(3));
+//! ```
+use crate::Optionable;
+use clap::{ArgMatches, Command};
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Extension trait to read optioned values from command line arguments. Implemented for the types that
+/// resolve to themselves as optioned type and have a clap value parser, derived for structs by
+/// forwarding `clap::Args` as derive.
+pub trait OptionableArgs: Optionable {
+    /// Adds the flag `--<name>` with the given help text. Structs add the flags of their fields instead,
+    /// named by `name`, `-` and the kebab-case field name (or just the field name if `name` is empty).
+    #[must_use]
+    fn augment_args_optioned(cmd: Command, name: &str, help: Option<&str>) -> Command;
+
+    /// Reads the optioned value from the flags added by [`OptionableArgs::augment_args_optioned`].
+    /// Returns `None` if no flag is set.
+    ///
+    /// # Errors
+    /// - If required flags are missing.
+    fn from_arg_matches_optioned(
+        matches: &ArgMatches,
+        name: &str,
+    ) -> Result<Option<Self::Optioned>, clap::Error>;
+}
+
+/// Helper macro to generate an impl for `OptionableArgs` for types with a clap value parser.
+macro_rules! impl_args_value {
+    ($($t:ty),* $(,)?) => {
+        $(impl OptionableArgs for $t {
+            fn augment_args_optioned(cmd: Command, name: &str, help: Option<&str>) -> Command {
+                cmd.arg(crate::__private::arg(name, help, false).value_parser(clap::value_parser!($t)))
+            }
+
+            fn from_arg_matches_optioned(matches: &ArgMatches, name: &str) -> Result<Option<$t>, clap::Error> {
+                Ok(matches.get_one::<$t>(name).cloned())
+            }
+        })*
+    };
+}
+
+impl_args_value!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, char, String
+);
+
+impl_args_value!(
+    std::num::NonZeroI8,
+    std::num::NonZeroI16,
+    std::num::NonZeroI32,
+    std::num::NonZeroI64,
+    std::num::NonZeroI128,
+    std::num::NonZeroIsize,
+    std::num::NonZeroU8,
+    std::num::NonZeroU16,
+    std::num::NonZeroU32,
+    std::num::NonZeroU64,
+    std::num::NonZeroU128,
+    std::num::NonZeroUsize
+);
+
+impl_args_value!(
+    std::net::IpAddr,
+    std::net::Ipv4Addr,
+    std::net::Ipv6Addr,
+    std::net::SocketAddr,
+    std::net::SocketAddrV4,
+    std::net::SocketAddrV6
+);
+
+impl_args_value!(PathBuf, OsString);
+
+// A flag without value sets `true`, e.g. `--verbose`, while `--verbose false` explicitly sets `false`.
+impl OptionableArgs for bool {
+    fn augment_args_optioned(cmd: Command, name: &str, help: Option<&str>) -> Command {
+        cmd.arg(
+            crate::__private::arg(name, help, false)
+                .value_parser(clap::value_parser!(bool))
+                .num_args(0..=1)
+                .default_missing_value("true"),
+        )
+    }
+
+    fn from_arg_matches_optioned(
+        matches: &ArgMatches,
+        name: &str,
+    ) -> Result<Option<bool>, clap::Error> {
+        Ok(matches.get_one::<bool>(name).copied())
+    }
+}
+
+/// Helper macro to generate an impl for `OptionableArgs` for smart pointers reading the pointed to value.
+macro_rules! impl_args_pointer {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableArgs> OptionableArgs for $t<T> {
+            fn augment_args_optioned(cmd: Command, name: &str, help: Option<&str>) -> Command {
+                T::augment_args_optioned(cmd, name, help)
+            }
+
+            fn from_arg_matches_optioned(matches: &ArgMatches, name: &str) -> Result<Option<$t<T::Optioned>>, clap::Error> {
+                Ok(T::from_arg_matches_optioned(matches, name)?.map($t::new))
+            }
+        })*
+    };
+}
+
+impl_args_pointer!(Box, Rc, Arc);
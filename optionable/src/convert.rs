@@ -0,0 +1,470 @@
+//! [`OptionableConvert`] implementations for the std types that are not their own optioned type.
+use crate::__private::{merge_value, result, try_from_optioned_value};
+use crate::{impl_convert_self, Error, OptionableConvert};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::string::ToString;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::{Cell, OnceCell, RefCell};
+use core::cmp::Reverse;
+use core::marker::PhantomData;
+use core::num::{Saturating, Wrapping};
+use core::ops::{Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive};
+use core::pin::Pin;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+#[cfg(feature = "std")]
+use std::sync::{LockResult, Mutex, OnceLock, PoisonError, RwLock};
+
+impl<T: OptionableConvert> OptionableConvert for Option<T> {
+    fn into_optioned(self) -> Option<T::Optioned> {
+        self.map(T::into_optioned)
+    }
+
+    fn try_from_optioned(value: Option<T::Optioned>) -> Result<Self, Error> {
+        value.map(T::try_from_optioned).transpose()
+    }
+
+    fn merge(&mut self, other: Option<T::Optioned>) -> Result<(), Error> {
+        match (self.as_mut(), other) {
+            (_, None) => {}
+            (Some(value), Some(other)) => value.merge(other)?,
+            (None, Some(other)) => *self = Some(T::try_from_optioned(other)?),
+        }
+        Ok(())
+    }
+}
+
+impl<T: OptionableConvert, E> OptionableConvert for Result<T, E> {
+    fn into_optioned(self) -> Result<T::Optioned, E> {
+        self.map(T::into_optioned)
+    }
+
+    fn try_from_optioned(value: Result<T::Optioned, E>) -> Result<Self, Error> {
+        Ok(match value {
+            Ok(value) => Ok(T::try_from_optioned(value)?),
+            Err(err) => Err(err),
+        })
+    }
+
+    fn merge(&mut self, other: Result<T::Optioned, E>) -> Result<(), Error> {
+        match (self.as_mut(), other) {
+            (Ok(value), Ok(other)) => value.merge(other),
+            (_, other) => {
+                *self = Self::try_from_optioned(other)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Helper macro to generate an impl of `OptionableConvert` for collections without keys.
+/// They are converted element-wise and replaced as a whole when merging.
+macro_rules! impl_convert_collection {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableConvert> OptionableConvert for $t<T> {
+            fn into_optioned(self) -> $t<T::Optioned> {
+                self.into_iter().map(T::into_optioned).collect()
+            }
+
+            fn try_from_optioned(value: $t<T::Optioned>) -> Result<Self, Error> {
+                value.into_iter().map(T::try_from_optioned).collect()
+            }
+
+            fn merge(&mut self, other: $t<T::Optioned>) -> Result<(), Error> {
+                *self = Self::try_from_optioned(other)?;
+                Ok(())
+            }
+        })*
+    };
+}
+
+impl_convert_collection!(Vec, VecDeque, LinkedList);
+
+impl<T: OptionableConvert + Ord> OptionableConvert for BTreeSet<T>
+where
+    T::Optioned: Ord,
+{
+    fn into_optioned(self) -> BTreeSet<T::Optioned> {
+        self.into_iter().map(T::into_optioned).collect()
+    }
+
+    fn try_from_optioned(value: BTreeSet<T::Optioned>) -> Result<Self, Error> {
+        value.into_iter().map(T::try_from_optioned).collect()
+    }
+
+    fn merge(&mut self, other: BTreeSet<T::Optioned>) -> Result<(), Error> {
+        *self = Self::try_from_optioned(other)?;
+        Ok(())
+    }
+}
+
+impl<T: OptionableConvert + Ord> OptionableConvert for BinaryHeap<T>
+where
+    T::Optioned: Ord,
+{
+    fn into_optioned(self) -> BinaryHeap<T::Optioned> {
+        self.into_iter().map(T::into_optioned).collect()
+    }
+
+    fn try_from_optioned(value: BinaryHeap<T::Optioned>) -> Result<Self, Error> {
+        value.into_iter().map(T::try_from_optioned).collect()
+    }
+
+    fn merge(&mut self, other: BinaryHeap<T::Optioned>) -> Result<(), Error> {
+        *self = Self::try_from_optioned(other)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: OptionableConvert + Eq + Hash, S: BuildHasher + Default> OptionableConvert for HashSet<T, S>
+where
+    T::Optioned: Eq + Hash,
+{
+    fn into_optioned(self) -> HashSet<T::Optioned, S> {
+        self.into_iter().map(T::into_optioned).collect()
+    }
+
+    fn try_from_optioned(value: HashSet<T::Optioned, S>) -> Result<Self, Error> {
+        value.into_iter().map(T::try_from_optioned).collect()
+    }
+
+    fn merge(&mut self, other: HashSet<T::Optioned, S>) -> Result<(), Error> {
+        *self = Self::try_from_optioned(other)?;
+        Ok(())
+    }
+}
+
+impl<K: Ord, T: OptionableConvert> OptionableConvert for BTreeMap<K, T> {
+    fn into_optioned(self) -> BTreeMap<K, T::Optioned> {
+        self.into_iter()
+            .map(|(k, v)| (k, v.into_optioned()))
+            .collect()
+    }
+
+    fn try_from_optioned(value: BTreeMap<K, T::Optioned>) -> Result<Self, Error> {
+        value
+            .into_iter()
+            .map(|(k, v)| Ok((k, T::try_from_optioned(v)?)))
+            .collect()
+    }
+
+    fn merge(&mut self, other: BTreeMap<K, T::Optioned>) -> Result<(), Error> {
+        for (k, v) in other {
+            if let Some(value) = self.get_mut(&k) {
+                value.merge(v)?;
+            } else {
+                self.insert(k, T::try_from_optioned(v)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, T: OptionableConvert, S: BuildHasher + Default> OptionableConvert
+    for HashMap<K, T, S>
+{
+    fn into_optioned(self) -> HashMap<K, T::Optioned, S> {
+        self.into_iter()
+            .map(|(k, v)| (k, v.into_optioned()))
+            .collect()
+    }
+
+    fn try_from_optioned(value: HashMap<K, T::Optioned, S>) -> Result<Self, Error> {
+        value
+            .into_iter()
+            .map(|(k, v)| Ok((k, T::try_from_optioned(v)?)))
+            .collect()
+    }
+
+    fn merge(&mut self, other: HashMap<K, T::Optioned, S>) -> Result<(), Error> {
+        for (k, v) in other {
+            if let Some(value) = self.get_mut(&k) {
+                value.merge(v)?;
+            } else {
+                self.insert(k, T::try_from_optioned(v)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Arrays have a fixed length, hence they are merged element-wise.
+impl<T: OptionableConvert, const N: usize> OptionableConvert for [T; N] {
+    fn into_optioned(self) -> [T::Optioned; N] {
+        self.map(T::into_optioned)
+    }
+
+    /// The missing fields of all elements are gathered, prefixed by the element index.
+    fn try_from_optioned(value: [T::Optioned; N]) -> Result<Self, Error> {
+        let mut missing_fields = Vec::new();
+        let values = value
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, value)| {
+                try_from_optioned_value::<T>(value, &i.to_string(), &mut missing_fields)
+            })
+            .collect::<Vec<_>>();
+        result(missing_fields)?;
+        Ok(values
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("length is fixed to N")))
+    }
+
+    /// All elements are merged, the missing fields of all elements are gathered prefixed by the element index.
+    fn merge(&mut self, other: [T::Optioned; N]) -> Result<(), Error> {
+        let mut missing_fields = Vec::new();
+        for (i, (value, other)) in self.iter_mut().zip(other).enumerate() {
+            merge_value(value, other, &i.to_string(), &mut missing_fields);
+        }
+        result(missing_fields)
+    }
+}
+
+impl<T: OptionableConvert> OptionableConvert for Box<T> {
+    fn into_optioned(self) -> Box<T::Optioned> {
+        Box::new((*self).into_optioned())
+    }
+
+    fn try_from_optioned(value: Box<T::Optioned>) -> Result<Self, Error> {
+        Ok(Box::new(T::try_from_optioned(*value)?))
+    }
+
+    fn merge(&mut self, other: Box<T::Optioned>) -> Result<(), Error> {
+        (**self).merge(*other)
+    }
+}
+
+/// Helper macro to generate an impl of `OptionableConvert` for shared pointer.
+/// The inner value is cloned if the pointer is not unique.
+macro_rules! impl_convert_shared_pointer {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableConvert + Clone> OptionableConvert for $t<T>
+        where
+            T::Optioned: Clone,
+        {
+            fn into_optioned(self) -> $t<T::Optioned> {
+                $t::new($t::unwrap_or_clone(self).into_optioned())
+            }
+
+            fn try_from_optioned(value: $t<T::Optioned>) -> Result<Self, Error> {
+                Ok($t::new(T::try_from_optioned($t::unwrap_or_clone(value))?))
+            }
+
+            fn merge(&mut self, other: $t<T::Optioned>) -> Result<(), Error> {
+                $t::make_mut(self).merge($t::unwrap_or_clone(other))
+            }
+        })*
+    };
+}
+
+impl_convert_shared_pointer!(Rc, Arc);
+
+impl<T: OptionableConvert + Unpin> OptionableConvert for Pin<Box<T>>
+where
+    T::Optioned: Unpin,
+{
+    fn into_optioned(self) -> Pin<Box<T::Optioned>> {
+        Box::into_pin(Pin::into_inner(self).into_optioned())
+    }
+
+    fn try_from_optioned(value: Pin<Box<T::Optioned>>) -> Result<Self, Error> {
+        Ok(Box::into_pin(Box::try_from_optioned(Pin::into_inner(
+            value,
+        ))?))
+    }
+
+    fn merge(&mut self, other: Pin<Box<T::Optioned>>) -> Result<(), Error> {
+        self.as_mut().get_mut().merge(*Pin::into_inner(other))
+    }
+}
+
+/// Helper macro to generate an impl of `OptionableConvert` for cells and locks.
+/// The `$into_inner` expression maps the value of the `into_inner` and `get_mut` methods to the inner value.
+macro_rules! impl_convert_cell {
+    ($($t:ident: $into_inner:expr),* $(,)?) => {
+        $(impl<T: OptionableConvert> OptionableConvert for $t<T> {
+            fn into_optioned(self) -> $t<T::Optioned> {
+                $t::new($into_inner(self.into_inner()).into_optioned())
+            }
+
+            fn try_from_optioned(value: $t<T::Optioned>) -> Result<Self, Error> {
+                Ok($t::new(T::try_from_optioned($into_inner(value.into_inner()))?))
+            }
+
+            fn merge(&mut self, other: $t<T::Optioned>) -> Result<(), Error> {
+                $into_inner(self.get_mut()).merge($into_inner(other.into_inner()))
+            }
+        })*
+    };
+}
+
+impl_convert_cell!(
+    Cell: core::convert::identity,
+    RefCell: core::convert::identity,
+);
+#[cfg(feature = "std")]
+impl_convert_cell!(Mutex: unpoison, RwLock: unpoison);
+
+#[cfg(feature = "std")]
+/// A poisoned lock does not prevent us from taking the value as there is no invariant we rely on.
+fn unpoison<T>(result: LockResult<T>) -> T {
+    result.unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Helper macro to generate an impl of `OptionableConvert` for cells that are initialized at most once.
+macro_rules! impl_convert_once_cell {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableConvert> OptionableConvert for $t<T> {
+            fn into_optioned(self) -> $t<T::Optioned> {
+                self.into_inner()
+                    .map_or_else($t::new, |value| $t::from(value.into_optioned()))
+            }
+
+            fn try_from_optioned(value: $t<T::Optioned>) -> Result<Self, Error> {
+                Ok(match value.into_inner() {
+                    Some(value) => $t::from(T::try_from_optioned(value)?),
+                    None => $t::new(),
+                })
+            }
+
+            fn merge(&mut self, other: $t<T::Optioned>) -> Result<(), Error> {
+                match (self.get_mut(), other.into_inner()) {
+                    (_, None) => {}
+                    (Some(value), Some(other)) => value.merge(other)?,
+                    (None, Some(other)) => *self = $t::from(T::try_from_optioned(other)?),
+                }
+                Ok(())
+            }
+        })*
+    };
+}
+
+impl_convert_once_cell!(OnceCell);
+#[cfg(feature = "std")]
+impl_convert_once_cell!(OnceLock);
+
+/// Helper macro to generate an impl of `OptionableConvert` for newtype wrapper around a single value.
+macro_rules! impl_convert_newtype {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableConvert> OptionableConvert for $t<T> {
+            fn into_optioned(self) -> $t<T::Optioned> {
+                $t(self.0.into_optioned())
+            }
+
+            fn try_from_optioned(value: $t<T::Optioned>) -> Result<Self, Error> {
+                Ok($t(T::try_from_optioned(value.0)?))
+            }
+
+            fn merge(&mut self, other: $t<T::Optioned>) -> Result<(), Error> {
+                self.0.merge(other.0)
+            }
+        })*
+    };
+}
+
+impl_convert_newtype!(Reverse, Wrapping, Saturating);
+
+// Ranges are values on their own and hence replaced as a whole.
+impl<T: OptionableConvert> OptionableConvert for Range<T> {
+    fn into_optioned(self) -> Range<T::Optioned> {
+        self.start.into_optioned()..self.end.into_optioned()
+    }
+
+    fn try_from_optioned(value: Range<T::Optioned>) -> Result<Self, Error> {
+        Ok(T::try_from_optioned(value.start)?..T::try_from_optioned(value.end)?)
+    }
+
+    fn merge(&mut self, other: Range<T::Optioned>) -> Result<(), Error> {
+        *self = Self::try_from_optioned(other)?;
+        Ok(())
+    }
+}
+
+impl<T: OptionableConvert> OptionableConvert for RangeInclusive<T> {
+    fn into_optioned(self) -> RangeInclusive<T::Optioned> {
+        let (start, end) = self.into_inner();
+        start.into_optioned()..=end.into_optioned()
+    }
+
+    fn try_from_optioned(value: RangeInclusive<T::Optioned>) -> Result<Self, Error> {
+        let (start, end) = value.into_inner();
+        Ok(T::try_from_optioned(start)?..=T::try_from_optioned(end)?)
+    }
+
+    fn merge(&mut self, other: RangeInclusive<T::Optioned>) -> Result<(), Error> {
+        *self = Self::try_from_optioned(other)?;
+        Ok(())
+    }
+}
+
+impl<T: OptionableConvert> OptionableConvert for RangeFrom<T> {
+    fn into_optioned(self) -> RangeFrom<T::Optioned> {
+        self.start.into_optioned()..
+    }
+
+    fn try_from_optioned(value: RangeFrom<T::Optioned>) -> Result<Self, Error> {
+        Ok(T::try_from_optioned(value.start)?..)
+    }
+
+    fn merge(&mut self, other: RangeFrom<T::Optioned>) -> Result<(), Error> {
+        *self = Self::try_from_optioned(other)?;
+        Ok(())
+    }
+}
+
+impl<T: OptionableConvert> OptionableConvert for RangeTo<T> {
+    fn into_optioned(self) -> RangeTo<T::Optioned> {
+        ..self.end.into_optioned()
+    }
+
+    fn try_from_optioned(value: RangeTo<T::Optioned>) -> Result<Self, Error> {
+        Ok(..T::try_from_optioned(value.end)?)
+    }
+
+    fn merge(&mut self, other: RangeTo<T::Optioned>) -> Result<(), Error> {
+        *self = Self::try_from_optioned(other)?;
+        Ok(())
+    }
+}
+
+impl<T: OptionableConvert> OptionableConvert for RangeToInclusive<T> {
+    fn into_optioned(self) -> RangeToInclusive<T::Optioned> {
+        ..=self.end.into_optioned()
+    }
+
+    fn try_from_optioned(value: RangeToInclusive<T::Optioned>) -> Result<Self, Error> {
+        Ok(..=T::try_from_optioned(value.end)?)
+    }
+
+    fn merge(&mut self, other: RangeToInclusive<T::Optioned>) -> Result<(), Error> {
+        *self = Self::try_from_optioned(other)?;
+        Ok(())
+    }
+}
+
+impl<B: ?Sized + ToOwned> OptionableConvert for Cow<'_, B>
+where
+    B::Owned: OptionableConvert,
+{
+    fn into_optioned(self) -> <B::Owned as crate::Optionable>::Optioned {
+        self.into_owned().into_optioned()
+    }
+
+    fn try_from_optioned(value: <B::Owned as crate::Optionable>::Optioned) -> Result<Self, Error> {
+        Ok(Cow::Owned(B::Owned::try_from_optioned(value)?))
+    }
+
+    fn merge(&mut self, other: <B::Owned as crate::Optionable>::Optioned) -> Result<(), Error> {
+        self.to_mut().merge(other)
+    }
+}
+
+impl_convert_self!([T: ?Sized] PhantomData<T>);
@@ -0,0 +1,208 @@
+//! Loading optioned values from environment variables, e.g. as a layer of a [`crate::layered::Layered`] configuration.
+//!
+//! The variable names are derived from the field names in upper case: fields of the top-level struct
+//! are prefixed by the given prefix and `_`, nested fields by the name of their parent and `__`.
+//! Unset variables leave the corresponding fields unset.
+//! ```rust
+//! # use optionable::Optionable;
+//! # use optionable::env::OptionableEnv;
+//! # use std::ffi::OsString;
+//! #[derive(Optionable)]
+//! #[optionable(env)]
+//! struct App {
+//!     name: String,
+//!     spec: Spec,
+//! }
+//!
+//! #[derive(Optionable)]
+//! #[optionable(env)]
+//! struct Spec {
+//!     replicas: u32,
+//! }
+//!
+//! let vars = |var: &str| (var == "APP_SPEC__REPLICAS").then(|| OsString::from("3"));
+//! let app = App::from_env_with("APP", vars).unwrap();
+//! assert_eq!(app.name, None);
+//! assert_eq!(app.spec.unwrap().replicas, Some(3));
+//! ```
+use crate::Optionable;
+use std::ffi::OsString;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Source of the environment variables, returns the value of the given variable if set.
+pub type EnvSource<'a> = &'a dyn Fn(&str) -> Option<OsString>;
+
+/// Extension trait to read optioned values from environment variables. Implemented for the types
+/// with a [`std::str::FromStr`] implementation that resolve to themselves as optioned type and derived for
+/// structs via `#[optionable(env)]`.
+pub trait OptionableEnv: Optionable {
+    /// Reads the optioned value from the variable `var`. Structs are read from the variables of their fields
+    /// instead, which are named by `var` followed by `__` and the field name.
+    /// Returns `None` if no variable is set and records the variables that are invalid in `errors`.
+    fn from_env_optioned(
+        var: &str,
+        env: EnvSource<'_>,
+        errors: &mut Vec<EnvVarError>,
+    ) -> Option<Self::Optioned> {
+        Self::fields_from_env(&format!("{var}__"), env, errors)
+    }
+
+    /// Reads the fields of a struct from the variables named by `prefix` followed by the field name.
+    /// Returns `None` if no variable is set. Types without fields are not read.
+    fn fields_from_env(
+        _prefix: &str,
+        _env: EnvSource<'_>,
+        _errors: &mut Vec<EnvVarError>,
+    ) -> Option<Self::Optioned> {
+        None
+    }
+
+    /// Reads the optioned value from the process environment, the fields from the variables
+    /// named by `prefix`, `_` and the field name, e.g. `APP_SPEC__REPLICAS`.
+    ///
+    /// # Errors
+    /// - If any of the variables can't be parsed or required fields are missing.
+    fn from_env(prefix: &str) -> Result<Self::Optioned, EnvError>
+    where
+        Self::Optioned: Default,
+    {
+        Self::from_env_with(prefix, |var| std::env::var_os(var))
+    }
+
+    /// Reads the optioned value from the variables of the given source, see [`OptionableEnv::from_env`].
+    ///
+    /// # Errors
+    /// - If any of the variables can't be parsed or required fields are missing.
+    fn from_env_with(
+        prefix: &str,
+        env: impl Fn(&str) -> Option<OsString>,
+    ) -> Result<Self::Optioned, EnvError>
+    where
+        Self::Optioned: Default,
+    {
+        let mut errors = vec![];
+        let optioned = Self::fields_from_env(&format!("{prefix}_"), &env, &mut errors);
+        if errors.is_empty() {
+            Ok(optioned.unwrap_or_default())
+        } else {
+            Err(EnvError { errors })
+        }
+    }
+}
+
+/// Helper macro to generate an impl for `OptionableEnv` for types parsed via `FromStr`.
+macro_rules! impl_env_from_str {
+    ($($t:ty),* $(,)?) => {
+        $(impl OptionableEnv for $t {
+            fn from_env_optioned(var: &str, env: EnvSource<'_>, errors: &mut Vec<EnvVarError>) -> Option<Self> {
+                crate::__private::parse_env(var, env, errors)
+            }
+        })*
+    };
+}
+
+impl_env_from_str!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, char, bool, String
+);
+
+impl_env_from_str!(
+    std::num::NonZeroI8,
+    std::num::NonZeroI16,
+    std::num::NonZeroI32,
+    std::num::NonZeroI64,
+    std::num::NonZeroI128,
+    std::num::NonZeroIsize,
+    std::num::NonZeroU8,
+    std::num::NonZeroU16,
+    std::num::NonZeroU32,
+    std::num::NonZeroU64,
+    std::num::NonZeroU128,
+    std::num::NonZeroUsize
+);
+
+impl_env_from_str!(
+    std::net::IpAddr,
+    std::net::Ipv4Addr,
+    std::net::Ipv6Addr,
+    std::net::SocketAddr,
+    std::net::SocketAddrV4,
+    std::net::SocketAddrV6
+);
+
+// Paths and OS strings are taken as is and don't have to be valid unicode.
+impl OptionableEnv for OsString {
+    fn from_env_optioned(
+        var: &str,
+        env: EnvSource<'_>,
+        _errors: &mut Vec<EnvVarError>,
+    ) -> Option<Self> {
+        env(var)
+    }
+}
+
+impl OptionableEnv for PathBuf {
+    fn from_env_optioned(
+        var: &str,
+        env: EnvSource<'_>,
+        _errors: &mut Vec<EnvVarError>,
+    ) -> Option<Self> {
+        env(var).map(PathBuf::from)
+    }
+}
+
+/// Helper macro to generate an impl for `OptionableEnv` for smart pointers reading the pointed to value.
+macro_rules! impl_env_pointer {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableEnv> OptionableEnv for $t<T> {
+            fn from_env_optioned(var: &str, env: EnvSource<'_>, errors: &mut Vec<EnvVarError>) -> Option<$t<T::Optioned>> {
+                T::from_env_optioned(var, env, errors).map($t::new)
+            }
+
+            fn fields_from_env(prefix: &str, env: EnvSource<'_>, errors: &mut Vec<EnvVarError>) -> Option<$t<T::Optioned>> {
+                T::fields_from_env(prefix, env, errors).map($t::new)
+            }
+        })*
+    };
+}
+
+impl_env_pointer!(Box, Rc, Arc);
+
+/// Error for environment variables that can't be read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvError {
+    /// The invalid variables.
+    pub errors: Vec<EnvVarError>,
+}
+
+impl Display for EnvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid environment variables: ")?;
+        for (i, err) in self.errors.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{err}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+/// An environment variable that can't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvVarError {
+    /// The name of the variable.
+    pub var: String,
+    /// The reason why the variable can't be read.
+    pub message: String,
+}
+
+impl Display for EnvVarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.var, self.message)
+    }
+}
@@ -0,0 +1,305 @@
+//! Field masks (like the protobuf [`FieldMask`](https://protobuf.dev/reference/protobuf/google.protobuf/#field-mask))
+//! selecting (sub-)fields of a type via their field paths, see [`OptionableFieldPaths`].
+//!
+//! A mask can be derived from the fields set by an optioned value, validated against the structure of a type
+//! and used to project a full value onto the selected fields:
+//! ```rust
+//! # use optionable::Optionable;
+//! # use optionable::field_mask::FieldMask;
+//! #[derive(Optionable, Debug, PartialEq)]
+//! #[optionable(derive(Debug, PartialEq))]
+//! struct Address {
+//!     street_name: String,
+//!     number: u8,
+//! }
+//!
+//! let mask = FieldMask::new(["number"]);
+//! mask.validate::<Address>().unwrap();
+//! let address = Address { street_name: "Main Street".to_owned(), number: 1 };
+//! let projected = mask.project(address).unwrap();
+//! assert_eq!(projected, AddressOpt { street_name: None, number: Some(1) });
+//! assert_eq!(projected.field_mask(), mask);
+//!
+//! let err = FieldMask::new(["number.value"]).validate::<Address>().unwrap_err();
+//! assert_eq!(err.invalid_paths, vec!["number.value"]);
+//! ```
+use crate::{OptionableConvert, OptionableFieldPaths};
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+/// A set of field paths selecting (sub-)fields of a type.
+///
+/// The paths use the format of [`OptionableFieldPaths`]: nested fields are separated by dots and map entries
+/// (as well as array elements) are appended as `[<key>]`, e.g. `spec.template.metadata.labels[app]`.
+/// Backslashes and closing brackets in keys are escaped with a backslash, e.g. `labels[a\\]b]` for the key `a]b`.
+/// A path selects the addressed value with all its (sub-)fields, the empty path selects the whole value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldMask {
+    /// The field paths of the mask.
+    pub paths: Vec<String>,
+}
+
+impl FieldMask {
+    /// Creates a mask from the given field paths.
+    pub fn new(paths: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            paths: paths.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Returns the mask of the (sub-)fields set by the optioned value of `T`.
+    #[must_use]
+    pub fn from_optioned<T: OptionableFieldPaths + ?Sized>(optioned: &T::Optioned) -> Self {
+        Self {
+            paths: T::set_field_paths(optioned),
+        }
+    }
+
+    /// Checks that all paths are well-formed and address (sub-)fields of `T`.
+    ///
+    /// # Errors
+    /// - If any of the paths is malformed or does not address a (sub-)field of `T`.
+    pub fn validate<T: OptionableFieldPaths + ?Sized>(&self) -> Result<(), FieldMaskError> {
+        let invalid_paths = self
+            .paths
+            .iter()
+            .filter(|path| parse_path(path).is_none_or(|segments| !T::is_field_path(&segments)))
+            .cloned()
+            .collect::<Vec<_>>();
+        if invalid_paths.is_empty() {
+            Ok(())
+        } else {
+            Err(FieldMaskError { invalid_paths })
+        }
+    }
+
+    /// Returns the tree of the selected (sub-)fields.
+    ///
+    /// # Errors
+    /// - If any of the paths is malformed.
+    pub fn tree(&self) -> Result<FieldMaskTree, FieldMaskError> {
+        let mut tree = FieldMaskTree::default();
+        let mut invalid_paths = vec![];
+        for path in &self.paths {
+            match parse_path(path) {
+                Some(segments) => tree.insert(segments),
+                None => invalid_paths.push(path.clone()),
+            }
+        }
+        if invalid_paths.is_empty() {
+            Ok(tree)
+        } else {
+            Err(FieldMaskError { invalid_paths })
+        }
+    }
+
+    /// Projects the value onto the selected (sub-)fields, i.e. returns its optioned value with only
+    /// the selected (sub-)fields set. Required fields are always kept.
+    ///
+    /// # Errors
+    /// - If any of the paths is malformed or does not address a (sub-)field of `T`.
+    pub fn project<T: OptionableConvert + OptionableFieldPaths>(
+        &self,
+        value: T,
+    ) -> Result<T::Optioned, FieldMaskError> {
+        self.validate::<T>()?;
+        let tree = self.tree()?;
+        let mut optioned = value.into_optioned();
+        if !tree.is_whole() {
+            T::mask_optioned(&mut optioned, &tree);
+        }
+        Ok(optioned)
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for FieldMask {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+impl Display for FieldMask {
+    /// Formats the mask like the JSON representation of the protobuf `FieldMask`, i.e. comma-separated.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.paths.join(","))
+    }
+}
+
+/// Error for field masks with malformed paths or paths not addressing any (sub-)field of the type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldMaskError {
+    /// The invalid paths of the mask.
+    pub invalid_paths: Vec<String>,
+}
+
+impl Display for FieldMaskError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid field paths: {}", self.invalid_paths.join(", "))
+    }
+}
+
+impl core::error::Error for FieldMaskError {}
+
+/// A segment of a field path.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PathSegment {
+    /// A (sub-)field or enum variant, e.g. `spec`.
+    Field(String),
+    /// A map entry or array element, e.g. `[app]`.
+    Key(String),
+}
+
+/// Splits the path into its segments. Returns `None` if the path is malformed.
+/// The empty path has no segments.
+fn parse_path(path: &str) -> Option<Vec<PathSegment>> {
+    let mut segments = vec![];
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(key) = rest.strip_prefix('[') {
+            let (key, end) = parse_key(key)?;
+            segments.push(PathSegment::Key(key));
+            rest = end;
+        } else {
+            // fields are separated by dots, except for the first one
+            if !segments.is_empty() {
+                rest = rest.strip_prefix('.')?;
+            }
+            let end = rest.find(['.', '[', ']']).unwrap_or(rest.len());
+            if end == 0 {
+                return None;
+            }
+            segments.push(PathSegment::Field(rest[..end].to_owned()));
+            rest = &rest[end..];
+        }
+    }
+    Some(segments)
+}
+
+/// Splits off the (unescaped) key up to the closing bracket and returns it together with the rest of the path.
+/// Returns `None` if the key is not closed.
+fn parse_key(path: &str) -> Option<(String, &str)> {
+    let mut key = String::new();
+    let mut chars = path.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => key.push(chars.next()?.1),
+            ']' => return Some((key, &path[i + 1..])),
+            _ => key.push(c),
+        }
+    }
+    None
+}
+
+/// The (sub-)fields selected by a [`FieldMask`] as a tree of path segments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldMaskTree {
+    whole: bool,
+    children: BTreeMap<PathSegment, FieldMaskTree>,
+}
+
+/// The tree selecting nothing, e.g. for the fields of a non-selected enum variant.
+pub(crate) static EMPTY: FieldMaskTree = FieldMaskTree {
+    whole: false,
+    children: BTreeMap::new(),
+};
+
+impl FieldMaskTree {
+    /// Whether the value is selected with all its (sub-)fields.
+    #[must_use]
+    pub fn is_whole(&self) -> bool {
+        self.whole
+    }
+
+    /// Whether nothing is selected.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.whole && self.children.is_empty()
+    }
+
+    /// Returns the tree of the selected sub-field `name`, `None` if it is not selected.
+    #[must_use]
+    pub fn field(&self, name: &str) -> Option<&Self> {
+        self.child(&PathSegment::Field(name.to_owned()))
+    }
+
+    /// Returns the tree of the selected map entry `key`, `None` if it is not selected.
+    #[must_use]
+    pub fn key(&self, key: &str) -> Option<&Self> {
+        self.child(&PathSegment::Key(key.to_owned()))
+    }
+
+    fn child(&self, segment: &PathSegment) -> Option<&Self> {
+        if self.whole {
+            Some(self)
+        } else {
+            self.children.get(segment)
+        }
+    }
+
+    fn insert(&mut self, segments: Vec<PathSegment>) {
+        let mut tree = self;
+        for segment in segments {
+            if tree.whole {
+                return;
+            }
+            tree = tree.children.entry(segment).or_default();
+        }
+        tree.whole = true;
+        tree.children.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_path, PathSegment};
+    use alloc::borrow::ToOwned;
+    use alloc::vec;
+
+    #[test]
+    /// Check that paths are split into fields and keys and malformed paths are rejected.
+    fn parse() {
+        let field = |name: &str| PathSegment::Field(name.to_owned());
+        let key = |key: &str| PathSegment::Key(key.to_owned());
+        assert_eq!(parse_path(""), Some(vec![]));
+        assert_eq!(
+            parse_path("spec.labels[app.kubernetes.io/name]"),
+            Some(vec![
+                field("spec"),
+                field("labels"),
+                key("app.kubernetes.io/name")
+            ])
+        );
+        assert_eq!(
+            parse_path("[a][0].b"),
+            Some(vec![key("a"), key("0"), field("b")])
+        );
+        assert_eq!(
+            parse_path(r"labels[a\]b\\][c]"),
+            Some(vec![field("labels"), key(r"a]b\"), key("c")])
+        );
+        for path in ["a..b", ".a", "a.", "a[b", "a]", "a[b]c", r"a[b\]"] {
+            assert_eq!(parse_path(path), None, "{path}");
+        }
+    }
+
+    #[test]
+    /// Check that keys containing brackets or backslashes are escaped in the paths and select the entry.
+    fn escaped_keys() {
+        use super::FieldMask;
+        use crate::OptionableFieldPaths;
+        use alloc::collections::BTreeMap;
+        use alloc::string::String;
+
+        let key = r"a]b\[c".to_owned();
+        let value = BTreeMap::from([(key.clone(), 1), ("d".to_owned(), 2)]);
+        let paths = <BTreeMap<String, i32> as OptionableFieldPaths>::set_field_paths(&value);
+        assert_eq!(paths, vec![r"[a\]b\\[c]".to_owned(), "[d]".to_owned()]);
+
+        let mask = FieldMask::new([paths[0].clone()]);
+        assert_eq!(mask.project(value), Ok(BTreeMap::from([(key, 1)])));
+    }
+}
@@ -0,0 +1,352 @@
+//! [`OptionableFieldPaths`] implementations for the std types that are not their own optioned type.
+use crate::__private::{entry_path, mask_value, set_value_path};
+use crate::field_mask::{FieldMaskTree, PathSegment};
+use crate::{impl_field_paths_whole, OptionableFieldPaths};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::rc::{Rc, Weak as RcWeak};
+use alloc::string::{String, ToString};
+use alloc::sync::{Arc, Weak as ArcWeak};
+use alloc::vec::Vec;
+use core::cell::{Cell, OnceCell, RefCell};
+use core::cmp::Reverse;
+use core::fmt::Display;
+use core::marker::PhantomData;
+use core::num::{Saturating, Wrapping};
+use core::ops::{Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive};
+use core::pin::Pin;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, OnceLock, RwLock};
+
+impl<T: OptionableFieldPaths> OptionableFieldPaths for Option<T> {
+    fn set_field_paths_optioned(
+        optioned: &Option<T::Optioned>,
+        prefix: &str,
+        paths: &mut Vec<String>,
+    ) {
+        if let Some(optioned) = optioned {
+            T::set_field_paths_optioned(optioned, prefix, paths);
+        }
+    }
+
+    /// Unsets the value if nothing is selected, e.g. for unselected array elements.
+    fn mask_optioned(optioned: &mut Option<T::Optioned>, mask: &FieldMaskTree) {
+        if mask.is_empty() {
+            *optioned = None;
+        } else if let Some(optioned) = optioned {
+            T::mask_optioned(optioned, mask);
+        }
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        T::is_field_path(segments)
+    }
+}
+
+impl<T: OptionableFieldPaths, E> OptionableFieldPaths for Result<T, E> {
+    fn set_field_paths_optioned(
+        optioned: &Result<T::Optioned, E>,
+        prefix: &str,
+        paths: &mut Vec<String>,
+    ) {
+        match optioned {
+            Ok(optioned) => T::set_field_paths_optioned(optioned, prefix, paths),
+            Err(_) => paths.push(prefix.to_owned()),
+        }
+    }
+
+    fn mask_optioned(optioned: &mut Result<T::Optioned, E>, mask: &FieldMaskTree) {
+        if let Ok(optioned) = optioned {
+            T::mask_optioned(optioned, mask);
+        }
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        T::is_field_path(segments)
+    }
+}
+
+// The referenced optioned value cannot be masked in place and is hence kept.
+impl<T: OptionableFieldPaths> OptionableFieldPaths for &T {
+    fn set_field_paths_optioned(optioned: &&T::Optioned, prefix: &str, paths: &mut Vec<String>) {
+        T::set_field_paths_optioned(optioned, prefix, paths);
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        T::is_field_path(segments)
+    }
+}
+
+/// Helper macro to generate an impl of `OptionableFieldPaths` for pointers and newtype wrappers
+/// that are merged in place. The first expression maps the bound optioned wrapper to a reference of the inner value,
+/// the second one to an optional mutable reference (`None` if the inner value cannot be masked in place).
+macro_rules! impl_field_paths_forward {
+    ($($t:ident($optioned:ident => $inner:expr, $inner_mut:expr)),* $(,)?) => {
+        $(impl<T: OptionableFieldPaths> OptionableFieldPaths for $t<T> {
+            fn set_field_paths_optioned($optioned: &$t<T::Optioned>, prefix: &str, paths: &mut Vec<String>) {
+                T::set_field_paths_optioned($inner, prefix, paths);
+            }
+
+            fn mask_optioned($optioned: &mut $t<T::Optioned>, mask: &FieldMaskTree) {
+                if let Some(optioned) = $inner_mut {
+                    T::mask_optioned(optioned, mask);
+                }
+            }
+
+            fn is_field_path(segments: &[PathSegment]) -> bool {
+                T::is_field_path(segments)
+            }
+        })*
+    };
+}
+
+impl_field_paths_forward!(
+    Box(optioned => &**optioned, Some(&mut **optioned)),
+    Rc(optioned => &**optioned, Rc::get_mut(optioned)),
+    Arc(optioned => &**optioned, Arc::get_mut(optioned)),
+    Reverse(optioned => &optioned.0, Some(&mut optioned.0)),
+    Wrapping(optioned => &optioned.0, Some(&mut optioned.0)),
+    Saturating(optioned => &optioned.0, Some(&mut optioned.0)),
+);
+
+/// Helper macro to generate an impl of `OptionableFieldPaths` for cells that are initialized at most once.
+/// An uninitialized cell sets nothing.
+macro_rules! impl_field_paths_once_cell {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableFieldPaths> OptionableFieldPaths for $t<T> {
+            fn set_field_paths_optioned(optioned: &$t<T::Optioned>, prefix: &str, paths: &mut Vec<String>) {
+                if let Some(optioned) = optioned.get() {
+                    T::set_field_paths_optioned(optioned, prefix, paths);
+                }
+            }
+
+            fn mask_optioned(optioned: &mut $t<T::Optioned>, mask: &FieldMaskTree) {
+                if let Some(optioned) = optioned.get_mut() {
+                    T::mask_optioned(optioned, mask);
+                }
+            }
+
+            fn is_field_path(segments: &[PathSegment]) -> bool {
+                T::is_field_path(segments)
+            }
+        })*
+    };
+}
+
+impl_field_paths_once_cell!(OnceCell);
+#[cfg(feature = "std")]
+impl_field_paths_once_cell!(OnceLock);
+
+impl<B: ?Sized + ToOwned> OptionableFieldPaths for Cow<'_, B>
+where
+    B::Owned: OptionableFieldPaths,
+{
+    fn set_field_paths_optioned(
+        optioned: &<B::Owned as crate::Optionable>::Optioned,
+        prefix: &str,
+        paths: &mut Vec<String>,
+    ) {
+        B::Owned::set_field_paths_optioned(optioned, prefix, paths);
+    }
+
+    fn mask_optioned(
+        optioned: &mut <B::Owned as crate::Optionable>::Optioned,
+        mask: &FieldMaskTree,
+    ) {
+        B::Owned::mask_optioned(optioned, mask);
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        B::Owned::is_field_path(segments)
+    }
+}
+
+// Arrays have a fixed length and are merged element-wise, hence the elements are addressed by their index.
+impl<T: OptionableFieldPaths, const N: usize> OptionableFieldPaths for [T; N] {
+    fn set_field_paths_optioned(
+        optioned: &[T::Optioned; N],
+        prefix: &str,
+        paths: &mut Vec<String>,
+    ) {
+        for (i, optioned) in optioned.iter().enumerate() {
+            T::set_field_paths_optioned(optioned, &entry_path(prefix, i), paths);
+        }
+    }
+
+    fn mask_optioned(optioned: &mut [T::Optioned; N], mask: &FieldMaskTree) {
+        for (i, optioned) in optioned.iter_mut().enumerate() {
+            mask_value::<T>(optioned, mask.key(&i.to_string()));
+        }
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        match segments.split_first() {
+            None => true,
+            Some((PathSegment::Key(i), rest)) => {
+                i.parse::<usize>().is_ok_and(|i| i < N) && T::is_field_path(rest)
+            }
+            Some(_) => false,
+        }
+    }
+}
+
+impl<K: Ord + Display, T: OptionableFieldPaths> OptionableFieldPaths for BTreeMap<K, T> {
+    fn set_field_paths_optioned(
+        optioned: &BTreeMap<K, T::Optioned>,
+        prefix: &str,
+        paths: &mut Vec<String>,
+    ) {
+        set_entry_paths::<K, T>(optioned, prefix, paths);
+    }
+
+    fn mask_optioned(optioned: &mut BTreeMap<K, T::Optioned>, mask: &FieldMaskTree) {
+        optioned.retain(|k, v| mask_entry::<T>(k, v, mask));
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        is_entry_path::<T>(segments)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Display, T: OptionableFieldPaths, S> OptionableFieldPaths for HashMap<K, T, S> {
+    fn set_field_paths_optioned(
+        optioned: &HashMap<K, T::Optioned, S>,
+        prefix: &str,
+        paths: &mut Vec<String>,
+    ) {
+        set_entry_paths::<K, T>(optioned, prefix, paths);
+    }
+
+    fn mask_optioned(optioned: &mut HashMap<K, T::Optioned, S>, mask: &FieldMaskTree) {
+        optioned.retain(|k, v| mask_entry::<T>(k, v, mask));
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        is_entry_path::<T>(segments)
+    }
+}
+
+/// Records the set (sub-)fields of the map entries. An empty map is recorded as set as a whole
+/// unless it is the root value.
+pub(crate) fn set_entry_paths<'a, K: Display + 'a, T: OptionableFieldPaths>(
+    entries: impl IntoIterator<Item = (&'a K, &'a T::Optioned)>,
+    prefix: &str,
+    paths: &mut Vec<String>,
+) where
+    T::Optioned: 'a,
+{
+    let len = paths.len();
+    for (k, v) in entries {
+        T::set_field_paths_optioned(v, &entry_path(prefix, k), paths);
+    }
+    set_value_path(prefix, len, paths);
+}
+
+/// Masks the map entry and returns whether it is selected at all, i.e. whether it is kept.
+pub(crate) fn mask_entry<T: OptionableFieldPaths + ?Sized>(
+    key: impl Display,
+    optioned: &mut T::Optioned,
+    mask: &FieldMaskTree,
+) -> bool {
+    let Some(mask) = mask.key(&key.to_string()) else {
+        return false;
+    };
+    if !mask.is_whole() {
+        T::mask_optioned(optioned, mask);
+    }
+    true
+}
+
+/// Whether the path addresses the map itself or a (sub-)field of one of its entries.
+/// Any key is accepted as the entries are only known at runtime.
+pub(crate) fn is_entry_path<T: OptionableFieldPaths + ?Sized>(segments: &[PathSegment]) -> bool {
+    match segments.split_first() {
+        None => true,
+        Some((PathSegment::Key(_), rest)) => T::is_field_path(rest),
+        Some(_) => false,
+    }
+}
+
+// Collections without keys, ranges as well as cells and locks are replaced as a whole.
+impl_field_paths_whole!([T: OptionableFieldPaths] Vec<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] VecDeque<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] LinkedList<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] BTreeSet<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] BinaryHeap<T>);
+#[cfg(feature = "std")]
+impl_field_paths_whole!([T: OptionableFieldPaths, S] HashSet<T, S>);
+impl_field_paths_whole!([T: OptionableFieldPaths] Range<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] RangeInclusive<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] RangeFrom<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] RangeTo<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] RangeToInclusive<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] Cell<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] RefCell<T>);
+#[cfg(feature = "std")]
+impl_field_paths_whole!([T: OptionableFieldPaths] Mutex<T>);
+#[cfg(feature = "std")]
+impl_field_paths_whole!([T: OptionableFieldPaths] RwLock<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] Pin<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] RcWeak<T>);
+impl_field_paths_whole!([T: OptionableFieldPaths] ArcWeak<T>);
+impl_field_paths_whole!([T: ?Sized] PhantomData<T>);
+
+#[cfg(test)]
+mod tests {
+    use crate::field_mask::FieldMask;
+    use crate::OptionableFieldPaths;
+    use alloc::collections::BTreeMap;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    /// Check that map entries are recorded with their key and nested maps recursively.
+    fn map_entries() {
+        let optioned = BTreeMap::from([
+            ("a", BTreeMap::from([("b", 1), ("c", 2)])),
+            ("d", BTreeMap::new()),
+        ]);
+        assert_eq!(
+            <BTreeMap<&str, BTreeMap<&str, i32>>>::set_field_paths(&optioned),
+            vec!["[a][b]", "[a][c]", "[d]"]
+        );
+    }
+
+    #[test]
+    /// Check that values without inner structure and collections without keys are set as a whole.
+    fn whole_values() {
+        assert_eq!(
+            <Option<Vec<i32>>>::set_field_paths(&Some(vec![1])),
+            vec![""]
+        );
+        assert!(<Option<i32>>::set_field_paths(&None).is_empty());
+        assert_eq!(<[i32; 2]>::set_field_paths(&[1, 2]), vec!["[0]", "[1]"]);
+    }
+
+    #[test]
+    /// Check that unselected map entries are removed and array elements are masked per index.
+    fn mask_entries() {
+        let optioned = BTreeMap::from([
+            ("a", [Some(1), Some(2)]),
+            ("b", [Some(3), Some(4)]),
+            ("c", [Some(5), Some(6)]),
+        ]);
+        let mut masked = optioned.clone();
+        let mask = FieldMask::new(["[a][1]", "[b]"]).tree().unwrap();
+        <BTreeMap<&str, [Option<i32>; 2]>>::mask_optioned(&mut masked, &mask);
+        assert_eq!(
+            masked,
+            BTreeMap::from([("a", [None, Some(2)]), ("b", [Some(3), Some(4)])])
+        );
+        assert!(FieldMask::new(["[x][1]"])
+            .validate::<BTreeMap<&str, [Option<i32>; 2]>>()
+            .is_ok());
+        assert!(FieldMask::new(["[x][2]", "x"])
+            .validate::<BTreeMap<&str, [Option<i32>; 2]>>()
+            .is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! The Kubernetes [`FieldsV1`](https://kubernetes.io/docs/reference/using-api/server-side-apply/#field-management)
+//! representation of the (sub-)fields set by optioned values, as used by the `managedFields` of server-side apply.
+//!
+//! Fields are represented as `f:<name>` entries, map entries as `f:<key>`, elements of lists with
+//! `list_type = "map"` as `k:<key fields as JSON>` (with a `.` entry for the element itself) and elements of lists
+//! with `list_type = "set"` as `v:<value as JSON>`. All other lists are managed as a whole.
+//! ```rust
+//! # use optionable::Optionable;
+//! # use optionable::fields_v1::FieldsV1;
+//! # use serde::Serialize;
+//! #[derive(Optionable, Serialize)]
+//! #[optionable(fields_v1, derive(Serialize))]
+//! #[serde(rename_all = "camelCase")]
+//! struct PodSpec {
+//!     service_account: String,
+//!     #[optionable(list_type = "map", list_map_key = "name")]
+//!     containers: Vec<Container>,
+//! }
+//!
+//! #[derive(Optionable, Serialize)]
+//! #[optionable(fields_v1, derive(Serialize))]
+//! struct Container {
+//!     name: String,
+//!     image: String,
+//! }
+//!
+//! let spec = PodSpecOpt {
+//!     service_account: None,
+//!     containers: Some(vec![ContainerOpt { name: Some("nginx".to_owned()), image: Some("nginx:1.29".to_owned()) }]),
+//! };
+//! let fields = spec.fields_v1();
+//! assert_eq!(
+//!     serde_json::to_string(&fields).unwrap(),
+//!     r#"{"f:containers":{"k:{\"name\":\"nginx\"}":{".":{},"f:image":{},"f:name":{}}}}"#
+//! );
+//! assert_eq!(
+//!     fields.field_paths::<PodSpec>().unwrap().into_iter().collect::<Vec<_>>(),
+//!     vec![
+//!         r#"containers[k:{"name":"nginx"}]"#,
+//!         r#"containers[k:{"name":"nginx"}].image"#,
+//!         r#"containers[k:{"name":"nginx"}].name"#,
+//!     ]
+//! );
+//! ```
+use crate::__private::{entry_path, field_path};
+use crate::field_mask::PathSegment;
+use crate::{Optionable, OptionableFieldPaths};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::format;
+use alloc::rc::{Rc, Weak as RcWeak};
+use alloc::string::String;
+use alloc::sync::{Arc, Weak as ArcWeak};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::{Cell, OnceCell, RefCell};
+use core::cmp::Reverse;
+use core::fmt::{Display, Formatter};
+use core::marker::PhantomData;
+use core::num::{Saturating, Wrapping};
+use core::ops::{Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive};
+use core::pin::Pin;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+/// Extension of the [`trait@Optionable`] trait to compute the `FieldsV1` of the (sub-)fields an optioned value sets.
+/// Derived via `#[optionable(fields_v1)]`, the list and map types of the fields are configured via
+/// the `list_type`, `list_map_key` and `map_type` field attributes.
+pub trait OptionableFieldsV1: Optionable {
+    /// Inserts the entries of the (sub-)fields set by the optioned value into `fields`, the `FieldsV1` object
+    /// of the value itself. Values without inner structure and collections without keys are managed as a whole,
+    /// hence the default inserts nothing.
+    fn fields_v1_optioned(optioned: &Self::Optioned, fields: &mut Map<String, Value>) {
+        let _ = (optioned, fields);
+    }
+
+    /// Returns the `FieldsV1` of the (sub-)fields set by the optioned value.
+    fn fields_v1(optioned: &Self::Optioned) -> FieldsV1 {
+        let mut fields = Map::new();
+        Self::fields_v1_optioned(optioned, &mut fields);
+        FieldsV1(fields)
+    }
+}
+
+/// A set of (sub-)fields in the Kubernetes `FieldsV1` format, e.g. `{"f:spec":{"f:replicas":{}}}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FieldsV1(pub Map<String, Value>);
+
+impl FieldsV1 {
+    /// Returns the `FieldsV1` of the (sub-)fields set by the optioned value of `T`.
+    #[must_use]
+    pub fn from_optioned<T: OptionableFieldsV1 + ?Sized>(optioned: &T::Optioned) -> Self {
+        T::fields_v1(optioned)
+    }
+
+    /// Returns the paths of the contained (sub-)fields in the format of [`OptionableFieldPaths`].
+    /// `f:` entries are parsed as fields if `T` has such a field and as map entries otherwise, the elements of
+    /// list maps and sets are appended as `[k:<key fields>]` and `[v:<value>]`.
+    /// Elements with a `.` entry are contained themselves.
+    ///
+    /// # Errors
+    /// - If any of the entries is not an object or has an unknown prefix.
+    pub fn field_paths<T: OptionableFieldPaths + ?Sized>(
+        &self,
+    ) -> Result<BTreeSet<String>, FieldsV1Error> {
+        let mut paths = BTreeSet::new();
+        let mut invalid_entries = vec![];
+        collect_paths::<T>(
+            &self.0,
+            "",
+            &mut vec![],
+            &mut paths,
+            &mut invalid_entries,
+        );
+        if invalid_entries.is_empty() {
+            Ok(paths)
+        } else {
+            Err(FieldsV1Error { invalid_entries })
+        }
+    }
+}
+
+/// Records the paths of the entries of `fields` below `prefix`, `segments` being the segments of `prefix`.
+/// Entries without children are leaves.
+fn collect_paths<T: OptionableFieldPaths + ?Sized>(
+    fields: &Map<String, Value>,
+    prefix: &str,
+    segments: &mut Vec<PathSegment>,
+    paths: &mut BTreeSet<String>,
+    invalid_entries: &mut Vec<String>,
+) {
+    for (key, children) in fields {
+        let Value::Object(children) = children else {
+            invalid_entries.push(entry_path(prefix, key));
+            continue;
+        };
+        if key == "." {
+            paths.insert(prefix.to_owned());
+            continue;
+        }
+        let (segment, path) = match key.split_once(':') {
+            Some(("f", name)) if is_map_key::<T>(segments, name) => {
+                (PathSegment::Key(name.to_owned()), entry_path(prefix, name))
+            }
+            Some(("f", name)) => (PathSegment::Field(name.to_owned()), field_path(prefix, name)),
+            Some(("k" | "v", _)) => (PathSegment::Key(key.clone()), entry_path(prefix, key)),
+            _ => {
+                invalid_entries.push(entry_path(prefix, key));
+                continue;
+            }
+        };
+        if children.is_empty() {
+            paths.insert(path);
+            continue;
+        }
+        segments.push(segment);
+        collect_paths::<T>(children, &path, segments, paths, invalid_entries);
+        segments.pop();
+    }
+}
+
+/// Whether the `f:` entry `name` below the path of the `segments` is a map entry, i.e. whether `T` has a map
+/// but no field there. The elements of list maps and sets are not addressable by field paths, hence entries
+/// below them are fields.
+fn is_map_key<T: OptionableFieldPaths + ?Sized>(segments: &mut Vec<PathSegment>, name: &str) -> bool {
+    segments.push(PathSegment::Key(name.to_owned()));
+    let key = T::is_field_path(segments);
+    segments.pop();
+    segments.push(PathSegment::Field(name.to_owned()));
+    let field = T::is_field_path(segments);
+    segments.pop();
+    key && !field
+}
+
+/// Error for `FieldsV1` with malformed entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldsV1Error {
+    /// The malformed entries, appended as `[<entry>]` to the path of their parent.
+    pub invalid_entries: Vec<String>,
+}
+
+impl Display for FieldsV1Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "invalid FieldsV1 entries: {}",
+            self.invalid_entries.join(", ")
+        )
+    }
+}
+
+impl core::error::Error for FieldsV1Error {}
+
+/// Inserts the `f:<key>` entries of the set (sub-)fields of the map entries.
+pub(crate) fn map_fields_v1<'a, K: Display + 'a, T: OptionableFieldsV1 + ?Sized>(
+    entries: impl IntoIterator<Item = (&'a K, &'a T::Optioned)>,
+    fields: &mut Map<String, Value>,
+) where
+    T::Optioned: 'a,
+{
+    for (k, v) in entries {
+        T::fields_v1_optioned(v, entry(fields, format!("f:{k}")));
+    }
+}
+
+/// Returns the `FieldsV1` object of the given entry, inserting an empty one if it is missing.
+pub(crate) fn entry(fields: &mut Map<String, Value>, key: String) -> &mut Map<String, Value> {
+    let value = fields
+        .entry(key)
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+    value.as_object_mut().expect("entry is an object")
+}
+
+impl<T: OptionableFieldsV1> OptionableFieldsV1 for Option<T> {
+    fn fields_v1_optioned(optioned: &Option<T::Optioned>, fields: &mut Map<String, Value>) {
+        if let Some(optioned) = optioned {
+            T::fields_v1_optioned(optioned, fields);
+        }
+    }
+}
+
+impl<T: OptionableFieldsV1, E> OptionableFieldsV1 for Result<T, E> {
+    fn fields_v1_optioned(optioned: &Result<T::Optioned, E>, fields: &mut Map<String, Value>) {
+        if let Ok(optioned) = optioned {
+            T::fields_v1_optioned(optioned, fields);
+        }
+    }
+}
+
+impl<T: OptionableFieldsV1> OptionableFieldsV1 for &T {
+    fn fields_v1_optioned(optioned: &&T::Optioned, fields: &mut Map<String, Value>) {
+        T::fields_v1_optioned(optioned, fields);
+    }
+}
+
+/// Helper macro to generate an impl of `OptionableFieldsV1` for pointers and newtype wrappers that
+/// forward to the inner value. The expression maps the bound optioned wrapper to a reference of the inner value,
+/// `None` if there is none.
+macro_rules! impl_fields_v1_forward {
+    ($($t:ident($optioned:ident => $inner:expr)),* $(,)?) => {
+        $(impl<T: OptionableFieldsV1> OptionableFieldsV1 for $t<T> {
+            fn fields_v1_optioned($optioned: &$t<T::Optioned>, fields: &mut Map<String, Value>) {
+                if let Some(optioned) = $inner {
+                    T::fields_v1_optioned(optioned, fields);
+                }
+            }
+        })*
+    };
+}
+
+impl_fields_v1_forward!(
+    Box(optioned => Some(&**optioned)),
+    Rc(optioned => Some(&**optioned)),
+    Arc(optioned => Some(&**optioned)),
+    Reverse(optioned => Some(&optioned.0)),
+    Wrapping(optioned => Some(&optioned.0)),
+    Saturating(optioned => Some(&optioned.0)),
+    OnceCell(optioned => optioned.get()),
+    OnceLock(optioned => optioned.get()),
+);
+
+impl<B: ?Sized + ToOwned> OptionableFieldsV1 for Cow<'_, B>
+where
+    B::Owned: OptionableFieldsV1,
+{
+    fn fields_v1_optioned(
+        optioned: &<B::Owned as Optionable>::Optioned,
+        fields: &mut Map<String, Value>,
+    ) {
+        B::Owned::fields_v1_optioned(optioned, fields);
+    }
+}
+
+impl<K: Display, T: OptionableFieldsV1> OptionableFieldsV1 for BTreeMap<K, T> {
+    fn fields_v1_optioned(optioned: &BTreeMap<K, T::Optioned>, fields: &mut Map<String, Value>) {
+        map_fields_v1::<K, T>(optioned, fields);
+    }
+}
+
+impl<K: Display, T: OptionableFieldsV1, S> OptionableFieldsV1 for HashMap<K, T, S> {
+    fn fields_v1_optioned(
+        optioned: &HashMap<K, T::Optioned, S>,
+        fields: &mut Map<String, Value>,
+    ) {
+        map_fields_v1::<K, T>(optioned, fields);
+    }
+}
+
+// Lists are atomic unless configured otherwise via `list_type`, arrays, ranges as well as cells and locks
+// are managed as a whole.
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1, const N: usize] [T; N]);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] Vec<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] VecDeque<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] LinkedList<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] BTreeSet<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] BinaryHeap<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1, S] HashSet<T, S>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] Range<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] RangeInclusive<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] RangeFrom<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] RangeTo<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] RangeToInclusive<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] Cell<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] RefCell<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] Mutex<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] RwLock<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] Pin<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] RcWeak<T>);
+crate::impl_fields_v1_whole!([T: OptionableFieldsV1] ArcWeak<T>);
+crate::impl_fields_v1_whole!([T: ?Sized] PhantomData<T>);
+
+#[cfg(test)]
+mod tests {
+    use crate::fields_v1::{FieldsV1, OptionableFieldsV1};
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    /// Check that map entries are represented as `f:<key>` and parsed as map entries.
+    fn map_entries() {
+        let optioned = BTreeMap::from([
+            ("a", BTreeMap::from([("b", 1)])),
+            ("c", BTreeMap::new()),
+        ]);
+        let fields = <BTreeMap<&str, BTreeMap<&str, i32>>>::fields_v1(&optioned);
+        assert_eq!(
+            serde_json::to_value(&fields).unwrap(),
+            json!({"f:a": {"f:b": {}}, "f:c": {}})
+        );
+        assert_eq!(
+            fields
+                .field_paths::<BTreeMap<&str, BTreeMap<&str, i32>>>()
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec!["[a][b]", "[c]"]
+        );
+    }
+
+    #[test]
+    /// Check that malformed entries are reported with their path.
+    fn invalid_entries() {
+        let fields: FieldsV1 =
+            serde_json::from_value(json!({"f:a": {"x:b": {}, "f:c": 1}, "f:d": {".": {}}}))
+                .unwrap();
+        assert_eq!(
+            fields
+                .field_paths::<BTreeMap<String, BTreeMap<String, i32>>>()
+                .unwrap_err()
+                .invalid_entries,
+            vec!["[a][f:c]", "[a][x:b]"]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use crate::__private::entry_path;
+use crate::field_mask::{FieldMaskTree, PathSegment};
+use crate::field_paths::{is_entry_path, mask_entry, set_entry_paths};
+use crate::three_way::{merge_entries, OptionableThreeWayMerge};
+use crate::{
+    impl_field_paths_whole, impl_three_way_whole, Error, Optionable, OptionableConvert,
+    OptionableFieldPaths,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::hash::{BuildHasher, Hash};
+use indexmap::{IndexMap, IndexSet};
+
+// Same logic as for the std maps/sets, the key order is kept.
+impl<K, T: Optionable, S> Optionable for IndexMap<K, T, S> {
+    type Optioned = IndexMap<K, T::Optioned, S>;
+
+    fn is_empty_optioned(optioned: &IndexMap<K, T::Optioned, S>) -> bool {
+        optioned.is_empty()
+    }
+}
+
+impl<T: Optionable, S> Optionable for IndexSet<T, S> {
+    type Optioned = IndexSet<T::Optioned, S>;
+}
+
+impl<K: Eq + Hash, T: OptionableConvert, S: BuildHasher + Default> OptionableConvert
+    for IndexMap<K, T, S>
+{
+    fn into_optioned(self) -> IndexMap<K, T::Optioned, S> {
+        self.into_iter()
+            .map(|(k, v)| (k, v.into_optioned()))
+            .collect()
+    }
+
+    fn try_from_optioned(value: IndexMap<K, T::Optioned, S>) -> Result<Self, Error> {
+        value
+            .into_iter()
+            .map(|(k, v)| Ok((k, T::try_from_optioned(v)?)))
+            .collect()
+    }
+
+    fn merge(&mut self, other: IndexMap<K, T::Optioned, S>) -> Result<(), Error> {
+        for (k, v) in other {
+            if let Some(value) = self.get_mut(&k) {
+                value.merge(v)?;
+            } else {
+                self.insert(k, T::try_from_optioned(v)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: OptionableConvert + Eq + Hash, S: BuildHasher + Default> OptionableConvert
+    for IndexSet<T, S>
+where
+    T::Optioned: Eq + Hash,
+{
+    fn into_optioned(self) -> IndexSet<T::Optioned, S> {
+        self.into_iter().map(T::into_optioned).collect()
+    }
+
+    fn try_from_optioned(value: IndexSet<T::Optioned, S>) -> Result<Self, Error> {
+        value.into_iter().map(T::try_from_optioned).collect()
+    }
+
+    fn merge(&mut self, other: IndexSet<T::Optioned, S>) -> Result<(), Error> {
+        *self = Self::try_from_optioned(other)?;
+        Ok(())
+    }
+}
+
+impl<K: Display, T: OptionableFieldPaths, S> OptionableFieldPaths for IndexMap<K, T, S> {
+    fn set_field_paths_optioned(
+        optioned: &IndexMap<K, T::Optioned, S>,
+        prefix: &str,
+        paths: &mut Vec<String>,
+    ) {
+        set_entry_paths::<K, T>(optioned, prefix, paths);
+    }
+
+    fn mask_optioned(optioned: &mut IndexMap<K, T::Optioned, S>, mask: &FieldMaskTree) {
+        optioned.retain(|k, v| mask_entry::<T>(k, v, mask));
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        is_entry_path::<T>(segments)
+    }
+}
+
+impl_field_paths_whole!([T: OptionableFieldPaths, S] IndexSet<T, S>);
+
+#[cfg(feature = "serde_json")]
+impl<K: Display, T: crate::fields_v1::OptionableFieldsV1, S> crate::fields_v1::OptionableFieldsV1
+    for IndexMap<K, T, S>
+{
+    fn fields_v1_optioned(
+        optioned: &IndexMap<K, T::Optioned, S>,
+        fields: &mut serde_json::Map<String, serde_json::Value>,
+    ) {
+        crate::fields_v1::map_fields_v1::<K, T>(optioned, fields);
+    }
+}
+
+#[cfg(feature = "serde_json")]
+crate::impl_fields_v1_whole!([T: crate::fields_v1::OptionableFieldsV1, S] IndexSet<T, S>);
+
+#[cfg(feature = "serde_json")]
+crate::strategic_merge::impl_strategic_merge_elements!(
+    [K, T: crate::strategic_merge::OptionableStrategicMerge, S] IndexMap<K, T, S> => T,
+    [T: crate::strategic_merge::OptionableStrategicMerge, S] IndexSet<T, S> => T,
+);
+
+impl<K: Eq + Hash + Display, T: OptionableThreeWayMerge, S: BuildHasher + Default>
+    OptionableThreeWayMerge for IndexMap<K, T, S>
+{
+    fn three_way_merge_at(
+        base: Option<&IndexMap<K, T::Optioned, S>>,
+        ours: IndexMap<K, T::Optioned, S>,
+        theirs: IndexMap<K, T::Optioned, S>,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> IndexMap<K, T::Optioned, S> {
+        merge_entries::<K, T, _>(
+            base,
+            ours,
+            theirs,
+            (IndexMap::get, IndexMap::shift_remove),
+            |key| entry_path(path, key),
+            conflicts,
+        )
+    }
+}
+
+impl_three_way_whole!([T: OptionableThreeWayMerge<Optioned: Eq + Hash>, S: BuildHasher] IndexSet<T, S>);
+
+#[cfg(test)]
+mod tests {
+    use crate::{Optionable, OptionableConvert};
+    use indexmap::{IndexMap, IndexSet};
+    use std::hash::RandomState;
+
+    #[test]
+    /// Check that `IndexMap` and `IndexSet` implement optionable.
+    fn index_map() {
+        let a = IndexMap::<_, _, RandomState>::from_iter([(1, "a".to_owned())]);
+        let _: <IndexMap<i32, String, RandomState> as Optionable>::Optioned = a;
+
+        let a = IndexSet::<_, RandomState>::from_iter([1]);
+        let _: <IndexSet<i32, RandomState> as Optionable>::Optioned = a;
+    }
+
+    #[test]
+    /// Check that merging keeps the insertion order, existing keys stay in place and new keys are appended.
+    fn merge_keeps_insertion_order() {
+        let mut a = IndexMap::<_, _, RandomState>::from_iter([
+            ("c", Some(1)),
+            ("a", Some(2)),
+            ("b", Some(3)),
+        ]);
+        let patch = IndexMap::<_, _, RandomState>::from_iter([
+            ("e", Some(4)),
+            ("a", Some(5)),
+            ("d", None),
+        ]);
+        a.merge(patch).unwrap();
+        assert_eq!(a.keys().collect::<Vec<_>>(), vec![&"c", &"a", &"b", &"e", &"d"]);
+        assert_eq!(a.values().collect::<Vec<_>>(), vec![&Some(1), &Some(5), &Some(3), &Some(4), &None]);
+
+        let mut a = IndexSet::<_, RandomState>::from_iter(["c", "a", "b"]);
+        a.merge(IndexSet::from_iter(["b", "d", "a"])).unwrap();
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![&"b", &"d", &"a"]);
+    }
+}
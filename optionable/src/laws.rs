@@ -0,0 +1,184 @@
+//! Checks of the algebraic laws that merging and converting optioned values should satisfy,
+//! e.g. to test hand-written [`OptionableConvert`] impls or the derived impls for own types.
+//!
+//! The checks return a [`LawViolation`] describing the involved values if a law does not hold.
+//! They work well with generated values, see [`crate::proptest`] (requires the `proptest` feature).
+//! ```rust
+//! # use optionable::Optionable;
+//! # use optionable::laws;
+//! #[derive(Optionable, Clone, Debug, PartialEq)]
+//! #[optionable(derive(Clone, Debug, PartialEq))]
+//! struct Deployment {
+//!     name: String,
+//!     replicas: Option<u32>,
+//! }
+//!
+//! let deployment = Deployment { name: "a".to_owned(), replicas: None };
+//! let patch = DeploymentOpt { name: None, replicas: Some(2) };
+//! let other = DeploymentOpt { name: Some("b".to_owned()), replicas: None };
+//! laws::check_round_trip(&deployment).unwrap();
+//! laws::check_merge_empty(&deployment, &DeploymentOpt::default()).unwrap();
+//! laws::check_merge_composition(&deployment, &patch, &other).unwrap();
+//! ```
+use crate::{Optionable, OptionableConvert};
+use std::fmt::{Debug, Display, Formatter};
+
+/// Error for values that violate one of the laws.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LawViolation {
+    /// The name of the violated law, i.e. the name of the check without `check_`.
+    pub law: &'static str,
+    /// Description of the involved values.
+    pub details: String,
+}
+
+impl Display for LawViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "violated law {}: {}", self.law, self.details)
+    }
+}
+
+impl std::error::Error for LawViolation {}
+
+/// Checks that converting the value into its optioned type and back returns the value unchanged,
+/// i.e. `try_from_optioned(into_optioned(x)) == x`.
+///
+/// # Errors
+/// - If the conversion fails or returns a different value.
+pub fn check_round_trip<T>(value: &T) -> Result<(), LawViolation>
+where
+    T: OptionableConvert + Clone + Debug + PartialEq,
+{
+    let violation = |details| violation("round_trip", details);
+    match T::try_from_optioned(value.clone().into_optioned()) {
+        Ok(result) if result == *value => Ok(()),
+        Ok(result) => Err(violation(format!("{value:?} round-trips to {result:?}"))),
+        Err(err) => Err(violation(format!("{value:?} fails to round-trip: {err}"))),
+    }
+}
+
+/// Checks that merging an empty optioned value leaves the value unchanged, i.e. `apply(x, empty) == x`.
+/// Empty values are those for which [`Optionable::is_empty_optioned`] holds, e.g. the `Default` of derived
+/// optioned types. Their required fields are merged as well and hence have to equal the ones of the value.
+///
+/// # Errors
+/// - If `empty` is not empty, the merge fails or changes the value.
+pub fn check_merge_empty<T>(value: &T, empty: &T::Optioned) -> Result<(), LawViolation>
+where
+    T: OptionableConvert + Clone + Debug + PartialEq,
+    T::Optioned: Clone + Debug,
+{
+    let violation = |details| violation("merge_empty", details);
+    if !T::is_empty_optioned(empty) {
+        return Err(violation(format!("{empty:?} is not empty")));
+    }
+    let mut result = value.clone();
+    match result.merge(empty.clone()) {
+        Ok(()) if result == *value => Ok(()),
+        Ok(()) => Err(violation(format!(
+            "merging {empty:?} changes {value:?} to {result:?}"
+        ))),
+        Err(err) => Err(violation(format!(
+            "merging {empty:?} into {value:?} fails: {err}"
+        ))),
+    }
+}
+
+/// Checks that merging is associative for optioned values, i.e. `(a + b) + c == a + (b + c)`
+/// with `+` denoting the merge of the right into the left value.
+///
+/// # Errors
+/// - If one of the merges fails or the results differ.
+pub fn check_merge_associativity<T>(
+    a: &T::Optioned,
+    b: &T::Optioned,
+    c: &T::Optioned,
+) -> Result<(), LawViolation>
+where
+    T: Optionable,
+    T::Optioned: OptionableConvert<Optioned = T::Optioned> + Clone + Debug + PartialEq,
+{
+    let merge =
+        |mut value: T::Optioned, other: &T::Optioned| value.merge(other.clone()).map(|()| value);
+    let violation = |details| violation("merge_associativity", details);
+    let left = merge(a.clone(), b).and_then(|ab| merge(ab, c));
+    let right = merge(b.clone(), c).and_then(|bc| merge(a.clone(), &bc));
+    match (left, right) {
+        (Ok(left), Ok(right)) if left == right => Ok(()),
+        (Ok(left), Ok(right)) => Err(violation(format!(
+            "merging {a:?}, {b:?} and {c:?} results in {left:?} from the left but {right:?} from the right"
+        ))),
+        (Err(err), _) | (_, Err(err)) => Err(violation(format!(
+            "merging {a:?}, {b:?} and {c:?} fails: {err}"
+        ))),
+    }
+}
+
+/// Checks that merging two optioned values one after another into a value is the same as merging
+/// their combination, i.e. `apply(apply(x, a), b) == apply(x, a + b)`.
+/// Patches that can't be merged one after another (e.g. as they miss required fields) satisfy the law trivially.
+///
+/// # Errors
+/// - If merging the combination fails or results in a different value.
+pub fn check_merge_composition<T>(
+    value: &T,
+    a: &T::Optioned,
+    b: &T::Optioned,
+) -> Result<(), LawViolation>
+where
+    T: OptionableConvert + Clone + Debug + PartialEq,
+    T::Optioned: OptionableConvert<Optioned = T::Optioned> + Clone + Debug,
+{
+    let mut sequential = value.clone();
+    if sequential.merge(a.clone()).is_err() || sequential.merge(b.clone()).is_err() {
+        return Ok(());
+    }
+    let violation = |details| violation("merge_composition", details);
+    let mut combined = a.clone();
+    if let Err(err) = combined.merge(b.clone()) {
+        return Err(violation(format!("merging {b:?} into {a:?} fails: {err}")));
+    }
+    let mut result = value.clone();
+    match result.merge(combined) {
+        Ok(()) if result == sequential => Ok(()),
+        Ok(()) => Err(violation(format!(
+            "merging {a:?} and {b:?} into {value:?} results in {sequential:?} one after another \
+            but in {result:?} combined"
+        ))),
+        Err(err) => Err(violation(format!(
+            "merging the combination of {a:?} and {b:?} into {value:?} fails: {err}"
+        ))),
+    }
+}
+
+/// Checks that merging the difference of two values into the first results in the second,
+/// i.e. `apply(x, diff(x, y)) == y`. The crate does not compute differences itself,
+/// hence the function computing the optioned value that turns `x` into `y` is passed as `diff`.
+///
+/// # Errors
+/// - If merging the difference fails or does not result in `y`.
+pub fn check_diff<T>(
+    x: &T,
+    y: &T,
+    diff: impl FnOnce(&T, &T) -> T::Optioned,
+) -> Result<(), LawViolation>
+where
+    T: OptionableConvert + Clone + Debug + PartialEq,
+    T::Optioned: Debug,
+{
+    let patch = diff(x, y);
+    let details = format!("the difference {patch:?} of {x:?} and {y:?}");
+    let mut result = x.clone();
+    match result.merge(patch) {
+        Ok(()) if result == *y => Ok(()),
+        Ok(()) => Err(violation(
+            "diff",
+            format!("merging {details} results in {result:?}"),
+        )),
+        Err(err) => Err(violation("diff", format!("merging {details} fails: {err}"))),
+    }
+}
+
+fn violation(law: &'static str, details: String) -> LawViolation {
+    LawViolation { law, details }
+}
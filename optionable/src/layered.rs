@@ -0,0 +1,206 @@
+//! Layered configuration assembled from several partial sources like config files, environment variables
+//! and command line arguments, each represented by an optioned value.
+//!
+//! The layers are merged in the order they are added, i.e. later layers take precedence. Finalizing builds the
+//! full type and reports which layer supplied each (sub-)field, see [`OptionableFieldPaths`] for the paths.
+//! ```rust
+//! # use optionable::Optionable;
+//! # use optionable::layered::Layered;
+//! #[derive(Optionable, Debug, PartialEq)]
+//! struct Config {
+//!     host: String,
+//!     port: u16,
+//! }
+//!
+//! let config = Layered::<Config>::new()
+//!     .layer("defaults", ConfigOpt { host: Some("localhost".to_owned()), port: Some(80) })
+//!     .layer("cli", ConfigOpt { host: None, port: Some(8080) })
+//!     .finalize()
+//!     .unwrap();
+//! assert_eq!(config.value, Config { host: "localhost".to_owned(), port: 8080 });
+//! assert_eq!(config.source("port"), Some("cli"));
+//!
+//! let err = Layered::<Config>::new()
+//!     .layer("cli", ConfigOpt { host: None, port: Some(8080) })
+//!     .finalize()
+//!     .unwrap_err();
+//! assert_eq!(err.missing_fields, vec!["host"]);
+//! ```
+use crate::{Error, OptionableConvert, OptionableFieldPaths};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+/// Named optioned values of `T` merged by precedence into a full `T`.
+pub struct Layered<T: OptionableConvert> {
+    layers: Vec<(String, T::Optioned)>,
+}
+
+impl<T: OptionableConvert> Default for Layered<T> {
+    fn default() -> Self {
+        Self { layers: vec![] }
+    }
+}
+
+impl<T: OptionableConvert> Layered<T> {
+    /// Creates a configuration without any layers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer taking precedence over all previously added layers.
+    #[must_use]
+    pub fn layer(mut self, name: impl Into<String>, optioned: T::Optioned) -> Self {
+        self.layers.push((name.into(), optioned));
+        self
+    }
+
+    /// Adds the content of a JSON (`.json`), YAML (`.yaml`, `.yml`) or TOML (`.toml`) file as layer
+    /// named by the path. The formats are available with the `serde_json`, `serde_yaml` and `toml` features.
+    ///
+    /// # Errors
+    /// - If the file can't be read, has an unsupported format or can't be deserialized.
+    #[cfg(any(feature = "serde_json", feature = "serde_yaml", feature = "toml"))]
+    pub fn file(self, path: impl AsRef<std::path::Path>) -> Result<Self, LayerError>
+    where
+        T::Optioned: serde::de::DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let layer = path.display().to_string();
+        let error = |source: Box<dyn core::error::Error + Send + Sync>| LayerError {
+            layer: layer.clone(),
+            source,
+        };
+        let content = std::fs::read_to_string(path).map_err(|err| error(err.into()))?;
+        let optioned = match path.extension().and_then(|ext| ext.to_str()) {
+            #[cfg(feature = "serde_json")]
+            Some("json") => serde_json::from_str(&content).map_err(|err| error(err.into()))?,
+            #[cfg(feature = "serde_yaml")]
+            Some("yaml" | "yml") => {
+                serde_yaml::from_str(&content).map_err(|err| error(err.into()))?
+            }
+            #[cfg(feature = "toml")]
+            Some("toml") => toml::from_str(&content).map_err(|err| error(err.into()))?,
+            _ => return Err(error("unsupported file format".into())),
+        };
+        Ok(self.layer(layer, optioned))
+    }
+
+    /// Adds the values of the environment variables with the given prefix as layer named `env`,
+    /// see [`crate::env::OptionableEnv::from_env`] for the variable names. Requires the `env` feature.
+    ///
+    /// # Errors
+    /// - If any of the variables can't be parsed or required fields are missing.
+    #[cfg(feature = "env")]
+    pub fn env(self, prefix: &str) -> Result<Self, LayerError>
+    where
+        T: crate::env::OptionableEnv,
+        T::Optioned: Default,
+    {
+        let optioned = T::from_env(prefix).map_err(|err| LayerError {
+            layer: "env".to_owned(),
+            source: err.into(),
+        })?;
+        Ok(self.layer("env", optioned))
+    }
+}
+
+impl<T: OptionableConvert + OptionableFieldPaths> Layered<T>
+where
+    T::Optioned: OptionableConvert<Optioned = T::Optioned> + Default,
+{
+    /// Merges the layers by precedence and builds the full value.
+    ///
+    /// # Errors
+    /// - If (sub-)fields that are required to build the full type are not set by any layer.
+    pub fn finalize(self) -> Result<Finalized<T>, Error> {
+        let mut merged = T::Optioned::default();
+        let mut sources = BTreeMap::new();
+        for (name, optioned) in self.layers {
+            for path in T::set_field_paths(&optioned) {
+                // the (sub-)fields of the path are supplied by this layer now
+                sources.retain(|source: &String, _| !is_sub_path(source, &path));
+                sources.insert(path, name.clone());
+            }
+            merged.merge(optioned)?;
+        }
+        Ok(Finalized {
+            value: T::try_from_optioned(merged)?,
+            sources,
+        })
+    }
+}
+
+/// Whether `path` addresses a strict (sub-)field of `parent`.
+pub(crate) fn is_sub_path(path: &str, parent: &str) -> bool {
+    path.strip_prefix(parent).is_some_and(|rest| {
+        if parent.is_empty() {
+            !rest.is_empty()
+        } else {
+            rest.starts_with(['.', '['])
+        }
+    })
+}
+
+/// The value built from the layers together with the layers that supplied its (sub-)fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finalized<T> {
+    /// The full value.
+    pub value: T,
+    /// The name of the supplying layer per set field path. Required fields are not recorded.
+    pub sources: BTreeMap<String, String>,
+}
+
+impl<T> Finalized<T> {
+    /// Returns the name of the layer that supplied the (sub-)field at `path`,
+    /// i.e. of the layer that set it or the closest of its parent fields.
+    #[must_use]
+    pub fn source(&self, path: &str) -> Option<&str> {
+        self.sources
+            .iter()
+            .filter(|(source, _)| *source == path || is_sub_path(path, source))
+            .max_by_key(|(source, _)| source.len())
+            .map(|(_, layer)| layer.as_str())
+    }
+}
+
+/// Error for layers that can't be loaded.
+#[derive(Debug)]
+pub struct LayerError {
+    /// The name of the layer.
+    pub layer: String,
+    /// The cause of the error.
+    pub source: Box<dyn core::error::Error + Send + Sync>,
+}
+
+impl Display for LayerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "failed to load layer {}: {}", self.layer, self.source)
+    }
+}
+
+impl core::error::Error for LayerError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_sub_path;
+
+    #[test]
+    /// Check that only strict (sub-)fields are sub paths.
+    fn sub_path() {
+        assert!(is_sub_path("a.b", "a"));
+        assert!(is_sub_path("a[b]", "a"));
+        assert!(is_sub_path("a", ""));
+        assert!(!is_sub_path("a", "a"));
+        assert!(!is_sub_path("ab", "a"));
+        assert!(!is_sub_path("", ""));
+    }
+}
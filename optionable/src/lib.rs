@@ -84,10 +84,254 @@
 //! }
 //! ```
 //! For many primitive types as well as common wrapper or collection types the `Optionable`-trait is already implemented.
-
+//!
+//! ## Converting and merging
+//! The [`trait@OptionableConvert`] trait (derived by default, see the `no_convert` attribute) converts between a type
+//! and its optioned type and merges optioned values (patches) into a full value:
+//! ```rust
+//! # use optionable::{Optionable, OptionableConvert};
+//! #[derive(Optionable, Debug, PartialEq)]
+//! struct Address {
+//!     street_name: String,
+//!     number: u8,
+//! }
+//!
+//! let mut address = Address { street_name: "Main Street".to_owned(), number: 1 };
+//! address.merge(AddressOpt { street_name: None, number: Some(2) }).unwrap();
+//! assert_eq!(address, Address { street_name: "Main Street".to_owned(), number: 2 });
+//!
+//! let err = Address::try_from_optioned(AddressOpt { street_name: None, number: Some(2) }).unwrap_err();
+//! assert_eq!(err.missing_fields, vec!["street_name"]);
+//! ```
+//! Unset fields keep their value during a merge. Maps are merged per key, arrays per element and structs/enums
+//! field-wise (if the enum variant matches). All other collections like `Vec` are replaced as a whole.
+//! Values that have to be newly created (e.g. a new map entry or another enum variant) need all required fields set.
+//! Dynamic documents like `serde_json::Value` are merged according to [RFC 7396](https://www.rfc-editor.org/rfc/rfc7396) (JSON Merge Patch).
+//!
+//! ## Building optioned values
+//! With `#[optionable(builder)]` the optioned type of a struct with named fields gets fluent `with_<field>` setters, similar to the `WithXxx` functions of the Kubernetes apply configurations.
+//! Nested optioned values can be built in place via the `with_<field>_fn` closures that receive the current value
+//! (or the default if unset):
+//! ```rust
+//! # use optionable::Optionable;
+//! #[derive(Optionable)]
+//! #[optionable(builder)]
+//! struct Deployment {
+//!     name: String,
+//!     spec: DeploymentSpec,
+//! }
+//! #[derive(Optionable)]
+//! #[optionable(builder)]
+//! struct DeploymentSpec {
+//!     replicas: u32,
+//!     paused: bool,
+//! }
+//!
+//! let patch = DeploymentOpt::default()
+//!     .with_name("nginx".to_owned())
+//!     .with_spec_fn(|spec| spec.with_replicas(3));
+//! assert_eq!(patch.spec.unwrap().replicas, Some(3));
+//!
+//! let patch = DeploymentOpt::default().with_spec_value(DeploymentSpec { replicas: 2, paused: false });
+//! assert_eq!(patch.spec.unwrap().paused, Some(false));
+//! ```
+//! The setters take the optioned value of the field, the `with_<field>_value` setters (unless `no_convert` is set)
+//! take the full value and convert it via [`OptionableConvert::into_optioned`].
+//!
+//! ## Empty optioned values
+//! Optioned structs implement `Default` and provide an `empty` constructor with all non-required fields unset.
+//! Required fields are taken as parameters of `empty` (`Default` requires their types to implement `Default`).
+//! The `is_empty` method checks recursively whether any field is set, e.g. to skip empty patches:
+//! ```rust
+//! # use optionable::Optionable;
+//! #[derive(Optionable)]
+//! struct Address {
+//!     #[optionable(required)]
+//!     id: u32,
+//!     street_name: String,
+//!     details: Details,
+//! }
+//! #[derive(Optionable)]
+//! struct Details {
+//!     floor: u8,
+//! }
+//!
+//! let mut patch = AddressOpt::empty(1);
+//! assert!(patch.is_empty());
+//! patch.details = Some(DetailsOpt::empty());
+//! assert!(patch.is_empty());
+//! patch.details = Some(DetailsOpt { floor: Some(2) });
+//! assert!(!patch.is_empty());
+//! ```
+//! Values without inner structure and collections that are replaced as a whole (e.g. `Vec`) count as set,
+//! maps as empty if they have no entries. Pointers, cells and newtype wrappers like `Box`, `RefCell` or `Wrapping`
+//! are empty if their value is, arrays if all their elements are.
+//! The check is available for all optioned types via [`Optionable::is_empty_optioned`].
+//! ## Set field paths
+//! The [`trait@OptionableFieldPaths`] trait (derived by default, see the `no_field_paths` attribute) lists the paths
+//! of the (sub-)fields an optioned value sets, e.g. to log which fields a patch touches.
+//! Nested fields are separated by dots and map entries are appended as `[<key>]`. The derived names follow the
+//! `serde` attributes of the original type (renames, flattened fields and the enum representation):
+//! ```rust
+//! # use optionable::Optionable;
+//! # use serde::Serialize;
+//! # use std::collections::BTreeMap;
+//! #[derive(Optionable, Serialize)]
+//! #[serde(rename_all = "camelCase")]
+//! struct PodTemplate {
+//!     service_account: String,
+//!     labels: BTreeMap<String, String>,
+//! }
+//!
+//! let patch = PodTemplateOpt {
+//!     service_account: Some("default".to_owned()),
+//!     labels: Some(BTreeMap::from([("app".to_owned(), "nginx".to_owned())])),
+//! };
+//! assert_eq!(patch.set_field_paths(), vec!["serviceAccount", "labels[app]"]);
+//! ```
+//! Values without inner structure and collections without keys like `Vec` are set as a whole.
+//! A nested value that is set without any of its fields is listed itself.
+//!
+//! ## Field masks
+//! A [`field_mask::FieldMask`] selects (sub-)fields by their field paths like the protobuf `FieldMask` used
+//! for update masks. It can be derived from the fields set by an optioned value (`field_mask`), validated against
+//! the structure of a type and used to project a full value onto the selected fields:
+//! ```rust
+//! # use optionable::Optionable;
+//! # use optionable::field_mask::FieldMask;
+//! # use serde::Serialize;
+//! # use std::collections::BTreeMap;
+//! #[derive(Optionable, Serialize)]
+//! #[serde(rename_all = "camelCase")]
+//! struct PodTemplate {
+//!     service_account: String,
+//!     labels: BTreeMap<String, String>,
+//! }
+//!
+//! let mask = FieldMask::new(["labels[app]"]);
+//! mask.validate::<PodTemplate>().unwrap();
+//! assert!(FieldMask::new(["labels.app"]).validate::<PodTemplate>().is_err());
+//!
+//! let template = PodTemplate {
+//!     service_account: "default".to_owned(),
+//!     labels: BTreeMap::from([("app".to_owned(), "nginx".to_owned()), ("tier".to_owned(), "web".to_owned())]),
+//! };
+//! let projected = mask.project(template).unwrap();
+//! assert_eq!(projected.service_account, None);
+//! assert_eq!(projected.labels, Some(BTreeMap::from([("app".to_owned(), "nginx".to_owned())])));
+//! assert_eq!(projected.field_mask(), mask);
+//! ```
+//! Map entries accept any key during validation as the keys are only known at runtime.
+//! Required fields are always kept by a projection.
+//!
+//! ## JSON schemas
+//! Forwarding `JsonSchema` from [schemars](https://docs.rs/schemars/latest/schemars/) via `#[optionable(derive(JsonSchema))]`
+//! generates schemas with all non-required properties optional, nullable only if the original field is an `Option`.
+//! The `schemars` feature adds the schemas for the optioned types of this crate.
+//!
+//! ## Schemas for utoipa
+//! Listing `ToSchema` from [utoipa](https://docs.rs/utoipa/latest/utoipa/) in `#[optionable(derive(...))]` implements it
+//! following the same rules as for JSON schemas. The `utoipa` feature adds the schemas for the optioned types of this crate.
+//!
+//! ## GraphQL input objects
+//! With the `async-graphql` feature, forwarding `InputObject` from [async-graphql](https://docs.rs/async-graphql/latest/async_graphql/)
+//! turns the optioned type into an input object whose named `Option` fields are tri-state via `async_graphql::MaybeUndefined`.
+//!
+//! ## SQL updates
+//! Listing `AsChangeset` from [diesel](https://docs.rs/diesel/latest/diesel/) in `#[optionable(derive(...))]` implements it for
+//! partial row updates. With the `sqlx` feature the `sqlx` attribute generates an `update_query` method for [sqlx](https://docs.rs/sqlx/latest/sqlx/).
+//! `AsChangeset` needs no crate feature: the impl only refers to the `diesel` crate and the `table` module of the
+//! deriving crate, which depends on them anyway, while the `sqlx` queries are built by helpers of this crate.
+//!
+//! ## Layered configuration
+//! [`layered::Layered`] merges optioned values from several sources by precedence and reports which layer supplied each field.
+//!
+//! ## Environment variables
+//! With the `env` feature the `env` attribute implements [`env::OptionableEnv`] to read optioned values from environment variables.
+//!
+//! ## Command line arguments
+//! With the `clap` feature, forwarding `Args` implements [`clap::OptionableArgs`] with an optional flag per field.
+//!
+//! ## Validation
+//! The `validate` attribute implements [`validate::OptionableValidate`] to check the set fields of optioned values.
+//!
+//! ## Property-based testing
+//! With the `proptest` feature the `proptest` attribute implements [`proptest::OptionableStrategy`] for random optioned values.
+//! With the `arbitrary` feature the `arbitrary` attribute implements [`arbitrary::OptionableArbitrary`] to generate them from unstructured bytes, e.g. for fuzzing.
+//!
+//! ## Law checks
+//! With the `laws` feature the [`laws`] module checks that merging and converting behave consistently for a type.
+//!
+//! ## Three-way merge
+//! The `three_way` attribute implements [`three_way::OptionableThreeWayMerge`] to reconcile concurrent changes like `kubectl apply`.
+//!
+//! ## Managed fields
+//! [`managed_fields::ManagedFields`] emulates the field ownership of Kubernetes server-side apply.
+//!
+//! ## `FieldsV1`
+//! With the `serde_json` feature the `fields_v1` attribute implements [`fields_v1::OptionableFieldsV1`] to compute the Kubernetes
+//! `FieldsV1` of the set fields.
+//!
+//! ## Strategic merge patches
+//! With the `serde_json` feature the `strategic_merge` attribute implements [`strategic_merge::OptionableStrategicMerge`] to create
+//! and apply Kubernetes strategic merge patches.
+//!
+//! ## `no_std` support
+//! The crate is `no_std` compatible when disabling the default `std` feature. With the `alloc` feature the conversion,
+//! field path, field mask, layering, validation, three-way merge and managed fields support is available together with the impls for the `alloc` types
+//! (`Vec`, `BTreeMap`, `Box`, `Rc`, `Arc`, ...). Without it only the `Optionable` trait with the impls for the `core`
+//! types remains, hence deriving requires the `no_convert` and `no_field_paths` attributes.
+//! `std` adds the impls for `HashMap`, `HashSet`, `Mutex`, `RwLock`, `OnceLock`, paths, OS strings and `Instant`/`SystemTime`.
+//! The integrations with other crates require `alloc` or `std` and enable them.
+//! ```toml
+//! [dependencies]
+//! optionable = { version = "0.1", default-features = false, features = ["alloc"] }
+//! ```
+//!
 //! ## Crate features
+//! - `alloc`: Support for `no_std` environments with an allocator, see [`no_std` support](#no_std-support).
+//! - `arbitrary`: Support for generating optioned values via [arbitrary](https://docs.rs/arbitrary/latest/arbitrary/), see [Property-based testing](#property-based-testing).
+//! - `arrayvec`: Derive [`trait@Optionable`] for types from [arrayvec](https://docs.rs/arrayvec/latest/arrayvec/).
+//! - `async-graphql`: Support forwarding `InputObject` from [async-graphql](https://docs.rs/async-graphql/latest/async_graphql/), see [GraphQL input objects](#graphql-input-objects).
+//! - `bigdecimal`: Derive [`trait@Optionable`] for types from [bigdecimal](https://docs.rs/bigdecimal/latest/bigdecimal/).
+//! - `bytes`: Derive [`trait@Optionable`] for types from [bytes](https://docs.rs/bytes/latest/bytes/).
 //! - `chrono`: Derive [`trait@Optionable`] for types from [chrono](https://docs.rs/chrono/latest/chrono/).
-//! - `serde_json`: Derive [`trait@Optionable`] for [serde_json](https://docs.rs/serde_json/latest/serde_json/)`::Value`.
+//! - `clap`: Support forwarding `Args` from [clap](https://docs.rs/clap/latest/clap/), see [Command line arguments](#command-line-arguments).
+//! - `env`: Support for reading optioned types from environment variables, see [Environment variables](#environment-variables).
+//! - `indexmap`: Derive [`trait@Optionable`] for types from [indexmap](https://docs.rs/indexmap/latest/indexmap/).
+//! - `laws`: Support for checking the laws of merging and converting, see [Law checks](#law-checks).
+//! - `ordered-float`: Derive [`trait@Optionable`] for types from [ordered-float](https://docs.rs/ordered-float/latest/ordered_float/).
+//! - `proptest`: Support for [proptest](https://docs.rs/proptest/latest/proptest/) strategies, see [Property-based testing](#property-based-testing).
+//! - `prost`: Derive [`trait@Optionable`] for the protobuf well-known types of [prost-types](https://docs.rs/prost-types/latest/prost_types/)
+//!   (`Struct` and `Value` with merge patch semantics) and convert between `prost_types::FieldMask` and [`field_mask::FieldMask`].
+//!   Messages derived via `prost::Message` can derive [`trait@Optionable`] as well, their `Option` fields are not wrapped again.
+//! - `regex`: Support for the `regex` constraint of [Validation](#validation) via [regex](https://docs.rs/regex/latest/regex/).
+//! - `rust_decimal`: Derive [`trait@Optionable`] for [rust_decimal](https://docs.rs/rust_decimal/latest/rust_decimal/)`::Decimal`.
+//! - `schemars`: Derive [`trait@Optionable`] for [schemars](https://docs.rs/schemars/latest/schemars/)`::Schema` and
+//!   implement `JsonSchema` for the optioned types of this crate, see [JSON schemas](#json-schemas).
+//! - `serde_json`: Derive [`trait@Optionable`] for [serde_json](https://docs.rs/serde_json/latest/serde_json/)`::{Map, Value}` with JSON merge patch semantics, see [`serde_json::ValueOpt`].
+//!   Also adds the `FieldsV1` representation of server-side apply, see [`FieldsV1`](#fieldsv1), and
+//!   [Strategic merge patches](#strategic-merge-patches).
+//! - `serde_yaml`: Derive [`trait@Optionable`] for [serde_yaml](https://docs.rs/serde_yaml/latest/serde_yaml/)`::{Mapping, Value}` with merge patch semantics, see [`serde_yaml::ValueOpt`].
+//! - `smallvec`: Derive [`trait@Optionable`] for [smallvec](https://docs.rs/smallvec/latest/smallvec/)`::SmallVec`.
+//! - `sqlx`: Support for building `UPDATE` statements with [sqlx](https://docs.rs/sqlx/latest/sqlx/), see [SQL updates](#sql-updates).
+//! - `std` (default): Derive [`trait@Optionable`] for the types of the std library that are not part of `alloc`, see [`no_std` support](#no_std-support).
+//! - `time`: Derive [`trait@Optionable`] for types from [time](https://docs.rs/time/latest/time/).
+//! - `tinyvec`: Derive [`trait@Optionable`] for types from [tinyvec](https://docs.rs/tinyvec/latest/tinyvec/).
+//! - `toml`: Derive [`trait@Optionable`] for [toml](https://docs.rs/toml/latest/toml/)`::{Table, Value}` with merge patch semantics, see [`toml::ValueOpt`].
+//! - `url`: Derive [`trait@Optionable`] for [url](https://docs.rs/url/latest/url/)`::Url`.
+//! - `utoipa`: Derive [`trait@Optionable`] for [utoipa](https://docs.rs/utoipa/latest/utoipa/)`::openapi::schema::Schema` and
+//!   implement `ToSchema` for the optioned types of this crate, see [Schemas for utoipa](#schemas-for-utoipa).
+//! - `uuid`: Derive [`trait@Optionable`] for types from [uuid](https://docs.rs/uuid/latest/uuid/).
+//!
+//! ## Upgrading from 0.1
+//! Deriving [`trait@Optionable`] now also derives [`trait@OptionableConvert`] and [`trait@OptionableFieldPaths`] by default, which requires
+//! all (non-required) field types to implement these traits as well. Types whose fields only implement `Optionable`,
+//! e.g. via hand-written impls, have to add the impls or opt out via `#[optionable(no_convert, no_field_paths)]`.
+//! The [`trait@Optionable`] trait only gained the provided method [`Optionable::is_empty_optioned`], which defaults to `false`. Hand-written impls for
+//! types that merge their optioned value into an existing one (e.g. wrappers) should override it.
+//! With `serde::Serialize` or `serde::Deserialize` forwarded, the optioned type now keeps the `serde` attributes naming
+//! the fields and variants of the original type (e.g. `rename_all`), which changes its serialized representation.
 //!
 //! ## Limitations
 //!
@@ -104,18 +348,166 @@
 //! Another crate with similar scope is [optional_struct](https://crates.io/crates/optional_struct).
 //! It focuses specifically on structs (not enums) and offers a more manual approach, especially in respect to nested sub-struct,
 //! providing many fine-grained configuration options.
+//!
+
+// The links to the items of disabled features point to docs.rs.
+#![cfg_attr(
+    not(feature = "clap"),
+    doc = "[`clap::OptionableArgs`]: https://docs.rs/optionable/latest/optionable/clap/trait.OptionableArgs.html"
+)]
+#![cfg_attr(
+    not(feature = "env"),
+    doc = "[`env::OptionableEnv`]: https://docs.rs/optionable/latest/optionable/env/trait.OptionableEnv.html"
+)]
+#![cfg_attr(
+    not(feature = "serde_json"),
+    doc = "[`fields_v1::OptionableFieldsV1`]: https://docs.rs/optionable/latest/optionable/fields_v1/trait.OptionableFieldsV1.html"
+)]
+#![cfg_attr(
+    not(feature = "laws"),
+    doc = "[`laws`]: https://docs.rs/optionable/latest/optionable/laws/index.html"
+)]
+#![cfg_attr(
+    not(feature = "arbitrary"),
+    doc = "[`arbitrary::OptionableArbitrary`]: https://docs.rs/optionable/latest/optionable/arbitrary/trait.OptionableArbitrary.html"
+)]
+#![cfg_attr(
+    not(feature = "proptest"),
+    doc = "[`proptest::OptionableStrategy`]: https://docs.rs/optionable/latest/optionable/proptest/trait.OptionableStrategy.html"
+)]
+#![cfg_attr(
+    not(feature = "serde_json"),
+    doc = "[`serde_json::ValueOpt`]: https://docs.rs/optionable/latest/optionable/serde_json/enum.ValueOpt.html"
+)]
+#![cfg_attr(
+    not(feature = "serde_yaml"),
+    doc = "[`serde_yaml::ValueOpt`]: https://docs.rs/optionable/latest/optionable/serde_yaml/enum.ValueOpt.html"
+)]
+#![cfg_attr(
+    not(feature = "serde_json"),
+    doc = "[`strategic_merge::OptionableStrategicMerge`]: https://docs.rs/optionable/latest/optionable/strategic_merge/trait.OptionableStrategicMerge.html"
+)]
+#![cfg_attr(
+    not(feature = "toml"),
+    doc = "[`toml::ValueOpt`]: https://docs.rs/optionable/latest/optionable/toml/enum.ValueOpt.html"
+)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 #[doc(inline)]
 pub use optionable_derive::Optionable;
 
-use std::cell::RefCell;
-use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
-use std::rc::Rc;
-use std::sync::{Arc, Mutex};
+#[cfg(feature = "alloc")]
+use alloc::borrow::{Cow, ToOwned};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+#[cfg(feature = "alloc")]
+use alloc::rc::{Rc, Weak as RcWeak};
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::sync::{Arc, Weak as ArcWeak};
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "alloc")]
+use field_mask::{FieldMaskTree, PathSegment};
+
+use core::cell::{Cell, OnceCell, RefCell};
+use core::cmp::{Ordering, Reverse};
+use core::marker::PhantomData;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use core::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping,
+};
+use core::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+use core::pin::Pin;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::ffi::{OsStr, OsString};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, OnceLock, RwLock, TryLockError};
+#[cfg(feature = "std")]
+use std::time::{Instant, SystemTime};
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub mod __private;
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+#[cfg(feature = "arrayvec")]
+mod arrayvec;
+#[cfg(feature = "bigdecimal")]
+mod bigdecimal;
+#[cfg(feature = "bytes")]
+mod bytes;
 #[cfg(feature = "chrono")]
 mod chrono;
+#[cfg(feature = "clap")]
+pub mod clap;
+#[cfg(feature = "alloc")]
+mod convert;
+#[cfg(feature = "env")]
+pub mod env;
+#[cfg(feature = "alloc")]
+pub mod field_mask;
 #[cfg(feature = "serde_json")]
-mod serde_json;
+pub mod fields_v1;
+#[cfg(feature = "alloc")]
+mod field_paths;
+#[cfg(feature = "indexmap")]
+mod indexmap;
+#[cfg(feature = "laws")]
+pub mod laws;
+#[cfg(feature = "alloc")]
+pub mod layered;
+#[cfg(feature = "alloc")]
+pub mod managed_fields;
+#[cfg(feature = "ordered-float")]
+mod ordered_float;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "prost")]
+mod prost;
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal;
+#[cfg(feature = "schemars")]
+mod schemars;
+#[cfg(feature = "serde_json")]
+pub mod serde_json;
+#[cfg(feature = "serde_yaml")]
+pub mod serde_yaml;
+#[cfg(feature = "smallvec")]
+mod smallvec;
+#[cfg(feature = "serde_json")]
+pub mod strategic_merge;
+#[cfg(feature = "alloc")]
+pub mod three_way;
+#[cfg(feature = "time")]
+mod time;
+#[cfg(feature = "tinyvec")]
+mod tinyvec;
+#[cfg(feature = "toml")]
+pub mod toml;
+#[cfg(feature = "url")]
+mod url;
+#[cfg(feature = "utoipa")]
+mod utoipa;
+#[cfg(feature = "uuid")]
+mod uuid;
+#[cfg(feature = "alloc")]
+pub mod validate;
 
 /// Marker trait that associated this type with a corresponding type where potential
 /// inner sub-fields are recursively optional if possible for the given use case of the type.
@@ -129,11 +521,116 @@ mod serde_json;
 pub trait Optionable {
     /// The associated type where fields (if possible for the given use case) are recursively optional.
     type Optioned;
+
+    /// Checks whether the optioned value sets nothing, i.e. merging it would leave every value untouched.
+    /// Values without inner structure (e.g. `i32`) always count as set, hence the default returns `false`.
+    /// Impls for types that merge their optioned value into an existing value instead of replacing it
+    /// (e.g. pointers, cells and newtype wrappers) should forward the check to the contained value.
+    /// Derived optioned structs are empty if none of their non-required fields is set (recursively),
+    /// maps are empty if they have no entries.
+    fn is_empty_optioned(optioned: &Self::Optioned) -> bool {
+        let _ = optioned;
+        false
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Extension of the [`trait@Optionable`] trait to convert between a type and its optioned type
+/// and to merge optioned values into an existing value.
+///
+/// The [`derive@Optionable`]-derive macro implements this trait for the derived type as well unless
+/// `#[optionable(no_convert)]` is set. In that case all non-required fields need to implement this trait.
+/// The optioned type implements it as well (as its own optioned type) if the optioned types of its fields do:
+/// merging two optioned values combines them with the set fields of the merged value taking precedence.
+pub trait OptionableConvert: Sized + Optionable {
+    /// Converts the value into its optioned type with all (sub-)fields set.
+    fn into_optioned(self) -> Self::Optioned;
+
+    /// Tries to build the value from its optioned type.
+    ///
+    /// # Errors
+    /// - If (sub-)fields that are required to build the full type are not set.
+    fn try_from_optioned(value: Self::Optioned) -> Result<Self, Error>;
+
+    /// Merges the set (sub-)fields of an optioned value into this value.
+    /// Unset fields of the optioned value leave the corresponding fields untouched.
+    /// Collections without keys like `Vec` are replaced as a whole, maps are merged per key.
+    ///
+    /// # Errors
+    /// - If a (sub-)field that is not present in `self` is set but misses required (sub-)fields.
+    ///   The other fields are still merged in this case.
+    fn merge(&mut self, other: Self::Optioned) -> Result<(), Error>;
+}
+
+#[cfg(feature = "alloc")]
+/// Extension of the [`trait@Optionable`] trait to list the (sub-)fields an optioned value sets,
+/// e.g. to record which fields a patch touches for audit logging.
+///
+/// The paths consist of the field names separated by dots, map entries are appended as `[<key>]`,
+/// e.g. `spec.template.metadata.labels[app]`, where backslashes and closing brackets in the key are escaped
+/// with a backslash. The value itself is denoted by the empty path.
+/// The [`derive@Optionable`]-derive macro implements this trait for the derived type as well unless
+/// `#[optionable(no_field_paths)]` is set. The derived field and variant names honor the `serde` renames.
+/// The same paths are used by the [`field_mask::FieldMask`] to select (sub-)fields.
+pub trait OptionableFieldPaths: Optionable {
+    /// Appends the paths of the (sub-)fields set by the optioned value to `paths`, `prefix` being the path
+    /// of the optioned value itself.
+    /// Values without inner structure and collections without keys are set as a whole, hence the default
+    /// appends `prefix`.
+    fn set_field_paths_optioned(optioned: &Self::Optioned, prefix: &str, paths: &mut Vec<String>) {
+        let _ = optioned;
+        paths.push(prefix.to_owned());
+    }
+
+    /// Returns the paths of the (sub-)fields set by the optioned value.
+    fn set_field_paths(optioned: &Self::Optioned) -> Vec<String> {
+        let mut paths = vec![];
+        Self::set_field_paths_optioned(optioned, "", &mut paths);
+        paths
+    }
+
+    /// Unsets the (sub-)fields of the optioned value that are not selected by the mask.
+    /// Only called for masks that do not select the value as a whole. Required fields are kept.
+    /// Values without inner structure can only be kept or unset as a whole by their parent,
+    /// hence the default keeps the value.
+    fn mask_optioned(optioned: &mut Self::Optioned, mask: &FieldMaskTree) {
+        let _ = (optioned, mask);
+    }
+
+    /// Whether the path (split into its segments) addresses the value itself or one of its (sub-)fields.
+    /// The default only accepts the empty path of the value itself.
+    #[must_use]
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        segments.is_empty()
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Error when converting an optioned value into the full type fails
+/// as (sub-)fields required for the full type are not set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Error {
+    /// The paths of the missing fields, nested fields are separated by a dot, e.g. `address.street`.
+    pub missing_fields: Vec<String>,
 }
 
+#[cfg(feature = "alloc")]
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "missing fields: {}", self.missing_fields.join(", "))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl core::error::Error for Error {}
+
 // Blanket implementation for references to `Optionable` types.
 impl<'a, T: Optionable> Optionable for &'a T {
     type Optioned = &'a T::Optioned;
+
+    fn is_empty_optioned(optioned: &&'a T::Optioned) -> bool {
+        T::is_empty_optioned(optioned)
+    }
 }
 
 /// Helper macro to generate an impl for `Optionalable` where the `Optioned` type
@@ -142,18 +639,140 @@ macro_rules! impl_optional_self {
     ($($t:ty),* $(,)?) => {
         $(impl Optionable for $t{
             type Optioned = Self;
-        })*
+        }
+
+        #[cfg(feature = "alloc")]
+        crate::impl_convert_self!([] $t);
+        #[cfg(feature = "alloc")]
+        crate::impl_field_paths_whole!([] $t);
+        #[cfg(feature = "alloc")]
+        crate::impl_three_way_whole!([] $t);
+        #[cfg(feature = "serde_json")]
+        crate::impl_fields_v1_whole!([] $t);
+        #[cfg(feature = "serde_json")]
+        crate::impl_strategic_merge_whole!([] $t);)*
     };
 }
+#[allow(unused_imports)]
 pub(crate) use impl_optional_self;
 
+/// Helper macro to generate an impl for `OptionableConvert` for types that are their own `Optioned` type.
+/// The generic parameters of the impl are passed in brackets, e.g. `impl_convert_self!([Tz: TimeZone] DateTime<Tz>)`.
+#[cfg(feature = "alloc")]
+macro_rules! impl_convert_self {
+    ([$($generics:tt)*] $t:ty) => {
+        impl<$($generics)*> crate::OptionableConvert for $t {
+            fn into_optioned(self) -> Self {
+                self
+            }
+
+            fn try_from_optioned(value: Self) -> Result<Self, crate::Error> {
+                Ok(value)
+            }
+
+            fn merge(&mut self, other: Self) -> Result<(), crate::Error> {
+                *self = other;
+                Ok(())
+            }
+        }
+    };
+}
+#[cfg(feature = "alloc")]
+pub(crate) use impl_convert_self;
+
+/// Helper macro to generate an impl for `OptionableFieldPaths` for types that are set as a whole.
+/// The generic parameters of the impl are passed in brackets, e.g. `impl_field_paths_whole!([T: Optionable] Vec<T>)`.
+#[cfg(feature = "alloc")]
+macro_rules! impl_field_paths_whole {
+    ([$($generics:tt)*] $t:ty) => {
+        impl<$($generics)*> crate::OptionableFieldPaths for $t {}
+    };
+}
+#[cfg(feature = "alloc")]
+pub(crate) use impl_field_paths_whole;
+
+/// Helper macro to generate an impl for `OptionableThreeWayMerge` for types that are merged as a whole.
+/// The generic parameters of the impl are passed in brackets, e.g. `impl_three_way_whole!([T: OptionableThreeWayMerge] Vec<T>)`.
+#[cfg(feature = "alloc")]
+macro_rules! impl_three_way_whole {
+    ([$($generics:tt)*] $t:ty) => {
+        impl<$($generics)*> crate::three_way::OptionableThreeWayMerge for $t {}
+    };
+}
+#[cfg(feature = "alloc")]
+pub(crate) use impl_three_way_whole;
+
+/// Helper macro to generate an impl for `OptionableFieldsV1` for types that are managed as a whole.
+/// The generic parameters of the impl are passed in brackets, e.g. `impl_fields_v1_whole!([T: OptionableFieldsV1] Vec<T>)`.
+#[cfg(feature = "serde_json")]
+macro_rules! impl_fields_v1_whole {
+    ([$($generics:tt)*] $t:ty) => {
+        impl<$($generics)*> crate::fields_v1::OptionableFieldsV1 for $t {}
+    };
+}
+#[cfg(feature = "serde_json")]
+pub(crate) use impl_fields_v1_whole;
+
+/// Helper macro to generate an impl for `OptionableStrategicMerge` for types that are replaced as a whole.
+/// The generic parameters of the impl are passed in brackets, e.g. `impl_strategic_merge_whole!([T: OptionableStrategicMerge] Cell<T>)`.
+#[cfg(feature = "serde_json")]
+macro_rules! impl_strategic_merge_whole {
+    ([$($generics:tt)*] $t:ty) => {
+        impl<$($generics)*> crate::strategic_merge::OptionableStrategicMerge for $t {}
+    };
+}
+#[cfg(feature = "serde_json")]
+pub(crate) use impl_strategic_merge_whole;
+
 impl_optional_self!(
     // Rust primitives don't have inner structure, https://doc.rust-lang.org/rust-by-example/primitives.html
     i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, char, bool,
     // Other types without inner structure
-    String, &str
+    &str
 );
 
+#[cfg(feature = "alloc")]
+impl_optional_self!(String);
+
+impl_optional_self!((), Ordering, RangeFull);
+
+// Integers that are known to not equal zero
+impl_optional_self!(
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize
+);
+impl_optional_self!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize
+);
+
+// Time, https://doc.rust-lang.org/std/time/index.html
+impl_optional_self!(Duration);
+#[cfg(feature = "std")]
+impl_optional_self!(Instant, SystemTime);
+
+// Networking primitives, https://doc.rust-lang.org/std/net/index.html
+impl_optional_self!(
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6
+);
+
+// Paths and OS strings, https://doc.rust-lang.org/std/path/index.html
+#[cfg(feature = "std")]
+impl_optional_self!(PathBuf, &Path, OsString, &OsStr);
+
 /// Helper macro to generate an impl for `Optionable` for Containers.
 /// Containers can be made optional by getting a corresponding container over the associated optional type.
 macro_rules! impl_container {
@@ -164,34 +783,193 @@ macro_rules! impl_container {
     };
 }
 
-impl_container!(
-    Option,
-    // Collections without an extra key, https://doc.rust-lang.org/std/collections/index.html
-    Vec, VecDeque, LinkedList, BTreeSet, BinaryHeap, // Smart pointer and sync-container
-    Box, Rc, Arc, RefCell, Mutex,
-);
+// Collections without an extra key, https://doc.rust-lang.org/std/collections/index.html
+#[cfg(feature = "alloc")]
+impl_container!(Vec, VecDeque, LinkedList, BTreeSet, BinaryHeap);
+
+// Sync-container, empty if the contained value is empty. A value that is currently borrowed mutably counts as set.
+impl<T: Optionable> Optionable for RefCell<T> {
+    type Optioned = RefCell<T::Optioned>;
+
+    fn is_empty_optioned(optioned: &RefCell<T::Optioned>) -> bool {
+        optioned
+            .try_borrow()
+            .is_ok_and(|inner| T::is_empty_optioned(&inner))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Optionable> Optionable for Mutex<T> {
+    type Optioned = Mutex<T::Optioned>;
+
+    fn is_empty_optioned(optioned: &Mutex<T::Optioned>) -> bool {
+        match optioned.try_lock() {
+            Ok(inner) => T::is_empty_optioned(&inner),
+            Err(TryLockError::Poisoned(err)) => T::is_empty_optioned(&err.into_inner()),
+            Err(TryLockError::WouldBlock) => false,
+        }
+    }
+}
+
+// An unset `Option` expresses an unset field.
+impl<T: Optionable> Optionable for Option<T> {
+    type Optioned = Option<T::Optioned>;
+
+    fn is_empty_optioned(optioned: &Option<T::Optioned>) -> bool {
+        optioned.is_none()
+    }
+}
+
+/// Helper macro to generate an impl for `Optionable` for smart pointers
+/// that are empty if the pointed to value is empty.
+#[cfg(feature = "alloc")]
+macro_rules! impl_pointer {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: Optionable> Optionable for $t<T>{
+            type Optioned = $t<T::Optioned>;
+
+            fn is_empty_optioned(optioned: &$t<T::Optioned>) -> bool {
+                T::is_empty_optioned(optioned)
+            }
+        })*
+    };
+}
+
+#[cfg(feature = "alloc")]
+impl_pointer!(Box, Rc, Arc);
+
+// Further pointer, cell and sync-container. Their value can't be inspected by reference, hence they count as set.
+impl_container!(Pin, Cell);
+#[cfg(feature = "alloc")]
+impl_container!(RcWeak, ArcWeak);
+
+#[cfg(feature = "std")]
+impl<T: Optionable> Optionable for RwLock<T> {
+    type Optioned = RwLock<T::Optioned>;
+
+    fn is_empty_optioned(optioned: &RwLock<T::Optioned>) -> bool {
+        match optioned.try_read() {
+            Ok(inner) => T::is_empty_optioned(&inner),
+            Err(TryLockError::Poisoned(err)) => T::is_empty_optioned(&err.into_inner()),
+            Err(TryLockError::WouldBlock) => false,
+        }
+    }
+}
+
+/// Helper macro to generate an impl for `Optionable` for once cells
+/// that are empty if they are uninitialized or their value is empty.
+macro_rules! impl_once_cell {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: Optionable> Optionable for $t<T>{
+            type Optioned = $t<T::Optioned>;
+
+            fn is_empty_optioned(optioned: &$t<T::Optioned>) -> bool {
+                optioned.get().is_none_or(T::is_empty_optioned)
+            }
+        })*
+    };
+}
+
+impl_once_cell!(OnceCell);
+#[cfg(feature = "std")]
+impl_once_cell!(OnceLock);
+
+/// Helper macro to generate an impl for `Optionable` for newtype wrapper around a single value
+/// that are empty if the wrapped value is empty.
+macro_rules! impl_newtype {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: Optionable> Optionable for $t<T>{
+            type Optioned = $t<T::Optioned>;
+
+            fn is_empty_optioned(optioned: &$t<T::Optioned>) -> bool {
+                T::is_empty_optioned(&optioned.0)
+            }
+        })*
+    };
+}
+
+// Wrapper adjusting ordering or arithmetic behaviour
+impl_newtype!(Reverse, Wrapping, Saturating);
+
+// Ranges, https://doc.rust-lang.org/std/ops/index.html, are replaced as a whole.
+impl_container!(Range, RangeInclusive, RangeFrom, RangeTo, RangeToInclusive);
+
+// Arrays are merged element-wise, hence they are empty if all elements are empty.
+impl<T: Optionable, const N: usize> Optionable for [T; N] {
+    type Optioned = [T::Optioned; N];
+
+    fn is_empty_optioned(optioned: &[T::Optioned; N]) -> bool {
+        optioned.iter().all(T::is_empty_optioned)
+    }
+}
+
+#[cfg(feature = "alloc")]
+// Whether the value is borrowed or owned is irrelevant for the optioned type, hence it resolves
+// to the optioned type of the owned variant.
+impl<B: ?Sized + ToOwned> Optionable for Cow<'_, B>
+where
+    B::Owned: Optionable,
+{
+    type Optioned = <B::Owned as Optionable>::Optioned;
+
+    fn is_empty_optioned(optioned: &Self::Optioned) -> bool {
+        B::Owned::is_empty_optioned(optioned)
+    }
+}
+
+// `PhantomData` carries no data, hence no `Optionable` bound is required for the marker type.
+impl<T: ?Sized> Optionable for PhantomData<T> {
+    type Optioned = Self;
+
+    fn is_empty_optioned(_: &Self) -> bool {
+        true
+    }
+}
 
 impl<T: Optionable, E> Optionable for Result<T, E> {
     type Optioned = Result<T::Optioned, E>;
 }
 
+#[cfg(feature = "std")]
 impl<T: Optionable, S> Optionable for HashSet<T, S> {
     type Optioned = HashSet<T::Optioned, S>;
 }
 
+#[cfg(feature = "alloc")]
 impl<K, T: Optionable> Optionable for BTreeMap<K, T> {
     type Optioned = BTreeMap<K, T::Optioned>;
+
+    fn is_empty_optioned(optioned: &BTreeMap<K, T::Optioned>) -> bool {
+        optioned.is_empty()
+    }
 }
 
+#[cfg(feature = "std")]
 impl<K, T: Optionable, S> Optionable for HashMap<K, T, S> {
     type Optioned = HashMap<K, T::Optioned, S>;
+
+    fn is_empty_optioned(optioned: &HashMap<K, T::Optioned, S>) -> bool {
+        optioned.is_empty()
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use crate::Optionable;
+    use std::borrow::Cow;
+    use std::cell::{Cell, OnceCell, RefCell};
+    use std::cmp::{Ordering, Reverse};
     use std::collections::{BTreeMap, HashMap};
     use std::fmt::Error;
+    use std::marker::PhantomData;
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+    use std::num::{NonZeroU32, Saturating, Wrapping};
+    use std::ops::{Range, RangeInclusive};
+    use std::path::{Path, PathBuf};
+    use std::pin::Pin;
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex, OnceLock, RwLock};
+    use std::time::{Duration, SystemTime};
 
     #[test]
     /// Check that an exemplary primitive type like `i32` resolves to itself as `Optioned` type.
@@ -224,6 +1002,51 @@ mod tests {
         let _: <Vec<i64> as Optionable>::Optioned = a;
     }
 
+    #[test]
+    /// Check the emptiness of unset options, maps without entries and pointers to them.
+    fn is_empty_optioned() {
+        assert!(<Option<i32> as Optionable>::is_empty_optioned(&None));
+        assert!(!<Option<i32> as Optionable>::is_empty_optioned(&Some(1)));
+        assert!(<BTreeMap<i32, i32> as Optionable>::is_empty_optioned(
+            &BTreeMap::new()
+        ));
+        assert!(!<HashMap<i32, i32> as Optionable>::is_empty_optioned(
+            &HashMap::from([(1, 2)])
+        ));
+        assert!(<Box<Option<i32>> as Optionable>::is_empty_optioned(
+            &Box::new(None)
+        ));
+        assert!(!<i32 as Optionable>::is_empty_optioned(&0));
+    }
+
+    #[test]
+    /// Check that containers merging into the contained value forward the emptiness check to it,
+    /// while collections replaced as a whole always count as set.
+    fn is_empty_optioned_nested() {
+        type Inner = BTreeMap<i32, i32>;
+        let empty = BTreeMap::new;
+        let set = || BTreeMap::from([(1, 2)]);
+        assert!(<RefCell<Inner> as Optionable>::is_empty_optioned(&RefCell::new(empty())));
+        assert!(!<RefCell<Inner> as Optionable>::is_empty_optioned(&RefCell::new(set())));
+        let cell = RefCell::new(empty());
+        let _borrowed = cell.borrow_mut();
+        assert!(!<RefCell<Inner> as Optionable>::is_empty_optioned(&cell));
+        assert!(<RwLock<Inner> as Optionable>::is_empty_optioned(&RwLock::new(empty())));
+        assert!(<Mutex<Inner> as Optionable>::is_empty_optioned(&Mutex::new(empty())));
+        assert!(!<Mutex<Inner> as Optionable>::is_empty_optioned(&Mutex::new(set())));
+        assert!(<OnceCell<Inner> as Optionable>::is_empty_optioned(&OnceCell::new()));
+        assert!(<OnceLock<Inner> as Optionable>::is_empty_optioned(&OnceLock::from(empty())));
+        assert!(!<OnceLock<Inner> as Optionable>::is_empty_optioned(&OnceLock::from(set())));
+        assert!(<Reverse<Inner> as Optionable>::is_empty_optioned(&Reverse(empty())));
+        assert!(!<Wrapping<Inner> as Optionable>::is_empty_optioned(&Wrapping(set())));
+        assert!(<[Inner; 2] as Optionable>::is_empty_optioned(&[empty(), empty()]));
+        assert!(!<[Inner; 2] as Optionable>::is_empty_optioned(&[empty(), set()]));
+        assert!(<Rc<Option<Inner>> as Optionable>::is_empty_optioned(&Rc::new(None)));
+        assert!(<PhantomData<i32> as Optionable>::is_empty_optioned(&PhantomData));
+        assert!(!<Vec<Inner> as Optionable>::is_empty_optioned(&vec![empty()]));
+        assert!(!<Option<Inner> as Optionable>::is_empty_optioned(&Some(empty())));
+    }
+
     #[test]
     /// Check that `Result` implements optionable.
     fn result() {
@@ -240,4 +1063,94 @@ mod tests {
         let a = BTreeMap::from([(1, "a".to_owned())]);
         let _: <BTreeMap<i32, String> as Optionable>::Optioned = a;
     }
+
+    #[test]
+    /// Check that the cell and lock containers implement optionable.
+    fn cell_and_lock() {
+        let _: <Cell<i32> as Optionable>::Optioned = Cell::new(1);
+        let _: <RefCell<i32> as Optionable>::Optioned = RefCell::new(1);
+        let _: <OnceCell<i32> as Optionable>::Optioned = OnceCell::from(1);
+        let _: <RwLock<i32> as Optionable>::Optioned = RwLock::new(1);
+        let _: <OnceLock<i32> as Optionable>::Optioned = OnceLock::from(1);
+    }
+
+    #[test]
+    /// Check that smart pointers and their weak counterparts implement optionable.
+    fn pointer() {
+        let a = Rc::new(1);
+        let _: <std::rc::Weak<i32> as Optionable>::Optioned = Rc::downgrade(&a);
+        let a = Arc::new(1);
+        let _: <std::sync::Weak<i32> as Optionable>::Optioned = Arc::downgrade(&a);
+        let _: <Pin<Box<i32>> as Optionable>::Optioned = Box::pin(1);
+    }
+
+    #[test]
+    /// Check that `Cow` resolves to the optioned type of the owned variant.
+    fn cow() {
+        let _: <Cow<'_, str> as Optionable>::Optioned = "a".to_owned();
+        let _: <Cow<'_, [i32]> as Optionable>::Optioned = vec![1, 2];
+    }
+
+    #[test]
+    /// Check that `PhantomData` implements optionable also for non-optionable type parameters.
+    fn phantom_data() {
+        struct Marker;
+        let _: <PhantomData<Marker> as Optionable>::Optioned = PhantomData;
+    }
+
+    #[test]
+    /// Check that wrapper adjusting ordering or arithmetic implement optionable.
+    fn wrapper() {
+        let _: <Reverse<i32> as Optionable>::Optioned = Reverse(1);
+        let _: <Wrapping<u8> as Optionable>::Optioned = Wrapping(1);
+        let _: <Saturating<u8> as Optionable>::Optioned = Saturating(1);
+    }
+
+    #[test]
+    /// Check that non-zero integers implement optionable.
+    fn non_zero() {
+        let _: <NonZeroU32 as Optionable>::Optioned = NonZeroU32::MIN;
+    }
+
+    #[test]
+    /// Check that time types implement optionable.
+    fn time() {
+        let _: <Duration as Optionable>::Optioned = Duration::from_secs(1);
+        let _: <SystemTime as Optionable>::Optioned = SystemTime::UNIX_EPOCH;
+    }
+
+    #[test]
+    /// Check that `Ordering` implements optionable.
+    fn ordering() {
+        let _: <Ordering as Optionable>::Optioned = Ordering::Less;
+    }
+
+    #[test]
+    /// Check that ranges implement optionable.
+    fn range() {
+        let _: <Range<i32> as Optionable>::Optioned = 0..1;
+        let _: <RangeInclusive<i32> as Optionable>::Optioned = 0..=1;
+        let _: <std::ops::RangeFull as Optionable>::Optioned = ..;
+    }
+
+    #[test]
+    /// Check that networking primitives implement optionable.
+    fn net() {
+        let _: <IpAddr as Optionable>::Optioned = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let _: <SocketAddr as Optionable>::Optioned =
+            SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 80);
+    }
+
+    #[test]
+    /// Check that paths implement optionable.
+    fn path() {
+        let _: <PathBuf as Optionable>::Optioned = PathBuf::from("/a");
+        let _: <&Path as Optionable>::Optioned = Path::new("/a");
+    }
+
+    #[test]
+    /// Check that arrays implement optionable.
+    fn array() {
+        let _: <[i32; 2] as Optionable>::Optioned = [1, 2];
+    }
 }
@@ -0,0 +1,326 @@
+//! Field ownership tracking for applies of several managers, like the `managedFields` of Kubernetes server-side apply.
+//!
+//! Each apply configuration of a manager is an optioned value, the manager owns the (sub-)fields it sets
+//! (see [`OptionableFieldPaths`] for the paths). Applying a different value for a field owned by another manager
+//! is a conflict unless forced, which takes over the ownership. Applying the same value shares the ownership.
+//! Fields a manager no longer applies are released and unset if no other manager owns them.
+//! ```rust
+//! # use optionable::Optionable;
+//! # use optionable::managed_fields::{ApplyError, ManagedFields};
+//! #[derive(Optionable)]
+//! #[optionable(derive(Clone, Debug, Default, PartialEq))]
+//! struct Deployment {
+//!     replicas: u32,
+//!     image: String,
+//! }
+//!
+//! let mut managed = ManagedFields::<Deployment>::new();
+//! managed.apply("kubectl", DeploymentOpt { replicas: Some(2), image: Some("nginx".to_owned()) }, false).unwrap();
+//! let Err(ApplyError::Conflicts(conflicts)) =
+//!     managed.apply("hpa", DeploymentOpt { replicas: Some(5), image: None }, false)
+//! else {
+//!     panic!("expected a conflict");
+//! };
+//! assert_eq!((conflicts[0].manager.as_str(), conflicts[0].path.as_str()), ("kubectl", "replicas"));
+//!
+//! managed.apply("hpa", DeploymentOpt { replicas: Some(5), image: None }, true).unwrap();
+//! assert_eq!(managed.value().replicas, Some(5));
+//! assert_eq!(managed.owners("replicas"), vec!["hpa"]);
+//! assert_eq!(managed.owners("image"), vec!["kubectl"]);
+//! ```
+use crate::field_mask::{FieldMask, FieldMaskError};
+use crate::layered::is_sub_path;
+use crate::{Error, OptionableConvert, OptionableFieldPaths};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+/// The value resulting from the applies of several managers together with the field paths each manager owns.
+pub struct ManagedFields<T: OptionableConvert> {
+    value: T::Optioned,
+    managers: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl<T: OptionableConvert> Default for ManagedFields<T>
+where
+    T::Optioned: Default,
+{
+    fn default() -> Self {
+        Self::from_value(T::Optioned::default())
+    }
+}
+
+impl<T: OptionableConvert> ManagedFields<T> {
+    /// Creates an empty value without any managers.
+    #[must_use]
+    pub fn new() -> Self
+    where
+        T::Optioned: Default,
+    {
+        Self::default()
+    }
+
+    /// Starts from an existing value whose fields are not owned by any manager.
+    #[must_use]
+    pub fn from_value(value: T::Optioned) -> Self {
+        Self {
+            value,
+            managers: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the value resulting from all applies.
+    #[must_use]
+    pub fn value(&self) -> &T::Optioned {
+        &self.value
+    }
+
+    /// Returns the value resulting from all applies.
+    #[must_use]
+    pub fn into_value(self) -> T::Optioned {
+        self.value
+    }
+
+    /// Returns the paths of the (sub-)fields owned per manager.
+    #[must_use]
+    pub fn managers(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.managers
+    }
+
+    /// Returns the paths of the (sub-)fields owned by the manager, `None` if it owns nothing.
+    #[must_use]
+    pub fn managed_fields(&self, manager: &str) -> Option<&BTreeSet<String>> {
+        self.managers.get(manager)
+    }
+
+    /// Returns the managers owning the (sub-)field at `path` or one of its parent fields.
+    #[must_use]
+    pub fn owners(&self, path: &str) -> Vec<&str> {
+        self.managers
+            .iter()
+            .filter(|(_, owned)| {
+                owned
+                    .iter()
+                    .any(|owned| owned == path || is_sub_path(path, owned))
+            })
+            .map(|(manager, _)| manager.as_str())
+            .collect()
+    }
+}
+
+impl<T: OptionableConvert + OptionableFieldPaths> ManagedFields<T>
+where
+    T::Optioned: OptionableConvert<Optioned = T::Optioned> + Clone + PartialEq,
+{
+    /// Applies the configuration of the manager, which owns exactly the (sub-)fields set by it afterwards.
+    /// Fields of the previous configuration of the manager that are not owned by any other manager are unset.
+    /// With `force` the conflicting fields of other managers are taken over, otherwise nothing changes on conflicts.
+    ///
+    /// # Errors
+    /// - If the configuration changes (sub-)fields owned by other managers without `force`.
+    /// - If a set (sub-)field misses (sub-)fields required by the full type.
+    /// - If the field paths listed by [`OptionableFieldPaths`] are malformed.
+    pub fn apply(
+        &mut self,
+        manager: impl Into<String>,
+        config: T::Optioned,
+        force: bool,
+    ) -> Result<(), ApplyError> {
+        let manager = manager.into();
+        let paths = T::set_field_paths(&config)
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        let conflicts = self.conflicts(&manager, &config, &paths)?;
+        if !conflicts.is_empty() && !force {
+            return Err(ApplyError::Conflicts(conflicts));
+        }
+
+        let mut managers = self.managers.clone();
+        for conflict in &conflicts {
+            if let Some(owned) = managers.get_mut(&conflict.manager) {
+                owned.retain(|owned| !overlaps(owned, &conflict.path));
+            }
+        }
+        let previous = managers.remove(&manager).unwrap_or_default();
+        // fields shared with other managers are kept
+        let released = previous
+            .iter()
+            .filter(|path| !paths.contains(*path))
+            .filter(|path| {
+                !managers
+                    .values()
+                    .flatten()
+                    .any(|owned| overlaps(owned, path))
+            })
+            .collect::<Vec<_>>();
+        let mut value = self.value.clone();
+        if !released.is_empty() {
+            let kept = T::set_field_paths(&value)
+                .into_iter()
+                .filter(|path| !released.iter().any(|released| overlaps(path, released)));
+            value = project::<T>(&value, &kept.collect::<Vec<_>>())?;
+        }
+        value.merge(config).map_err(ApplyError::Merge)?;
+
+        managers.insert(manager, paths);
+        managers.retain(|_, owned| !owned.is_empty());
+        self.value = value;
+        self.managers = managers;
+        Ok(())
+    }
+
+    /// Returns the (sub-)fields owned by other managers that the configuration sets to a different value.
+    /// The conflicts are reported with the more specific of the owned and the applied path.
+    fn conflicts(
+        &self,
+        manager: &str,
+        config: &T::Optioned,
+        paths: &BTreeSet<String>,
+    ) -> Result<Vec<Conflict>, ApplyError> {
+        let mut conflicts = vec![];
+        for (other, owned) in self.managers.iter().filter(|(other, _)| *other != manager) {
+            for owned in owned {
+                for path in paths.iter().filter(|path| overlaps(owned, path)) {
+                    let path = if path.len() > owned.len() { path } else { owned };
+                    let selected = core::slice::from_ref(path);
+                    let conflict = Conflict {
+                        manager: other.clone(),
+                        path: path.clone(),
+                    };
+                    if !conflicts.contains(&conflict)
+                        && project::<T>(&self.value, selected)? != project::<T>(config, selected)?
+                    {
+                        conflicts.push(conflict);
+                    }
+                }
+            }
+        }
+        Ok(conflicts)
+    }
+}
+
+/// Whether one of the paths addresses the same or a (sub-)field of the other.
+fn overlaps(a: &str, b: &str) -> bool {
+    a == b || is_sub_path(a, b) || is_sub_path(b, a)
+}
+
+/// Returns the optioned value with only the (sub-)fields at the given set field paths set.
+fn project<T: OptionableFieldPaths>(
+    optioned: &T::Optioned,
+    paths: &[String],
+) -> Result<T::Optioned, ApplyError>
+where
+    T::Optioned: Clone,
+{
+    let mut optioned = optioned.clone();
+    let tree = FieldMask::new(paths)
+        .tree()
+        .map_err(ApplyError::InvalidPaths)?;
+    if !tree.is_whole() {
+        T::mask_optioned(&mut optioned, &tree);
+    }
+    Ok(optioned)
+}
+
+/// A (sub-)field owned by another manager that an apply would change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The manager owning the field.
+    pub manager: String,
+    /// The path of the field.
+    pub path: String,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} (owned by {})", self.path, self.manager)
+    }
+}
+
+/// Error for applies that can't be performed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyError {
+    /// The configuration changes (sub-)fields owned by other managers.
+    Conflicts(Vec<Conflict>),
+    /// The resulting value misses (sub-)fields required by the full type.
+    Merge(Error),
+    /// The field paths listed by [`OptionableFieldPaths`] are malformed, e.g. by a hand-written impl.
+    InvalidPaths(FieldMaskError),
+}
+
+impl Display for ApplyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Conflicts(conflicts) => {
+                write!(f, "conflicting fields: ")?;
+                for (i, conflict) in conflicts.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{conflict}")?;
+                }
+                Ok(())
+            }
+            Self::Merge(err) => write!(f, "{err}"),
+            Self::InvalidPaths(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl core::error::Error for ApplyError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Conflicts(_) => None,
+            Self::Merge(err) => Some(err),
+            Self::InvalidPaths(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApplyError, Conflict, ManagedFields};
+    use alloc::borrow::ToOwned;
+    use alloc::collections::BTreeMap;
+    use alloc::vec;
+
+    #[test]
+    /// Check that map entries are owned per key, equal values are shared
+    /// and released entries are only unset if no other manager owns them.
+    fn map_entries() {
+        let mut managed = ManagedFields::<BTreeMap<&str, i32>>::new();
+        managed
+            .apply("a", BTreeMap::from([("x", 1), ("y", 2)]), false)
+            .unwrap();
+        managed
+            .apply("b", BTreeMap::from([("x", 1), ("z", 3)]), false)
+            .unwrap();
+        assert_eq!(managed.owners("[x]"), vec!["a", "b"]);
+
+        assert_eq!(
+            managed.apply("b", BTreeMap::from([("y", 4)]), false),
+            Err(ApplyError::Conflicts(vec![Conflict {
+                manager: "a".to_owned(),
+                path: "[y]".to_owned(),
+            }]))
+        );
+        managed.apply("a", BTreeMap::new(), false).unwrap();
+        assert_eq!(managed.value(), &BTreeMap::from([("x", 1), ("z", 3)]));
+        assert_eq!(managed.managed_fields("a"), None);
+    }
+
+    #[test]
+    /// Check that entries with keys containing closing brackets are kept when other entries are released.
+    fn escaped_keys() {
+        let mut managed = ManagedFields::<BTreeMap<&str, i32>>::new();
+        managed.apply("a", BTreeMap::from([("x", 1)]), false).unwrap();
+        managed
+            .apply("b", BTreeMap::from([("y]z", 2)]), false)
+            .unwrap();
+        assert_eq!(managed.owners(r"[y\]z]"), vec!["b"]);
+        managed.apply("a", BTreeMap::new(), false).unwrap();
+        assert_eq!(managed.value(), &BTreeMap::from([("y]z", 2)]));
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{impl_convert_self, impl_field_paths_whole, impl_three_way_whole, Optionable};
+use ordered_float::{FloatCore, NotNan, OrderedFloat};
+
+// The wrapped floats don't have inner structure.
+impl<T> Optionable for OrderedFloat<T> {
+    type Optioned = Self;
+}
+
+impl<T> Optionable for NotNan<T> {
+    type Optioned = Self;
+}
+
+impl_convert_self!([T] OrderedFloat<T>);
+impl_convert_self!([T] NotNan<T>);
+impl_field_paths_whole!([T] OrderedFloat<T>);
+impl_field_paths_whole!([T] NotNan<T>);
+impl_three_way_whole!([T: FloatCore] OrderedFloat<T>);
+impl_three_way_whole!([T: PartialEq] NotNan<T>);
+#[cfg(feature = "serde_json")]
+crate::impl_fields_v1_whole!([T] OrderedFloat<T>);
+#[cfg(feature = "serde_json")]
+crate::impl_fields_v1_whole!([T] NotNan<T>);
+#[cfg(feature = "serde_json")]
+crate::impl_strategic_merge_whole!([T] OrderedFloat<T>);
+#[cfg(feature = "serde_json")]
+crate::impl_strategic_merge_whole!([T] NotNan<T>);
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use ordered_float::OrderedFloat;
+
+    #[test]
+    /// Check that `OrderedFloat` implements optionable.
+    fn ordered_float() {
+        let _: <OrderedFloat<f64> as Optionable>::Optioned = OrderedFloat(1.0);
+    }
+}
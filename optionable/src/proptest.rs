@@ -0,0 +1,442 @@
+//! Strategies for random optioned values via [proptest](https://docs.rs/proptest/latest/proptest/),
+//! e.g. to fuzz the code applying patches.
+//!
+//! The `proptest` attribute of the derive macro implements [`OptionableStrategy`] for the type and
+//! `proptest::arbitrary::Arbitrary` for its optioned type. The strategies set every (sub-)field
+//! with a given probability (the density) and either produce arbitrary optioned values or patches that can
+//! be merged into a given value without error.
+//! ```rust
+//! # use optionable::{Optionable, OptionableConvert};
+//! # use optionable::proptest::OptionableStrategy;
+//! # use proptest::prelude::*;
+//! #[derive(Optionable, Clone, Debug)]
+//! #[optionable(proptest, derive(Debug))]
+//! struct Deployment {
+//!     name: String,
+//!     replicas: Option<u32>,
+//! }
+//!
+//! proptest!(|(patch in Deployment::optioned_strategy(0.2))| {
+//!     let _ = Deployment::try_from_optioned(patch);
+//! });
+//!
+//! let deployment = Deployment { name: "a".to_owned(), replicas: None };
+//! proptest!(|(patch in Deployment::patch_strategy(&deployment, 0.5))| {
+//!     let mut deployment = deployment.clone();
+//!     prop_assert!(deployment.merge(patch).is_ok());
+//! });
+//! ```
+use crate::Optionable;
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::collection::{btree_map, btree_set, hash_map, hash_set, vec, SizeRange};
+use proptest::strategy::{BoxedStrategy, Just, LazyJust, Strategy, Union};
+use std::borrow::Cow;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::ffi::OsString;
+use std::fmt::Debug;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating, Wrapping,
+};
+use std::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+/// The density of the `proptest::arbitrary::Arbitrary` impls of derived optioned types.
+pub const DEFAULT_DENSITY: f64 = 0.5;
+
+/// The number of elements of generated collections.
+fn size() -> SizeRange {
+    (0..8).into()
+}
+
+/// Returns a strategy for values of `strategy` that are set with the probability `density`.
+/// In contrast to `proptest::option::weighted` the densities `0.0` (never set) and `1.0` (always set) are valid.
+pub fn optional<S: Strategy + 'static>(
+    density: f64,
+    strategy: S,
+) -> BoxedStrategy<Option<S::Value>> {
+    if density <= 0.0 {
+        LazyJust::new(|| None).boxed()
+    } else if density >= 1.0 {
+        strategy.prop_map(Some).boxed()
+    } else {
+        proptest::option::weighted(density, strategy).boxed()
+    }
+}
+
+/// Extension trait providing `proptest` strategies for optioned values.
+pub trait OptionableStrategy: Optionable<Optioned: Debug> + 'static {
+    /// Returns a strategy for optioned values setting every (sub-)field with the probability `density`.
+    /// With a density of `1.0` all (sub-)fields are set and the values can be converted into the full type.
+    fn optioned_strategy(density: f64) -> BoxedStrategy<Self::Optioned>;
+
+    /// Returns a strategy for patches of `value` that can be merged into it without error, setting
+    /// every (sub-)field with the probability `density`.
+    fn patch_strategy(value: &Self, density: f64) -> BoxedStrategy<Self::Optioned>;
+}
+
+/// Helper macro to generate an impl for `OptionableStrategy` for types that are their own optioned type,
+/// the values are replaced as a whole.
+macro_rules! impl_strategy_self {
+    ($($t:ty),* $(,)?) => {
+        $(impl OptionableStrategy for $t {
+            fn optioned_strategy(_density: f64) -> BoxedStrategy<$t> {
+                any::<$t>().boxed()
+            }
+
+            fn patch_strategy(_value: &$t, _density: f64) -> BoxedStrategy<$t> {
+                any::<$t>().boxed()
+            }
+        })*
+    };
+}
+
+impl_strategy_self!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, char, bool, String
+);
+
+impl_strategy_self!((), Ordering, RangeFull);
+
+impl_strategy_self!(
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize,
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize
+);
+
+impl_strategy_self!(Duration, Instant, SystemTime);
+
+impl_strategy_self!(
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6
+);
+
+impl_strategy_self!(PathBuf, OsString);
+
+impl<T: ?Sized + 'static> OptionableStrategy for PhantomData<T> {
+    fn optioned_strategy(_density: f64) -> BoxedStrategy<PhantomData<T>> {
+        Just(PhantomData).boxed()
+    }
+
+    fn patch_strategy(_value: &PhantomData<T>, _density: f64) -> BoxedStrategy<PhantomData<T>> {
+        Just(PhantomData).boxed()
+    }
+}
+
+// An unset value is kept as is, a value that is not present yet has to be set completely.
+impl<T: OptionableStrategy> OptionableStrategy for Option<T> {
+    fn optioned_strategy(density: f64) -> BoxedStrategy<Option<T::Optioned>> {
+        optional(density, T::optioned_strategy(density))
+    }
+
+    fn patch_strategy(value: &Option<T>, density: f64) -> BoxedStrategy<Option<T::Optioned>> {
+        match value {
+            Some(value) => optional(density, T::patch_strategy(value, density)),
+            None => optional(density, T::optioned_strategy(1.0)),
+        }
+    }
+}
+
+impl<T: OptionableStrategy, E: Arbitrary + Clone + 'static> OptionableStrategy for Result<T, E> {
+    fn optioned_strategy(density: f64) -> BoxedStrategy<Result<T::Optioned, E>> {
+        Union::new([
+            T::optioned_strategy(density).prop_map(Ok).boxed(),
+            any::<E>().prop_map(Err).boxed(),
+        ])
+        .boxed()
+    }
+
+    fn patch_strategy(value: &Result<T, E>, density: f64) -> BoxedStrategy<Result<T::Optioned, E>> {
+        match value {
+            Ok(value) => Union::new([
+                T::patch_strategy(value, density).prop_map(Ok).boxed(),
+                Self::optioned_strategy(1.0),
+            ])
+            .boxed(),
+            Err(_) => Self::optioned_strategy(1.0),
+        }
+    }
+}
+
+/// Helper macro to generate an impl for `OptionableStrategy` for smart pointers and cells
+/// with a `new` constructor. The `$get` expression returns the pointed to value of `$value`.
+macro_rules! impl_strategy_pointer {
+    ($($t:ident: |$value:ident| $get:expr),* $(,)?) => {
+        $(impl<T: OptionableStrategy> OptionableStrategy for $t<T>
+        where
+            $t<T::Optioned>: Debug,
+        {
+            fn optioned_strategy(density: f64) -> BoxedStrategy<$t<T::Optioned>> {
+                T::optioned_strategy(density).prop_map($t::new).boxed()
+            }
+
+            fn patch_strategy($value: &$t<T>, density: f64) -> BoxedStrategy<$t<T::Optioned>> {
+                T::patch_strategy(&$get, density).prop_map($t::new).boxed()
+            }
+        })*
+    };
+}
+
+impl_strategy_pointer!(
+    Box: |value| **value,
+    Rc: |value| **value,
+    Arc: |value| **value,
+    RefCell: |value| value.borrow(),
+    Mutex: |value| value.lock().unwrap_or_else(PoisonError::into_inner),
+    RwLock: |value| value.read().unwrap_or_else(PoisonError::into_inner),
+);
+
+impl<T: OptionableStrategy + Copy> OptionableStrategy for Cell<T>
+where
+    T::Optioned: Copy,
+{
+    fn optioned_strategy(density: f64) -> BoxedStrategy<Cell<T::Optioned>> {
+        T::optioned_strategy(density).prop_map(Cell::new).boxed()
+    }
+
+    fn patch_strategy(value: &Cell<T>, density: f64) -> BoxedStrategy<Cell<T::Optioned>> {
+        T::patch_strategy(&value.get(), density)
+            .prop_map(Cell::new)
+            .boxed()
+    }
+}
+
+impl<T: OptionableStrategy + Unpin> OptionableStrategy for Pin<Box<T>>
+where
+    T::Optioned: Unpin,
+{
+    fn optioned_strategy(density: f64) -> BoxedStrategy<Pin<Box<T::Optioned>>> {
+        T::optioned_strategy(density).prop_map(Box::pin).boxed()
+    }
+
+    fn patch_strategy(value: &Pin<Box<T>>, density: f64) -> BoxedStrategy<Pin<Box<T::Optioned>>> {
+        T::patch_strategy(value, density).prop_map(Box::pin).boxed()
+    }
+}
+
+/// Helper macro to generate an impl for `OptionableStrategy` for cells that are initialized at most once.
+macro_rules! impl_strategy_once_cell {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableStrategy> OptionableStrategy for $t<T>
+        where
+            $t<T::Optioned>: Debug,
+        {
+            fn optioned_strategy(density: f64) -> BoxedStrategy<$t<T::Optioned>> {
+                <Option<T>>::optioned_strategy(density)
+                    .prop_map(|value| value.map_or_else($t::new, $t::from))
+                    .boxed()
+            }
+
+            fn patch_strategy(value: &$t<T>, density: f64) -> BoxedStrategy<$t<T::Optioned>> {
+                let strategy = match value.get() {
+                    Some(value) => optional(density, T::patch_strategy(value, density)),
+                    None => optional(density, T::optioned_strategy(1.0)),
+                };
+                strategy
+                    .prop_map(|value| value.map_or_else($t::new, $t::from))
+                    .boxed()
+            }
+        })*
+    };
+}
+
+impl_strategy_once_cell!(OnceCell, OnceLock);
+
+/// Helper macro to generate an impl for `OptionableStrategy` for newtype wrapper around a single value.
+macro_rules! impl_strategy_newtype {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableStrategy> OptionableStrategy for $t<T>
+        where
+            $t<T::Optioned>: Debug,
+        {
+            fn optioned_strategy(density: f64) -> BoxedStrategy<$t<T::Optioned>> {
+                T::optioned_strategy(density).prop_map($t).boxed()
+            }
+
+            fn patch_strategy(value: &$t<T>, density: f64) -> BoxedStrategy<$t<T::Optioned>> {
+                T::patch_strategy(&value.0, density).prop_map($t).boxed()
+            }
+        })*
+    };
+}
+
+impl_strategy_newtype!(Reverse, Wrapping, Saturating);
+
+/// Helper macro to generate an impl for `OptionableStrategy` for collections without keys.
+/// They are replaced as a whole when merging, hence the elements of patches are set completely.
+macro_rules! impl_strategy_collection {
+    ($($t:ident<T $(: $bound:path)?>: $collection:path),* $(,)?) => {
+        $(impl<T: OptionableStrategy> OptionableStrategy for $t<T>
+        where
+            T::Optioned: $($bound +)? Debug,
+        {
+            fn optioned_strategy(density: f64) -> BoxedStrategy<$t<T::Optioned>> {
+                $collection(T::optioned_strategy(density), size())
+                    .prop_map(|values| values.into_iter().collect())
+                    .boxed()
+            }
+
+            fn patch_strategy(_value: &$t<T>, _density: f64) -> BoxedStrategy<$t<T::Optioned>> {
+                Self::optioned_strategy(1.0)
+            }
+        })*
+    };
+}
+
+impl_strategy_collection!(
+    Vec<T>: vec,
+    VecDeque<T>: vec,
+    LinkedList<T>: vec,
+    BinaryHeap<T: Ord>: vec,
+    BTreeSet<T: Ord>: btree_set,
+);
+
+impl<T: OptionableStrategy, S: BuildHasher + Default + 'static> OptionableStrategy for HashSet<T, S>
+where
+    T::Optioned: Eq + Hash,
+{
+    fn optioned_strategy(density: f64) -> BoxedStrategy<HashSet<T::Optioned, S>> {
+        hash_set(T::optioned_strategy(density), size())
+            .prop_map(|values| values.into_iter().collect())
+            .boxed()
+    }
+
+    fn patch_strategy(
+        _value: &HashSet<T, S>,
+        _density: f64,
+    ) -> BoxedStrategy<HashSet<T::Optioned, S>> {
+        Self::optioned_strategy(1.0)
+    }
+}
+
+// Arrays are merged element-wise.
+impl<T: OptionableStrategy, const N: usize> OptionableStrategy for [T; N] {
+    fn optioned_strategy(density: f64) -> BoxedStrategy<[T::Optioned; N]> {
+        array((0..N).map(|_| T::optioned_strategy(density)).collect())
+    }
+
+    fn patch_strategy(value: &[T; N], density: f64) -> BoxedStrategy<[T::Optioned; N]> {
+        array(
+            value
+                .iter()
+                .map(|value| T::patch_strategy(value, density))
+                .collect(),
+        )
+    }
+}
+
+/// Returns a strategy for arrays from the strategies of the `N` elements.
+fn array<T: Debug + 'static, const N: usize>(
+    elements: Vec<BoxedStrategy<T>>,
+) -> BoxedStrategy<[T; N]> {
+    elements
+        .prop_map(|values| {
+            values
+                .try_into()
+                .unwrap_or_else(|_| unreachable!("length is fixed to N"))
+        })
+        .boxed()
+}
+
+/// Helper macro to generate an impl for `OptionableStrategy` for maps. The patches set some of the present
+/// entries and some new entries, the latter completely.
+/// The generic parameters of the impl (including the key type `K` and value type `T`) are passed in brackets.
+macro_rules! impl_strategy_map {
+    ($([$($generics:tt)*] $t:ty: $map:path),* $(,)?) => {
+        $(impl<$($generics)*> OptionableStrategy for $t {
+            fn optioned_strategy(density: f64) -> BoxedStrategy<Self::Optioned> {
+                $map(any::<K>(), T::optioned_strategy(density), size())
+                    .prop_map(|entries| entries.into_iter().collect())
+                    .boxed()
+            }
+
+            fn patch_strategy(value: &$t, density: f64) -> BoxedStrategy<Self::Optioned> {
+                let present = value
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = key.clone();
+                        optional(density, T::patch_strategy(value, density))
+                            .prop_map(move |value| value.map(|value| (key.clone(), value)))
+                            .boxed()
+                    })
+                    .collect::<Vec<_>>();
+                let added = $map(any::<K>(), T::optioned_strategy(1.0), size());
+                (present, added)
+                    .prop_map(|(present, added)| present.into_iter().flatten().chain(added).collect())
+                    .boxed()
+            }
+        })*
+    };
+}
+
+impl_strategy_map!(
+    [K: Arbitrary + Clone + Ord + 'static, T: OptionableStrategy] BTreeMap<K, T>: btree_map,
+    [K: Arbitrary + Clone + Eq + Hash + 'static, T: OptionableStrategy, S: BuildHasher + Default + 'static]
+        HashMap<K, T, S>: hash_map,
+);
+
+/// Helper macro to generate an impl for `OptionableStrategy` for ranges. They are replaced as a whole
+/// when merging, hence the bounds of patches are set completely.
+macro_rules! impl_strategy_range {
+    ($($t:ident: |$($bound:ident),*| $range:expr),* $(,)?) => {
+        $(impl<T: OptionableStrategy> OptionableStrategy for $t<T> {
+            fn optioned_strategy(density: f64) -> BoxedStrategy<$t<T::Optioned>> {
+                ($({
+                    let $bound = T::optioned_strategy(density);
+                    $bound
+                },)*)
+                    .prop_map(|($($bound,)*)| $range)
+                    .boxed()
+            }
+
+            fn patch_strategy(_value: &$t<T>, _density: f64) -> BoxedStrategy<$t<T::Optioned>> {
+                Self::optioned_strategy(1.0)
+            }
+        })*
+    };
+}
+
+impl_strategy_range!(
+    Range: |start, end| start..end,
+    RangeInclusive: |start, end| start..=end,
+    RangeFrom: |start| start..,
+    RangeTo: |end| ..end,
+    RangeToInclusive: |end| ..=end,
+);
+
+impl<B: ?Sized + ToOwned + 'static> OptionableStrategy for Cow<'static, B>
+where
+    B::Owned: OptionableStrategy,
+{
+    fn optioned_strategy(density: f64) -> BoxedStrategy<<B::Owned as Optionable>::Optioned> {
+        B::Owned::optioned_strategy(density)
+    }
+
+    fn patch_strategy(
+        value: &Cow<'static, B>,
+        density: f64,
+    ) -> BoxedStrategy<<B::Owned as Optionable>::Optioned> {
+        B::Owned::patch_strategy(&value.clone().into_owned(), density)
+    }
+}
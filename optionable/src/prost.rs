@@ -0,0 +1,303 @@
+//! Implementations for the protobuf well-known types of [`prost_types`] and the conversion between
+//! [`prost_types::FieldMask`] and [`crate::field_mask::FieldMask`].
+//!
+//! [`Struct`] and [`Value`] are their own optioned types and merge analogously to
+//! [RFC 7396](https://www.rfc-editor.org/rfc/rfc7396) (JSON Merge Patch): struct fields set to a null value
+//! are removed from the target struct. All other types are replaced as a whole.
+use crate::__private::{entry_path, three_way_merge_value};
+use crate::field_mask::{FieldMaskTree, PathSegment};
+use crate::field_paths::{is_entry_path, mask_entry, set_entry_paths};
+use crate::three_way::{merge_entries, OptionableThreeWayMerge};
+use crate::{impl_optional_self, Error, Optionable, OptionableConvert, OptionableFieldPaths};
+use prost_types::value::Kind;
+use prost_types::{Duration, FieldMask, ListValue, Struct, Timestamp, Value};
+use std::collections::BTreeMap;
+
+impl_optional_self!(Duration, FieldMask, ListValue, Timestamp);
+
+impl Optionable for Value {
+    type Optioned = Self;
+}
+
+impl Optionable for Struct {
+    type Optioned = Self;
+
+    fn is_empty_optioned(optioned: &Struct) -> bool {
+        optioned.fields.is_empty()
+    }
+}
+
+impl OptionableConvert for Value {
+    fn into_optioned(self) -> Value {
+        self
+    }
+
+    fn try_from_optioned(value: Value) -> Result<Self, Error> {
+        Ok(value)
+    }
+
+    fn merge(&mut self, other: Value) -> Result<(), Error> {
+        match (&mut self.kind, other.kind) {
+            (Some(Kind::StructValue(target)), Some(Kind::StructValue(patch))) => {
+                target.merge(patch)?;
+            }
+            (_, kind) => *self = patched_value(kind)?,
+        }
+        Ok(())
+    }
+}
+
+/// Returns the result of applying the patch to a value that is no struct.
+/// Structs are applied to an empty struct and hence drop their fields set to a null value.
+fn patched_value(kind: Option<Kind>) -> Result<Value, Error> {
+    match kind {
+        Some(Kind::StructValue(patch)) => {
+            let mut target = Struct::default();
+            target.merge(patch)?;
+            Ok(Value {
+                kind: Some(Kind::StructValue(target)),
+            })
+        }
+        kind => Ok(Value { kind }),
+    }
+}
+
+impl OptionableConvert for Struct {
+    fn into_optioned(self) -> Struct {
+        self
+    }
+
+    /// Fields set to a null value are kept, only merging removes them.
+    fn try_from_optioned(value: Struct) -> Result<Self, Error> {
+        Ok(value)
+    }
+
+    fn merge(&mut self, other: Struct) -> Result<(), Error> {
+        for (k, v) in other.fields {
+            if matches!(v.kind, None | Some(Kind::NullValue(_))) {
+                self.fields.remove(&k);
+            } else if let Some(value) = self.fields.get_mut(&k) {
+                value.merge(v)?;
+            } else {
+                self.fields.insert(k, patched_value(v.kind)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Struct fields are addressed like map entries, all other values are set as a whole.
+impl OptionableFieldPaths for Value {
+    fn set_field_paths_optioned(optioned: &Value, prefix: &str, paths: &mut Vec<String>) {
+        match &optioned.kind {
+            Some(Kind::StructValue(optioned)) => {
+                Struct::set_field_paths_optioned(optioned, prefix, paths);
+            }
+            _ => paths.push(prefix.to_owned()),
+        }
+    }
+
+    fn mask_optioned(optioned: &mut Value, mask: &FieldMaskTree) {
+        if let Some(Kind::StructValue(optioned)) = &mut optioned.kind {
+            Struct::mask_optioned(optioned, mask);
+        }
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        is_entry_path::<Value>(segments)
+    }
+}
+
+impl OptionableFieldPaths for Struct {
+    fn set_field_paths_optioned(optioned: &Struct, prefix: &str, paths: &mut Vec<String>) {
+        set_entry_paths::<String, Value>(&optioned.fields, prefix, paths);
+    }
+
+    fn mask_optioned(optioned: &mut Struct, mask: &FieldMaskTree) {
+        optioned
+            .fields
+            .retain(|k, v| mask_entry::<Value>(k, v, mask));
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        is_entry_path::<Value>(segments)
+    }
+}
+
+// Struct fields are managed like map entries, all other values as a whole.
+#[cfg(feature = "serde_json")]
+impl crate::fields_v1::OptionableFieldsV1 for Value {
+    fn fields_v1_optioned(
+        optioned: &Value,
+        fields: &mut serde_json::Map<String, serde_json::Value>,
+    ) {
+        if let Some(Kind::StructValue(optioned)) = &optioned.kind {
+            Struct::fields_v1_optioned(optioned, fields);
+        }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl crate::fields_v1::OptionableFieldsV1 for Struct {
+    fn fields_v1_optioned(
+        optioned: &Struct,
+        fields: &mut serde_json::Map<String, serde_json::Value>,
+    ) {
+        crate::fields_v1::map_fields_v1::<String, Value>(&optioned.fields, fields);
+    }
+}
+
+// Objects are merged recursively by the strategic merge patches without any further strategies.
+#[cfg(feature = "serde_json")]
+crate::impl_strategic_merge_whole!([] Value);
+#[cfg(feature = "serde_json")]
+crate::impl_strategic_merge_whole!([] Struct);
+
+// Struct fields are merged per key, all other values are merged as a whole.
+impl OptionableThreeWayMerge for Value {
+    fn three_way_merge_at(
+        base: Option<&Value>,
+        ours: Value,
+        theirs: Value,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> Value {
+        match (ours.kind, theirs.kind) {
+            (Some(Kind::StructValue(ours)), Some(Kind::StructValue(theirs))) => {
+                let base = match base.and_then(|base| base.kind.as_ref()) {
+                    Some(Kind::StructValue(base)) => Some(base),
+                    _ => None,
+                };
+                Value {
+                    kind: Some(Kind::StructValue(Struct::three_way_merge_at(
+                        base, ours, theirs, path, conflicts,
+                    ))),
+                }
+            }
+            (ours, theirs) => three_way_merge_value(
+                base,
+                Value { kind: ours },
+                Value { kind: theirs },
+                path,
+                conflicts,
+            ),
+        }
+    }
+}
+
+impl OptionableThreeWayMerge for Struct {
+    fn three_way_merge_at(
+        base: Option<&Struct>,
+        ours: Struct,
+        theirs: Struct,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> Struct {
+        Struct {
+            fields: merge_entries::<String, Value, _>(
+                base.map(|base| &base.fields),
+                ours.fields,
+                theirs.fields,
+                (BTreeMap::get, BTreeMap::remove),
+                |key| entry_path(path, key),
+                conflicts,
+            ),
+        }
+    }
+}
+
+impl From<FieldMask> for crate::field_mask::FieldMask {
+    fn from(mask: FieldMask) -> Self {
+        Self { paths: mask.paths }
+    }
+}
+
+impl From<crate::field_mask::FieldMask> for FieldMask {
+    fn from(mask: crate::field_mask::FieldMask) -> Self {
+        Self { paths: mask.paths }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Optionable, OptionableConvert, OptionableFieldPaths};
+    use prost_types::value::Kind;
+    use prost_types::{Duration, Struct, Timestamp, Value};
+
+    /// Returns a struct value with the given fields.
+    fn object<const N: usize>(fields: [(&str, Kind); N]) -> Struct {
+        Struct {
+            fields: fields
+                .into_iter()
+                .map(|(k, kind)| (k.to_owned(), Value { kind: Some(kind) }))
+                .collect(),
+        }
+    }
+
+    #[test]
+    /// Check that the timestamp and duration types are their own optioned types.
+    fn well_known_types() {
+        let _: <Timestamp as Optionable>::Optioned = Timestamp::default();
+        let _: <Duration as Optionable>::Optioned = Duration::default();
+    }
+
+    #[test]
+    /// Check that the protobuf field mask converts from and to the field mask of this crate.
+    fn field_mask() {
+        let mask = prost_types::FieldMask {
+            paths: vec!["a.b".to_owned()],
+        };
+        let converted = crate::field_mask::FieldMask::from(mask.clone());
+        assert_eq!(converted, crate::field_mask::FieldMask::new(["a.b"]));
+        assert_eq!(prost_types::FieldMask::from(converted), mask);
+    }
+
+    #[test]
+    /// Check that structs are merged per field, null values remove fields and nested structs are merged recursively.
+    fn merge_struct() {
+        let mut target = object([
+            ("a", Kind::StringValue("b".to_owned())),
+            ("c", Kind::BoolValue(true)),
+            (
+                "d",
+                Kind::StructValue(object([("e", Kind::NumberValue(1.0))])),
+            ),
+        ]);
+        let patch = object([
+            ("a", Kind::NullValue(0)),
+            (
+                "d",
+                Kind::StructValue(object([
+                    ("f", Kind::NumberValue(2.0)),
+                    ("g", Kind::NullValue(0)),
+                ])),
+            ),
+        ]);
+        assert_eq!(
+            Struct::set_field_paths(&patch),
+            vec!["[a]", "[d][f]", "[d][g]"]
+        );
+        target.merge(patch).unwrap();
+        assert_eq!(
+            target,
+            object([
+                ("c", Kind::BoolValue(true)),
+                (
+                    "d",
+                    Kind::StructValue(object([
+                        ("e", Kind::NumberValue(1.0)),
+                        ("f", Kind::NumberValue(2.0)),
+                    ])),
+                ),
+            ])
+        );
+        let nulls = object([
+            ("a", Kind::NullValue(0)),
+            ("b", Kind::StructValue(object([("c", Kind::NullValue(0))]))),
+        ]);
+        assert_eq!(Struct::try_from_optioned(nulls.clone()).unwrap(), nulls);
+        let mut target = Struct::default();
+        target.merge(nulls).unwrap();
+        assert_eq!(target, object([("b", Kind::StructValue(Struct::default()))]));
+    }
+}
@@ -0,0 +1,16 @@
+use crate::{impl_optional_self, Optionable};
+use rust_decimal::Decimal;
+
+impl_optional_self!(Decimal);
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use rust_decimal::Decimal;
+
+    #[test]
+    /// Check that `Decimal` implements optionable.
+    fn decimal() {
+        let _: <Decimal as Optionable>::Optioned = Decimal::ONE;
+    }
+}
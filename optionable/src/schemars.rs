@@ -0,0 +1,17 @@
+use crate::{impl_optional_self, Optionable};
+use schemars::Schema;
+
+// Schemas are dynamic documents without a fixed structure.
+impl_optional_self!(Schema);
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use schemars::{json_schema, Schema};
+
+    #[test]
+    /// Check that `Schema` implements optionable.
+    fn schema() {
+        let _: <Schema as Optionable>::Optioned = json_schema!({"type": "string"});
+    }
+}
@@ -1,4 +1,546 @@
-use crate::{impl_optional_self, Optionable};
-use serde_json::Value;
+//! Optioned representation of the dynamic [`serde_json::Value`] that merges according to
+//! [RFC 7396](https://www.rfc-editor.org/rfc/rfc7396) (JSON Merge Patch).
+use crate::__private::{entry_path, three_way_merge_value};
+#[cfg(feature = "arbitrary")]
+use crate::arbitrary::{collection, optional, OptionableArbitrary};
+use crate::field_mask::{FieldMaskTree, PathSegment};
+use crate::field_paths::{is_entry_path, mask_entry, set_entry_paths};
+use crate::fields_v1::{map_fields_v1, OptionableFieldsV1};
+use crate::strategic_merge::OptionableStrategicMerge;
+use crate::three_way::{merge_entries, OptionableThreeWayMerge};
+use crate::{Error, Optionable, OptionableConvert, OptionableFieldPaths};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
 
-impl_optional_self!(Value);
+/// Optioned type of [`serde_json::Value`], i.e. a JSON merge patch.
+///
+/// Object members are optional: absent members are left untouched by a merge while
+/// members set to `null` are removed from the target object.
+/// All other values (including arrays) replace the target value as a whole.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ValueOpt {
+    /// Sets the value to `null`, removes the member if part of an object.
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    /// Arrays are replaced as a whole and hence keep their full values.
+    Array(Vec<Value>),
+    Object(BTreeMap<String, ValueOpt>),
+}
+
+// Like the JSON values themselves, their patches can hold any value.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for ValueOpt {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "AnyValue".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        true.into()
+    }
+}
+
+// `ComposeSchema` instead of `PartialSchema` (provided via a blanket impl) to support containers like `Vec`.
+#[cfg(feature = "utoipa")]
+impl utoipa::__dev::ComposeSchema for ValueOpt {
+    fn compose(
+        _: Vec<utoipa::openapi::RefOr<utoipa::openapi::schema::Schema>>,
+    ) -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        utoipa::openapi::schema::ObjectBuilder::new()
+            .schema_type(utoipa::openapi::schema::SchemaType::AnyValue)
+            .into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl utoipa::ToSchema for ValueOpt {}
+
+impl Optionable for Value {
+    type Optioned = ValueOpt;
+}
+
+impl Optionable for ValueOpt {
+    type Optioned = ValueOpt;
+}
+
+impl Optionable for Map<String, Value> {
+    type Optioned = BTreeMap<String, ValueOpt>;
+
+    fn is_empty_optioned(optioned: &BTreeMap<String, ValueOpt>) -> bool {
+        optioned.is_empty()
+    }
+}
+
+impl OptionableConvert for Value {
+    fn into_optioned(self) -> ValueOpt {
+        match self {
+            Value::Null => ValueOpt::Null,
+            Value::Bool(value) => ValueOpt::Bool(value),
+            Value::Number(value) => ValueOpt::Number(value),
+            Value::String(value) => ValueOpt::String(value),
+            Value::Array(value) => ValueOpt::Array(value),
+            Value::Object(value) => ValueOpt::Object(value.into_optioned()),
+        }
+    }
+
+    fn try_from_optioned(value: ValueOpt) -> Result<Self, Error> {
+        Ok(match value {
+            ValueOpt::Null => Value::Null,
+            ValueOpt::Bool(value) => Value::Bool(value),
+            ValueOpt::Number(value) => Value::Number(value),
+            ValueOpt::String(value) => Value::String(value),
+            ValueOpt::Array(value) => Value::Array(value),
+            ValueOpt::Object(value) => Value::Object(Map::try_from_optioned(value)?),
+        })
+    }
+
+    fn merge(&mut self, other: ValueOpt) -> Result<(), Error> {
+        match (self, other) {
+            (Value::Object(target), ValueOpt::Object(patch)) => target.merge(patch)?,
+            (target, other) => *target = patched_value(other)?,
+        }
+        Ok(())
+    }
+}
+
+/// Returns the result of applying the merge patch to a value that is no object.
+/// Objects are applied to an empty object (RFC 7396 §2) and hence drop their `null` members.
+fn patched_value(patch: ValueOpt) -> Result<Value, Error> {
+    match patch {
+        ValueOpt::Object(patch) => {
+            let mut map = Map::new();
+            map.merge(patch)?;
+            Ok(Value::Object(map))
+        }
+        patch => Value::try_from_optioned(patch),
+    }
+}
+
+// Merging two patches combines them, members set to `null` are kept to remove them from the final target.
+impl OptionableConvert for ValueOpt {
+    fn into_optioned(self) -> ValueOpt {
+        self
+    }
+
+    fn try_from_optioned(value: ValueOpt) -> Result<Self, Error> {
+        Ok(value)
+    }
+
+    fn merge(&mut self, other: ValueOpt) -> Result<(), Error> {
+        match (self, other) {
+            (ValueOpt::Object(target), ValueOpt::Object(patch)) => {
+                OptionableConvert::merge(target, patch)?;
+            }
+            // the object is applied to an empty object as the member is no object (RFC 7396 §2), dropping its `null`s
+            (target, other) => *target = patched_value(other)?.into_optioned(),
+        }
+        Ok(())
+    }
+}
+
+impl OptionableConvert for Map<String, Value> {
+    fn into_optioned(self) -> BTreeMap<String, ValueOpt> {
+        self.into_iter()
+            .map(|(k, v)| (k, v.into_optioned()))
+            .collect()
+    }
+
+    /// Members set to `null` are kept as `null` values, only merging removes them.
+    fn try_from_optioned(value: BTreeMap<String, ValueOpt>) -> Result<Self, Error> {
+        value
+            .into_iter()
+            .map(|(k, v)| Ok((k, Value::try_from_optioned(v)?)))
+            .collect()
+    }
+
+    fn merge(&mut self, other: BTreeMap<String, ValueOpt>) -> Result<(), Error> {
+        for (k, v) in other {
+            if v == ValueOpt::Null {
+                self.remove(&k);
+            } else if let Some(value) = self.get_mut(&k) {
+                value.merge(v)?;
+            } else {
+                self.insert(k, patched_value(v)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The maximal nesting of generated arrays and objects.
+#[cfg(feature = "arbitrary")]
+const ARBITRARY_DEPTH: usize = 3;
+
+/// Returns an arbitrary JSON value nested at most [`ARBITRARY_DEPTH`] levels below `depth`.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_value(u: &mut arbitrary::Unstructured<'_>, depth: usize) -> arbitrary::Result<Value> {
+    Ok(match arbitrary_kind(u, depth)? {
+        0 => Value::Null,
+        1 => Value::Bool(u.arbitrary()?),
+        2 => Value::Number(arbitrary_number(u)?),
+        3 => Value::String(u.arbitrary()?),
+        4 => Value::Array(collection(u, |u| arbitrary_value(u, depth + 1))?),
+        _ => Value::Object(
+            collection(u, |u| Ok((u.arbitrary()?, arbitrary_value(u, depth + 1)?)))?
+                .into_iter()
+                .collect(),
+        ),
+    })
+}
+
+/// Returns the index of the kind of an arbitrary value: null, boolean, number, string, array or object.
+/// Arrays and objects are left out once nested [`ARBITRARY_DEPTH`] levels deep.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_kind(u: &mut arbitrary::Unstructured<'_>, depth: usize) -> arbitrary::Result<usize> {
+    u.choose_index(if depth < ARBITRARY_DEPTH { 6 } else { 4 })
+}
+
+/// Returns an arbitrary JSON number, either an integer or a finite float.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_number(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Number> {
+    Ok(if u.arbitrary()? {
+        Number::from(u.arbitrary::<i64>()?)
+    } else {
+        Number::from_f64(u.arbitrary()?).unwrap_or_else(|| Number::from(0))
+    })
+}
+
+/// Returns an arbitrary merge patch whose object members are set with the probability `density`.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_value_opt(
+    u: &mut arbitrary::Unstructured<'_>,
+    density: f64,
+    depth: usize,
+) -> arbitrary::Result<ValueOpt> {
+    Ok(match arbitrary_kind(u, depth)? {
+        5 => ValueOpt::Object(arbitrary_members(u, density, depth + 1)?),
+        _ => arbitrary_value(u, depth)?.into_optioned(),
+    })
+}
+
+/// Returns the arbitrary members of an object merge patch, each is set with the probability `density`.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_members(
+    u: &mut arbitrary::Unstructured<'_>,
+    density: f64,
+    depth: usize,
+) -> arbitrary::Result<BTreeMap<String, ValueOpt>> {
+    let members = collection(u, |u| {
+        let key = u.arbitrary()?;
+        let value = optional(u, density, |u| arbitrary_value_opt(u, density, depth))?;
+        Ok(value.map(|value| (key, value)))
+    })?;
+    Ok(members.into_iter().flatten().collect())
+}
+
+// Patches of objects set some of the present members and some new members, merging never fails.
+#[cfg(feature = "arbitrary")]
+impl OptionableArbitrary for Value {
+    fn arbitrary_optioned(
+        u: &mut arbitrary::Unstructured<'_>,
+        density: f64,
+    ) -> arbitrary::Result<ValueOpt> {
+        arbitrary_value_opt(u, density, 0)
+    }
+
+    fn arbitrary_patch(
+        value: &Value,
+        u: &mut arbitrary::Unstructured<'_>,
+        density: f64,
+    ) -> arbitrary::Result<ValueOpt> {
+        match value {
+            Value::Object(value) if u.arbitrary()? => {
+                Map::arbitrary_patch(value, u, density).map(ValueOpt::Object)
+            }
+            _ => Self::arbitrary_optioned(u, density),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl OptionableArbitrary for Map<String, Value> {
+    fn arbitrary_optioned(
+        u: &mut arbitrary::Unstructured<'_>,
+        density: f64,
+    ) -> arbitrary::Result<BTreeMap<String, ValueOpt>> {
+        arbitrary_members(u, density, 0)
+    }
+
+    fn arbitrary_patch(
+        value: &Map<String, Value>,
+        u: &mut arbitrary::Unstructured<'_>,
+        density: f64,
+    ) -> arbitrary::Result<BTreeMap<String, ValueOpt>> {
+        let mut patch = BTreeMap::new();
+        for (key, value) in value {
+            if let Some(value) =
+                optional(u, density, |u| Value::arbitrary_patch(value, u, density))?
+            {
+                patch.insert(key.clone(), value);
+            }
+        }
+        patch.extend(Self::arbitrary_optioned(u, density)?);
+        Ok(patch)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ValueOpt {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        arbitrary_value_opt(u, crate::arbitrary::DEFAULT_DENSITY, 0)
+    }
+}
+
+// Object members are addressed like map entries, all other values are set as a whole.
+impl OptionableFieldPaths for Value {
+    fn set_field_paths_optioned(optioned: &ValueOpt, prefix: &str, paths: &mut Vec<String>) {
+        match optioned {
+            ValueOpt::Object(optioned) => set_entry_paths::<String, Value>(optioned, prefix, paths),
+            _ => paths.push(prefix.to_owned()),
+        }
+    }
+
+    fn mask_optioned(optioned: &mut ValueOpt, mask: &FieldMaskTree) {
+        if let ValueOpt::Object(optioned) = optioned {
+            Map::mask_optioned(optioned, mask);
+        }
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        is_entry_path::<Value>(segments)
+    }
+}
+
+impl OptionableFieldPaths for Map<String, Value> {
+    fn set_field_paths_optioned(
+        optioned: &BTreeMap<String, ValueOpt>,
+        prefix: &str,
+        paths: &mut Vec<String>,
+    ) {
+        set_entry_paths::<String, Value>(optioned, prefix, paths);
+    }
+
+    fn mask_optioned(optioned: &mut BTreeMap<String, ValueOpt>, mask: &FieldMaskTree) {
+        optioned.retain(|k, v| mask_entry::<Value>(k, v, mask));
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        is_entry_path::<Value>(segments)
+    }
+}
+
+// Object members are managed like map entries, all other values as a whole.
+impl OptionableFieldsV1 for Value {
+    fn fields_v1_optioned(optioned: &ValueOpt, fields: &mut Map<String, Value>) {
+        if let ValueOpt::Object(optioned) = optioned {
+            map_fields_v1::<String, Value>(optioned, fields);
+        }
+    }
+}
+
+impl OptionableFieldsV1 for Map<String, Value> {
+    fn fields_v1_optioned(optioned: &BTreeMap<String, ValueOpt>, fields: &mut Map<String, Value>) {
+        map_fields_v1::<String, Value>(optioned, fields);
+    }
+}
+
+// Objects are merged recursively by the strategic merge patches without any further strategies.
+impl OptionableStrategicMerge for Value {}
+impl OptionableStrategicMerge for Map<String, Value> {}
+
+// Object members are merged per key, all other values are merged as a whole.
+impl OptionableThreeWayMerge for Value {
+    fn three_way_merge_at(
+        base: Option<&ValueOpt>,
+        ours: ValueOpt,
+        theirs: ValueOpt,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> ValueOpt {
+        match (ours, theirs) {
+            (ValueOpt::Object(ours), ValueOpt::Object(theirs)) => {
+                let base = match base {
+                    Some(ValueOpt::Object(base)) => Some(base),
+                    _ => None,
+                };
+                ValueOpt::Object(Map::three_way_merge_at(base, ours, theirs, path, conflicts))
+            }
+            (ours, theirs) => three_way_merge_value(base, ours, theirs, path, conflicts),
+        }
+    }
+}
+
+impl OptionableThreeWayMerge for Map<String, Value> {
+    fn three_way_merge_at(
+        base: Option<&BTreeMap<String, ValueOpt>>,
+        ours: BTreeMap<String, ValueOpt>,
+        theirs: BTreeMap<String, ValueOpt>,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> BTreeMap<String, ValueOpt> {
+        merge_entries::<String, Value, _>(
+            base,
+            ours,
+            theirs,
+            (BTreeMap::get, BTreeMap::remove),
+            |key| entry_path(path, key),
+            conflicts,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::serde_json::ValueOpt;
+    use crate::{OptionableConvert, OptionableFieldPaths};
+    use serde_json::{json, Value};
+
+    /// Applies the merge patch to the target and returns the result.
+    fn merge(mut target: Value, patch: Value) -> Value {
+        let patch: ValueOpt = serde_json::from_value(patch).unwrap();
+        target.merge(patch).unwrap();
+        target
+    }
+
+    #[test]
+    /// Check the test cases from the appendix of RFC 7396.
+    fn merge_rfc7396() {
+        let tcs = [
+            (json!({"a":"b"}), json!({"a":"c"}), json!({"a":"c"})),
+            (json!({"a":"b"}), json!({"b":"c"}), json!({"a":"b","b":"c"})),
+            (json!({"a":"b"}), json!({"a":null}), json!({})),
+            (
+                json!({"a":"b","b":"c"}),
+                json!({"a":null}),
+                json!({"b":"c"}),
+            ),
+            (json!({"a":["b"]}), json!({"a":"c"}), json!({"a":"c"})),
+            (json!({"a":"c"}), json!({"a":["b"]}), json!({"a":["b"]})),
+            (
+                json!({"a":{"b":"c"}}),
+                json!({"a":{"b":"d","c":null}}),
+                json!({"a":{"b":"d"}}),
+            ),
+            (json!({"a":[{"b":"c"}]}), json!({"a":[1]}), json!({"a":[1]})),
+            (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+            (json!({"a":"b"}), json!(["c"]), json!(["c"])),
+            (json!({"a":"foo"}), json!(null), json!(null)),
+            (json!({"a":"foo"}), json!("bar"), json!("bar")),
+            (json!({"e":null}), json!({"a":1}), json!({"e":null,"a":1})),
+            (json!([1, 2]), json!({"a":"b","c":null}), json!({"a":"b"})),
+            (
+                json!({}),
+                json!({"a":{"bb":{"ccc":null}}}),
+                json!({"a":{"bb":{}}}),
+            ),
+        ];
+        for (target, patch, expected) in tcs {
+            assert_eq!(merge(target, patch), expected);
+        }
+    }
+
+    #[test]
+    /// Check that merging two patches keeps the removals and combines nested objects.
+    fn merge_patches() {
+        let mut patch: ValueOpt = serde_json::from_value(json!({"a": {"b": 1}, "c": 2})).unwrap();
+        let other: ValueOpt =
+            serde_json::from_value(json!({"a": {"d": null}, "c": null, "e": 3})).unwrap();
+        patch.merge(other).unwrap();
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            json!({"a": {"b": 1, "d": null}, "c": null, "e": 3})
+        );
+        assert_eq!(
+            merge(
+                json!({"a": {"d": 4}, "c": 5}),
+                serde_json::to_value(&patch).unwrap()
+            ),
+            json!({"a": {"b": 1}, "e": 3})
+        );
+    }
+
+    #[test]
+    /// Check that merging two patches equals applying them in sequence if the first one
+    /// sets a member to a non-object and the second one to an object with `null` members,
+    /// the latter is applied to an empty object and hence loses its `null` members.
+    fn merge_patches_non_object() {
+        let first = json!({"a": 1, "b": null, "c": {"d": 2}});
+        let second =
+            json!({"a": {"e": null, "f": {"g": null}}, "b": {"h": null, "i": 3}, "c": {"d": null}});
+        let target = json!({"a": "e", "b": 5, "c": {"d": 6}, "j": 7});
+        let mut patch: ValueOpt = serde_json::from_value(first.clone()).unwrap();
+        patch
+            .merge(serde_json::from_value(second.clone()).unwrap())
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            json!({"a": {"f": {}}, "b": {"i": 3}, "c": {"d": null}})
+        );
+        assert_eq!(
+            merge(target.clone(), serde_json::to_value(&patch).unwrap()),
+            merge(merge(target, first), second)
+        );
+    }
+
+    #[test]
+    /// Check that converting into the optioned type and back keeps the value.
+    fn roundtrip() {
+        let value = json!({"a": {"b": [1, null], "c": true}, "d": "e"});
+        let optioned = value.clone().into_optioned();
+        assert_eq!(serde_json::to_value(&optioned).unwrap(), value);
+        assert_eq!(Value::try_from_optioned(optioned).unwrap(), value);
+    }
+
+    #[test]
+    /// Check that converting back keeps `null` members, which only merging removes.
+    fn roundtrip_null_members() {
+        let value = json!({"a": null, "b": 1, "c": {"d": null}});
+        assert_eq!(
+            Value::try_from_optioned(value.clone().into_optioned()).unwrap(),
+            value
+        );
+        #[cfg(feature = "laws")]
+        crate::laws::check_round_trip(&value).unwrap();
+        assert_eq!(merge(json!({"a": 2}), value), json!({"b": 1, "c": {}}));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    /// Check that arbitrary merge patches merge into any value and that patches of a value keep its members.
+    fn arbitrary() {
+        use crate::arbitrary::OptionableArbitrary;
+        use arbitrary::Unstructured;
+        use std::collections::BTreeMap;
+
+        let value = json!({"a": {"b": [1, null], "c": true}, "d": "e"});
+        let bytes = (0..=255).cycle().take(4096).collect::<Vec<u8>>();
+        let u = &mut Unstructured::new(&bytes);
+        for _ in 0..16 {
+            let mut patched = value.clone();
+            let patch = Value::arbitrary_patch(&value, u, 0.5).unwrap();
+            assert!(patched.merge(patch).is_ok());
+            let optioned: ValueOpt = u.arbitrary().unwrap();
+            assert!(Value::try_from_optioned(optioned).is_ok());
+        }
+        let patch = Value::arbitrary_patch(&value, &mut Unstructured::new(&[1]), 0.0).unwrap();
+        assert_eq!(patch, ValueOpt::Object(BTreeMap::new()));
+    }
+
+    #[test]
+    /// Check that the paths of a merge patch address the object members including removed ones.
+    fn set_field_paths() {
+        let patch: ValueOpt =
+            serde_json::from_value(json!({"a": {"b": [1], "c": null, "d": {}}, "e": "f"})).unwrap();
+        assert_eq!(
+            Value::set_field_paths(&patch),
+            vec!["[a][b]", "[a][c]", "[a][d]", "[e]"]
+        );
+    }
+}
@@ -0,0 +1,362 @@
+//! Optioned representation of the dynamic [`serde_yaml::Value`] that merges analogously to
+//! [RFC 7396](https://www.rfc-editor.org/rfc/rfc7396) (JSON Merge Patch).
+use crate::__private::{entry_path, set_value_path, three_way_merge_value};
+use crate::field_mask::{FieldMaskTree, PathSegment};
+use crate::field_paths::{is_entry_path, mask_entry};
+use crate::three_way::{merge_entries, OptionableThreeWayMerge};
+use crate::{Error, Optionable, OptionableConvert, OptionableFieldPaths};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_yaml::value::TaggedValue;
+use serde_yaml::{Mapping, Number, Value};
+
+/// Optioned type of [`serde_yaml::Value`], i.e. a YAML merge patch.
+///
+/// Mapping entries are optional: absent entries are left untouched by a merge while
+/// entries set to `null` are removed from the target mapping.
+/// All other values (including sequences and tagged values) replace the target value as a whole.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ValueOpt {
+    /// Sets the value to `null`, removes the entry if part of a mapping.
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    /// Sequences are replaced as a whole and hence keep their full values.
+    Sequence(Vec<Value>),
+    Mapping(IndexMap<Value, ValueOpt>),
+    Tagged(Box<TaggedValue>),
+}
+
+// Like the YAML values themselves, their patches can hold any value.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for ValueOpt {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "AnyValue".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        true.into()
+    }
+}
+
+// `ComposeSchema` instead of `PartialSchema` (provided via a blanket impl) to support containers like `Vec`.
+#[cfg(feature = "utoipa")]
+impl utoipa::__dev::ComposeSchema for ValueOpt {
+    fn compose(
+        _: Vec<utoipa::openapi::RefOr<utoipa::openapi::schema::Schema>>,
+    ) -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        utoipa::openapi::schema::ObjectBuilder::new()
+            .schema_type(utoipa::openapi::schema::SchemaType::AnyValue)
+            .into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl utoipa::ToSchema for ValueOpt {}
+
+impl Optionable for Value {
+    type Optioned = ValueOpt;
+}
+
+impl Optionable for ValueOpt {
+    type Optioned = ValueOpt;
+}
+
+impl Optionable for Mapping {
+    type Optioned = IndexMap<Value, ValueOpt>;
+
+    fn is_empty_optioned(optioned: &IndexMap<Value, ValueOpt>) -> bool {
+        optioned.is_empty()
+    }
+}
+
+impl OptionableConvert for Value {
+    fn into_optioned(self) -> ValueOpt {
+        match self {
+            Value::Null => ValueOpt::Null,
+            Value::Bool(value) => ValueOpt::Bool(value),
+            Value::Number(value) => ValueOpt::Number(value),
+            Value::String(value) => ValueOpt::String(value),
+            Value::Sequence(value) => ValueOpt::Sequence(value),
+            Value::Mapping(value) => ValueOpt::Mapping(value.into_optioned()),
+            Value::Tagged(value) => ValueOpt::Tagged(value),
+        }
+    }
+
+    fn try_from_optioned(value: ValueOpt) -> Result<Self, Error> {
+        Ok(match value {
+            ValueOpt::Null => Value::Null,
+            ValueOpt::Bool(value) => Value::Bool(value),
+            ValueOpt::Number(value) => Value::Number(value),
+            ValueOpt::String(value) => Value::String(value),
+            ValueOpt::Sequence(value) => Value::Sequence(value),
+            ValueOpt::Mapping(value) => Value::Mapping(Mapping::try_from_optioned(value)?),
+            ValueOpt::Tagged(value) => Value::Tagged(value),
+        })
+    }
+
+    fn merge(&mut self, other: ValueOpt) -> Result<(), Error> {
+        match (self, other) {
+            (Value::Mapping(target), ValueOpt::Mapping(patch)) => target.merge(patch)?,
+            (target, other) => *target = patched_value(other)?,
+        }
+        Ok(())
+    }
+}
+
+/// Returns the result of applying the merge patch to a value that is no mapping.
+/// Mappings are applied to an empty mapping and hence drop their `null` entries.
+fn patched_value(patch: ValueOpt) -> Result<Value, Error> {
+    match patch {
+        ValueOpt::Mapping(patch) => {
+            let mut mapping = Mapping::new();
+            mapping.merge(patch)?;
+            Ok(Value::Mapping(mapping))
+        }
+        patch => Value::try_from_optioned(patch),
+    }
+}
+
+// Merging two patches combines them, entries set to `null` are kept to remove them from the final target.
+impl OptionableConvert for ValueOpt {
+    fn into_optioned(self) -> ValueOpt {
+        self
+    }
+
+    fn try_from_optioned(value: ValueOpt) -> Result<Self, Error> {
+        Ok(value)
+    }
+
+    fn merge(&mut self, other: ValueOpt) -> Result<(), Error> {
+        match (self, other) {
+            (ValueOpt::Mapping(target), ValueOpt::Mapping(patch)) => target.merge(patch)?,
+            // the mapping is applied to an empty mapping as the entry is no mapping, dropping its `null`s
+            (target, other) => *target = patched_value(other)?.into_optioned(),
+        }
+        Ok(())
+    }
+}
+
+impl OptionableConvert for Mapping {
+    fn into_optioned(self) -> IndexMap<Value, ValueOpt> {
+        self.into_iter()
+            .map(|(k, v)| (k, v.into_optioned()))
+            .collect()
+    }
+
+    /// Entries set to `null` are kept as `null` values, only merging removes them.
+    fn try_from_optioned(value: IndexMap<Value, ValueOpt>) -> Result<Self, Error> {
+        value
+            .into_iter()
+            .map(|(k, v)| Ok((k, Value::try_from_optioned(v)?)))
+            .collect()
+    }
+
+    fn merge(&mut self, other: IndexMap<Value, ValueOpt>) -> Result<(), Error> {
+        for (k, v) in other {
+            if v == ValueOpt::Null {
+                self.shift_remove(&k);
+            } else if let Some(value) = self.get_mut(&k) {
+                value.merge(v)?;
+            } else {
+                self.insert(k, patched_value(v)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Mapping entries are addressed like map entries, all other values are set as a whole.
+impl OptionableFieldPaths for Value {
+    fn set_field_paths_optioned(optioned: &ValueOpt, prefix: &str, paths: &mut Vec<String>) {
+        match optioned {
+            ValueOpt::Mapping(optioned) => {
+                Mapping::set_field_paths_optioned(optioned, prefix, paths);
+            }
+            _ => paths.push(prefix.to_owned()),
+        }
+    }
+
+    fn mask_optioned(optioned: &mut ValueOpt, mask: &FieldMaskTree) {
+        if let ValueOpt::Mapping(optioned) = optioned {
+            Mapping::mask_optioned(optioned, mask);
+        }
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        is_entry_path::<Value>(segments)
+    }
+}
+
+impl OptionableFieldPaths for Mapping {
+    /// Keys that are not strings are addressed by their YAML representation.
+    fn set_field_paths_optioned(
+        optioned: &IndexMap<Value, ValueOpt>,
+        prefix: &str,
+        paths: &mut Vec<String>,
+    ) {
+        let len = paths.len();
+        for (k, v) in optioned {
+            Value::set_field_paths_optioned(v, &entry_path(prefix, key_path(k)), paths);
+        }
+        set_value_path(prefix, len, paths);
+    }
+
+    fn mask_optioned(optioned: &mut IndexMap<Value, ValueOpt>, mask: &FieldMaskTree) {
+        optioned.retain(|k, v| mask_entry::<Value>(key_path(k), v, mask));
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        is_entry_path::<Value>(segments)
+    }
+}
+
+/// Returns the key as used in field paths, i.e. string keys as they are and all other keys
+/// in their YAML representation.
+fn key_path(key: &Value) -> String {
+    match key {
+        Value::String(key) => key.clone(),
+        key => serde_yaml::to_string(key)
+            .map(|key| key.trim_end().to_owned())
+            .unwrap_or_default(),
+    }
+}
+
+// Mapping entries are managed like map entries, all other values as a whole.
+#[cfg(feature = "serde_json")]
+impl crate::fields_v1::OptionableFieldsV1 for Value {
+    fn fields_v1_optioned(
+        optioned: &ValueOpt,
+        fields: &mut serde_json::Map<String, serde_json::Value>,
+    ) {
+        if let ValueOpt::Mapping(optioned) = optioned {
+            Mapping::fields_v1_optioned(optioned, fields);
+        }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl crate::fields_v1::OptionableFieldsV1 for Mapping {
+    /// Keys that are not strings are represented by their YAML representation.
+    fn fields_v1_optioned(
+        optioned: &IndexMap<Value, ValueOpt>,
+        fields: &mut serde_json::Map<String, serde_json::Value>,
+    ) {
+        for (k, v) in optioned {
+            let fields = crate::fields_v1::entry(fields, format!("f:{}", key_path(k)));
+            Value::fields_v1_optioned(v, fields);
+        }
+    }
+}
+
+// Objects are merged recursively by the strategic merge patches without any further strategies.
+#[cfg(feature = "serde_json")]
+crate::impl_strategic_merge_whole!([] Value);
+#[cfg(feature = "serde_json")]
+crate::impl_strategic_merge_whole!([] Mapping);
+
+// Mapping entries are merged per key, all other values are merged as a whole.
+impl OptionableThreeWayMerge for Value {
+    fn three_way_merge_at(
+        base: Option<&ValueOpt>,
+        ours: ValueOpt,
+        theirs: ValueOpt,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> ValueOpt {
+        match (ours, theirs) {
+            (ValueOpt::Mapping(ours), ValueOpt::Mapping(theirs)) => {
+                let base = match base {
+                    Some(ValueOpt::Mapping(base)) => Some(base),
+                    _ => None,
+                };
+                ValueOpt::Mapping(Mapping::three_way_merge_at(
+                    base, ours, theirs, path, conflicts,
+                ))
+            }
+            (ours, theirs) => three_way_merge_value(base, ours, theirs, path, conflicts),
+        }
+    }
+}
+
+impl OptionableThreeWayMerge for Mapping {
+    fn three_way_merge_at(
+        base: Option<&IndexMap<Value, ValueOpt>>,
+        ours: IndexMap<Value, ValueOpt>,
+        theirs: IndexMap<Value, ValueOpt>,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> IndexMap<Value, ValueOpt> {
+        merge_entries::<Value, Value, _>(
+            base,
+            ours,
+            theirs,
+            (IndexMap::get, IndexMap::shift_remove),
+            |key| entry_path(path, key_path(key)),
+            conflicts,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::serde_yaml::ValueOpt;
+    use crate::OptionableConvert;
+    use serde_yaml::Value;
+
+    #[test]
+    /// Check that a YAML merge patch is applied recursively and removes `null` entries.
+    fn merge() {
+        let mut target: Value = serde_yaml::from_str("a:\n  b: c\n  d: [1, 2]\ne: f\n").unwrap();
+        let patch: ValueOpt = serde_yaml::from_str("a:\n  d: [3]\n  g: h\ne: null\n").unwrap();
+        target.merge(patch).unwrap();
+        let expected: Value = serde_yaml::from_str("a:\n  b: c\n  d: [3]\n  g: h\n").unwrap();
+        assert_eq!(target, expected);
+    }
+
+    #[test]
+    /// Check that converting back keeps `null` entries, which only merging removes.
+    fn roundtrip_null_entries() {
+        let value: Value = serde_yaml::from_str("a: null
+b: 1
+c: {d: null}
+").unwrap();
+        assert_eq!(Value::try_from_optioned(value.clone().into_optioned()).unwrap(), value);
+        #[cfg(feature = "laws")]
+        crate::laws::check_round_trip(&value).unwrap();
+        let mut target: Value = serde_yaml::from_str("a: 2
+").unwrap();
+        target.merge(value.into_optioned()).unwrap();
+        let expected: Value = serde_yaml::from_str("b: 1
+c: {}
+").unwrap();
+        assert_eq!(target, expected);
+    }
+
+    #[test]
+    /// Check that merging two patches equals applying them in sequence if the first one
+    /// sets an entry to a non-mapping and the second one to a mapping with `null` entries,
+    /// the latter is applied to an empty mapping and hence loses its `null` entries.
+    fn merge_patches_non_mapping() {
+        let first: ValueOpt = serde_yaml::from_str("a: 1\nb: null\n").unwrap();
+        let second: ValueOpt = serde_yaml::from_str("a: {c: null, d: 2}\nb: {e: null}\n").unwrap();
+        let mut patch = first.clone();
+        patch.merge(second.clone()).unwrap();
+        let expected: ValueOpt = serde_yaml::from_str("a: {d: 2}\nb: {}\n").unwrap();
+        assert_eq!(patch, expected);
+
+        let target: Value = serde_yaml::from_str("a: [3]\nb: 4\nf: 5\n").unwrap();
+        let mut sequential = target.clone();
+        sequential.merge(first).unwrap();
+        sequential.merge(second).unwrap();
+        let mut composed = target;
+        composed.merge(patch).unwrap();
+        assert_eq!(composed, sequential);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::three_way::OptionableThreeWayMerge;
+use crate::{
+    impl_field_paths_whole, impl_three_way_whole, Error, Optionable, OptionableConvert,
+    OptionableFieldPaths,
+};
+use smallvec::SmallVec;
+
+impl<T: Optionable, const N: usize> Optionable for SmallVec<[T; N]> {
+    type Optioned = SmallVec<[T::Optioned; N]>;
+}
+
+impl<T: OptionableConvert, const N: usize> OptionableConvert for SmallVec<[T; N]> {
+    fn into_optioned(self) -> SmallVec<[T::Optioned; N]> {
+        self.into_iter().map(T::into_optioned).collect()
+    }
+
+    fn try_from_optioned(value: SmallVec<[T::Optioned; N]>) -> Result<Self, Error> {
+        value.into_iter().map(T::try_from_optioned).collect()
+    }
+
+    fn merge(&mut self, other: SmallVec<[T::Optioned; N]>) -> Result<(), Error> {
+        *self = Self::try_from_optioned(other)?;
+        Ok(())
+    }
+}
+
+impl_field_paths_whole!([T: OptionableFieldPaths, const N: usize] SmallVec<[T; N]>);
+impl_three_way_whole!([T: OptionableThreeWayMerge, const N: usize] SmallVec<[T; N]>);
+#[cfg(feature = "serde_json")]
+crate::impl_fields_v1_whole!([T: crate::fields_v1::OptionableFieldsV1, const N: usize] SmallVec<[T; N]>);
+#[cfg(feature = "serde_json")]
+crate::strategic_merge::impl_strategic_merge_elements!([T: crate::strategic_merge::OptionableStrategicMerge, const N: usize] SmallVec<[T; N]> => T);
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use smallvec::{smallvec, SmallVec};
+
+    #[test]
+    /// Check that `SmallVec` implements optionable.
+    fn small_vec() {
+        let a = smallvec![1, 2];
+        let _: <SmallVec<[i32; 2]> as Optionable>::Optioned = a;
+    }
+}
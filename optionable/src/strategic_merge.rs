@@ -0,0 +1,687 @@
+//! Kubernetes [strategic merge patches](https://kubernetes.io/docs/tasks/manage-kubernetes-objects/update-api-object-kubectl-patch/)
+//! between optioned values, merging lists according to the `patchStrategy` and `patchMergeKey` of their fields.
+//!
+//! The strategies are configured via the `patch_strategy` (`"merge"`, `"replace"`, `"retainKeys"` or
+//! `"merge,retainKeys"`) and `patch_merge_key` field attributes. Objects are merged recursively with removed fields
+//! set to `null`, all other values are replaced. Lists are replaced as a whole unless their strategy is `merge`:
+//! their elements are then merged by the merge key with removed elements deleted via `$patch: delete`, lists without
+//! merge key are merged as sets with removed values listed in `$deleteFromPrimitiveList/<field>`. The order of
+//! merged lists is kept via `$setElementOrder/<field>`. Objects with strategy `replace` are replaced via
+//! `$patch: replace`, with `retainKeys` all fields not listed in `$retainKeys` are cleared.
+//! ```rust
+//! # use optionable::Optionable;
+//! # use optionable::strategic_merge::OptionableStrategicMerge;
+//! # use serde::{Deserialize, Serialize};
+//! # use serde_json::json;
+//! #[derive(Optionable)]
+//! #[optionable(strategic_merge, derive(Debug, PartialEq, Deserialize, Serialize))]
+//! struct PodSpec {
+//!     #[optionable(patch_strategy = "merge", patch_merge_key = "name")]
+//!     containers: Vec<Container>,
+//! }
+//!
+//! #[derive(Optionable)]
+//! #[optionable(strategic_merge, derive(Debug, PartialEq, Deserialize, Serialize))]
+//! struct Container {
+//!     name: String,
+//!     image: String,
+//! }
+//!
+//! let container = |name: &str, image: &str| ContainerOpt { name: Some(name.to_owned()), image: Some(image.to_owned()) };
+//! let original = PodSpecOpt { containers: Some(vec![container("app", "app:1"), container("proxy", "envoy")]) };
+//! let modified = PodSpecOpt { containers: Some(vec![container("app", "app:2")]) };
+//! let patch = PodSpec::strategic_merge_patch(&original, &modified).unwrap();
+//! assert_eq!(
+//!     patch,
+//!     json!({
+//!         "$setElementOrder/containers": [{"name": "app"}],
+//!         "containers": [{"name": "app", "image": "app:2"}, {"name": "proxy", "$patch": "delete"}],
+//!     })
+//! );
+//! assert_eq!(PodSpec::apply_strategic_merge_patch(&original, &patch).unwrap(), modified);
+//! ```
+use crate::__private::{entry_path, field_path};
+use crate::Optionable;
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::format;
+use alloc::rc::{Rc, Weak as RcWeak};
+use alloc::string::String;
+use alloc::sync::{Arc, Weak as ArcWeak};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::{Cell, OnceCell, RefCell};
+use core::cmp::Reverse;
+use core::fmt::{Display, Formatter};
+use core::marker::PhantomData;
+use core::num::{Saturating, Wrapping};
+use core::ops::{Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive};
+use core::pin::Pin;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+const PATCH: &str = "$patch";
+const RETAIN_KEYS: &str = "$retainKeys";
+const SET_ELEMENT_ORDER: &str = "$setElementOrder/";
+const DELETE_FROM_PRIMITIVE_LIST: &str = "$deleteFromPrimitiveList/";
+
+/// The patch metadata of values without any (sub-)fields.
+static EMPTY: PatchMeta = PatchMeta {
+    fields: BTreeMap::new(),
+    elements: None,
+};
+
+/// Extension of the [`trait@Optionable`] trait to create and apply strategic merge patches between optioned values.
+/// Derived via `#[optionable(strategic_merge)]`, the strategies of the fields are configured via
+/// the `patch_strategy` and `patch_merge_key` field attributes.
+pub trait OptionableStrategicMerge: Optionable {
+    /// Returns the patch strategies of the (sub-)fields. Values without fields are replaced as a whole,
+    /// hence the default has no strategies.
+    #[must_use]
+    fn patch_meta() -> PatchMeta {
+        PatchMeta::default()
+    }
+
+    /// Returns the strategic merge patch turning the serialized `original` into the serialized `modified`
+    /// optioned value, see [`create_patch`].
+    ///
+    /// # Errors
+    /// - If any of the optioned values can't be serialized.
+    fn strategic_merge_patch(
+        original: &Self::Optioned,
+        modified: &Self::Optioned,
+    ) -> Result<Value, serde_json::Error>
+    where
+        Self::Optioned: Serialize,
+    {
+        Ok(create_patch(
+            &serde_json::to_value(original)?,
+            &serde_json::to_value(modified)?,
+            &Self::patch_meta(),
+        ))
+    }
+
+    /// Applies the strategic merge patch to the serialized optioned value, see [`apply_patch`].
+    ///
+    /// # Errors
+    /// - If the patch is malformed.
+    /// - If the optioned value can't be serialized or the patched value can't be deserialized.
+    fn apply_strategic_merge_patch(
+        optioned: &Self::Optioned,
+        patch: &Value,
+    ) -> Result<Self::Optioned, PatchError>
+    where
+        Self::Optioned: Serialize + DeserializeOwned,
+    {
+        let value = serde_json::to_value(optioned).map_err(PatchError::Serde)?;
+        let patched = apply_patch(&value, patch, &Self::patch_meta())?;
+        serde_json::from_value(patched).map_err(PatchError::Serde)
+    }
+}
+
+/// The patch strategies of the (sub-)fields of a value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatchMeta {
+    /// The strategies of the fields by their serialized names.
+    pub fields: BTreeMap<String, FieldPatchMeta>,
+    /// The strategies of the (sub-)fields of the map values or list elements.
+    pub elements: Option<Box<PatchMeta>>,
+}
+
+impl PatchMeta {
+    /// Returns the metadata of maps or lists with values or elements of the given metadata.
+    #[must_use]
+    pub fn with_elements(elements: PatchMeta) -> Self {
+        Self {
+            fields: BTreeMap::new(),
+            elements: Some(Box::new(elements)),
+        }
+    }
+
+    /// Returns the strategy, the merge key and the metadata of the field or map value `name`.
+    fn field(&self, name: &str) -> (PatchStrategy, Option<&str>, &PatchMeta) {
+        match self.fields.get(name) {
+            Some(field) => (field.strategy, field.merge_key.as_deref(), &field.meta),
+            None => (PatchStrategy::default(), None, self.elements()),
+        }
+    }
+
+    /// Returns the metadata of the map values or list elements.
+    fn elements(&self) -> &PatchMeta {
+        self.elements.as_deref().unwrap_or(&EMPTY)
+    }
+}
+
+/// The patch strategy and merge key of a field together with the strategies of its (sub-)fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldPatchMeta {
+    /// The `patchStrategy` of the field.
+    pub strategy: PatchStrategy,
+    /// The `patchMergeKey` of a list field with strategy `merge`.
+    pub merge_key: Option<String>,
+    /// The strategies of the (sub-)fields of the field.
+    pub meta: PatchMeta,
+}
+
+/// The `patchStrategy` of a field, a combination of `merge`, `replace` and `retainKeys`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PatchStrategy {
+    /// Lists are merged by their merge key or as sets of values instead of being replaced.
+    pub merge: bool,
+    /// Objects are replaced instead of being merged.
+    pub replace: bool,
+    /// Fields of objects that are not part of the patched value are cleared.
+    pub retain_keys: bool,
+}
+
+/// Returns the strategic merge patch turning `original` into `modified`. Fields only set by `original`
+/// are set to `null`, values that are no objects are replaced as a whole.
+#[must_use]
+pub fn create_patch(original: &Value, modified: &Value, meta: &PatchMeta) -> Value {
+    match (original, modified) {
+        (Value::Object(original), Value::Object(modified)) => {
+            Value::Object(diff_objects(original, modified, meta))
+        }
+        _ => modified.clone(),
+    }
+}
+
+/// Returns the patch of the fields of the objects, empty if they are equal.
+fn diff_objects(
+    original: &Map<String, Value>,
+    modified: &Map<String, Value>,
+    meta: &PatchMeta,
+) -> Map<String, Value> {
+    let mut patch = Map::new();
+    for (key, value) in modified {
+        let (strategy, merge_key, meta) = meta.field(key);
+        match (original.get(key), value) {
+            (Some(original), value) if original == value => {}
+            (Some(Value::Object(_)), Value::Object(value)) if strategy.replace => {
+                let mut value = value.clone();
+                value.insert(PATCH.to_owned(), "replace".into());
+                patch.insert(key.clone(), Value::Object(value));
+            }
+            (Some(Value::Object(original)), Value::Object(value)) => {
+                let mut diff = diff_objects(original, value, meta);
+                if strategy.retain_keys {
+                    let keys = value.keys().cloned().map(Value::String).collect();
+                    diff.insert(RETAIN_KEYS.to_owned(), Value::Array(keys));
+                }
+                patch.insert(key.clone(), Value::Object(diff));
+            }
+            (Some(Value::Array(original)), Value::Array(value)) if strategy.merge => {
+                diff_lists(key, original, value, merge_key, meta.elements(), &mut patch);
+            }
+            _ => {
+                patch.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    for key in original.keys().filter(|key| !modified.contains_key(*key)) {
+        patch.insert(key.clone(), Value::Null);
+    }
+    patch
+}
+
+/// Inserts the patch of the list field `key` with strategy `merge` into `patch`.
+fn diff_lists(
+    key: &str,
+    original: &[Value],
+    modified: &[Value],
+    merge_key: Option<&str>,
+    meta: &PatchMeta,
+    patch: &mut Map<String, Value>,
+) {
+    let mut elements = vec![];
+    let order = if let Some(merge_key) = merge_key {
+        for value in modified {
+            match (find_element(original, value, merge_key), value) {
+                (Some(original), value) if original == value => {}
+                (Some(Value::Object(original)), Value::Object(value)) => {
+                    let mut diff = diff_objects(original, value, meta);
+                    diff.insert(merge_key.to_owned(), value[merge_key].clone());
+                    elements.push(Value::Object(diff));
+                }
+                _ => elements.push(value.clone()),
+            }
+        }
+        for original in original
+            .iter()
+            .filter(|original| find_element(modified, original, merge_key).is_none())
+        {
+            let mut delete = Map::new();
+            if let Some(key) = original.get(merge_key) {
+                delete.insert(merge_key.to_owned(), key.clone());
+            }
+            delete.insert(PATCH.to_owned(), "delete".into());
+            elements.push(Value::Object(delete));
+        }
+        modified
+            .iter()
+            .filter_map(|value| {
+                let key = value.get(merge_key)?.clone();
+                Some(Value::Object(Map::from_iter([(merge_key.to_owned(), key)])))
+            })
+            .collect()
+    } else {
+        elements.extend(
+            modified
+                .iter()
+                .filter(|value| !original.contains(value))
+                .cloned(),
+        );
+        let deleted = original
+            .iter()
+            .filter(|original| !modified.contains(original))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !deleted.is_empty() {
+            patch.insert(
+                format!("{DELETE_FROM_PRIMITIVE_LIST}{key}"),
+                Value::Array(deleted),
+            );
+        }
+        modified.to_vec()
+    };
+    if !elements.is_empty() {
+        patch.insert(key.to_owned(), Value::Array(elements));
+    }
+    patch.insert(format!("{SET_ELEMENT_ORDER}{key}"), Value::Array(order));
+}
+
+/// Returns the element of the list with the same merge key as `element`.
+fn find_element<'a>(list: &'a [Value], element: &Value, merge_key: &str) -> Option<&'a Value> {
+    let key = element.get(merge_key)?;
+    list.iter().find(|value| value.get(merge_key) == Some(key))
+}
+
+/// Applies the strategic merge patch to `original`. The directives are removed from the patched value.
+///
+/// # Errors
+/// - If the patch contains unknown or malformed directives or elements of lists with merge key lack the key.
+pub fn apply_patch(original: &Value, patch: &Value, meta: &PatchMeta) -> Result<Value, PatchError> {
+    Ok(apply_value(Some(original), patch, meta, "")?.unwrap_or(Value::Null))
+}
+
+/// Returns the patched value at `path`, `None` if it is deleted.
+fn apply_value(
+    original: Option<&Value>,
+    value: &Value,
+    meta: &PatchMeta,
+    path: &str,
+) -> Result<Option<Value>, PatchError> {
+    let Value::Object(fields) = value else {
+        return Ok(Some(value.clone()));
+    };
+    let original = match fields.get(PATCH).map(Value::as_str) {
+        None => original.and_then(Value::as_object).cloned(),
+        Some(Some("replace")) => None,
+        Some(Some("delete")) => return Ok(None),
+        Some(_) => {
+            return Err(PatchError::invalid(
+                path,
+                "`$patch` has to be one of \"replace\" or \"delete\"",
+            ));
+        }
+    };
+    let object = apply_object(original.unwrap_or_default(), fields, meta, path)?;
+    Ok(Some(Value::Object(object)))
+}
+
+/// Applies the patched fields and the directives to the object at `path`.
+fn apply_object(
+    mut object: Map<String, Value>,
+    fields: &Map<String, Value>,
+    meta: &PatchMeta,
+    path: &str,
+) -> Result<Map<String, Value>, PatchError> {
+    for (key, value) in fields.iter().filter(|(key, _)| !key.starts_with('$')) {
+        let (strategy, merge_key, meta) = meta.field(key);
+        let path = field_path(path, key);
+        match value {
+            Value::Null => {
+                object.remove(key);
+            }
+            Value::Array(elements) if strategy.merge => {
+                let list = match object.remove(key) {
+                    Some(Value::Array(list)) => list,
+                    _ => vec![],
+                };
+                let list = merge_list(list, elements, merge_key, meta.elements(), &path)?;
+                object.insert(key.clone(), Value::Array(list));
+            }
+            value => match apply_value(object.get(key), value, meta, &path)? {
+                Some(value) => {
+                    object.insert(key.clone(), value);
+                }
+                None => {
+                    object.remove(key);
+                }
+            },
+        }
+    }
+
+    let directives = fields.iter().filter(|(key, _)| key.starts_with('$'));
+    // the removals precede the reordering as the elements of the order are not removed
+    for (key, value) in directives.clone() {
+        let Some(field) = key.strip_prefix(DELETE_FROM_PRIMITIVE_LIST) else {
+            continue;
+        };
+        let deleted = list_directive(value, path, key)?;
+        if let Some(Value::Array(list)) = object.get_mut(field) {
+            list.retain(|value| !deleted.contains(value));
+        }
+    }
+    for (key, value) in directives {
+        if let Some(field) = key.strip_prefix(SET_ELEMENT_ORDER) {
+            let order = list_directive(value, path, key)?;
+            let (_, merge_key, _) = meta.field(field);
+            if let Some(Value::Array(list)) = object.get_mut(field) {
+                reorder(list, order, merge_key);
+            }
+        } else if key == RETAIN_KEYS {
+            let retained = list_directive(value, path, key)?;
+            object.retain(|key, _| retained.iter().any(|retained| retained == key));
+        } else if key != PATCH && !key.starts_with(DELETE_FROM_PRIMITIVE_LIST) {
+            return Err(PatchError::invalid(
+                path,
+                format!("unknown directive `{key}`"),
+            ));
+        }
+    }
+    Ok(object)
+}
+
+/// Returns the values of a directive that has to be a list.
+fn list_directive<'a>(value: &'a Value, path: &str, key: &str) -> Result<&'a [Value], PatchError> {
+    match value {
+        Value::Array(values) => Ok(values),
+        _ => Err(PatchError::invalid(
+            path,
+            format!("`{key}` has to be a list"),
+        )),
+    }
+}
+
+/// Merges the patch elements into the list at `path`. Elements are matched by the merge key if any,
+/// otherwise the list is merged as set of values.
+fn merge_list(
+    mut list: Vec<Value>,
+    elements: &[Value],
+    merge_key: Option<&str>,
+    meta: &PatchMeta,
+    path: &str,
+) -> Result<Vec<Value>, PatchError> {
+    let Some(merge_key) = merge_key else {
+        for element in elements {
+            if !list.contains(element) {
+                list.push(element.clone());
+            }
+        }
+        return Ok(list);
+    };
+    for element in elements {
+        let Some(key) = element.get(merge_key) else {
+            return Err(PatchError::invalid(
+                path,
+                format!("list element without merge key `{merge_key}`"),
+            ));
+        };
+        let position = list
+            .iter()
+            .position(|value| value.get(merge_key) == Some(key));
+        let original = position.map(|i| &list[i]);
+        match (position, apply_value(original, element, meta, &entry_path(path, key))?) {
+            (Some(i), Some(value)) => list[i] = value,
+            (Some(i), None) => {
+                list.remove(i);
+            }
+            (None, Some(value)) => list.push(value),
+            (None, None) => {}
+        }
+    }
+    Ok(list)
+}
+
+/// Sorts the elements listed in `order` (by their merge key if any) to the front in the given order,
+/// keeping the order of the other elements.
+fn reorder(list: &mut [Value], order: &[Value], merge_key: Option<&str>) {
+    list.sort_by_key(|value| {
+        order
+            .iter()
+            .position(|ordered| match merge_key {
+                Some(merge_key) => {
+                    ordered.get(merge_key).is_some() && ordered.get(merge_key) == value.get(merge_key)
+                }
+                None => ordered == value,
+            })
+            .unwrap_or(order.len())
+    });
+}
+
+/// Error for strategic merge patches that can't be applied.
+#[derive(Debug)]
+pub enum PatchError {
+    /// The patch is malformed at the given path.
+    Invalid {
+        /// The path of the malformed (sub-)field.
+        path: String,
+        /// The description of the problem.
+        message: String,
+    },
+    /// The value can't be serialized or the patched value can't be deserialized.
+    Serde(serde_json::Error),
+}
+
+impl PatchError {
+    fn invalid(path: &str, message: impl Into<String>) -> Self {
+        Self::Invalid {
+            path: path.to_owned(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Invalid { path, message } => {
+                write!(f, "invalid strategic merge patch at `{path}`: {message}")
+            }
+            Self::Serde(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl core::error::Error for PatchError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Invalid { .. } => None,
+            Self::Serde(err) => Some(err),
+        }
+    }
+}
+
+/// Helper macro to generate impls of `OptionableStrategicMerge` for pointers and wrappers
+/// that are serialized like the inner value.
+macro_rules! impl_strategic_merge_forward {
+    ($([$($generics:tt)*] $t:ty => $inner:ty),* $(,)?) => {
+        $(impl<$($generics)*> OptionableStrategicMerge for $t {
+            fn patch_meta() -> PatchMeta {
+                <$inner>::patch_meta()
+            }
+        })*
+    };
+}
+
+/// Helper macro to generate impls of `OptionableStrategicMerge` for lists and maps
+/// with the strategies of their elements or values.
+macro_rules! impl_strategic_merge_elements {
+    ($([$($generics:tt)*] $t:ty => $inner:ty),* $(,)?) => {
+        $(impl<$($generics)*> $crate::strategic_merge::OptionableStrategicMerge for $t {
+            fn patch_meta() -> $crate::strategic_merge::PatchMeta {
+                $crate::strategic_merge::PatchMeta::with_elements(<$inner as $crate::strategic_merge::OptionableStrategicMerge>::patch_meta())
+            }
+        })*
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use impl_strategic_merge_elements;
+
+impl_strategic_merge_forward!(
+    [T: OptionableStrategicMerge] Option<T> => T,
+    [T: OptionableStrategicMerge, E] Result<T, E> => T,
+    [T: OptionableStrategicMerge] &T => T,
+    [T: OptionableStrategicMerge] Box<T> => T,
+    [T: OptionableStrategicMerge] Rc<T> => T,
+    [T: OptionableStrategicMerge] Arc<T> => T,
+    [T: OptionableStrategicMerge] Reverse<T> => T,
+    [T: OptionableStrategicMerge] Wrapping<T> => T,
+    [T: OptionableStrategicMerge] Saturating<T> => T,
+    [T: OptionableStrategicMerge] OnceCell<T> => T,
+    [T: OptionableStrategicMerge] OnceLock<T> => T,
+);
+
+impl<B: ?Sized + ToOwned> OptionableStrategicMerge for Cow<'_, B>
+where
+    B::Owned: OptionableStrategicMerge,
+{
+    fn patch_meta() -> PatchMeta {
+        B::Owned::patch_meta()
+    }
+}
+
+impl_strategic_merge_elements!(
+    [T: OptionableStrategicMerge, const N: usize] [T; N] => T,
+    [T: OptionableStrategicMerge] Vec<T> => T,
+    [T: OptionableStrategicMerge] VecDeque<T> => T,
+    [T: OptionableStrategicMerge] LinkedList<T> => T,
+    [T: OptionableStrategicMerge] BTreeSet<T> => T,
+    [T: OptionableStrategicMerge] BinaryHeap<T> => T,
+    [T: OptionableStrategicMerge, S] HashSet<T, S> => T,
+    [K, T: OptionableStrategicMerge] BTreeMap<K, T> => T,
+    [K, T: OptionableStrategicMerge, S] HashMap<K, T, S> => T,
+);
+
+// Ranges as well as cells and locks are replaced as a whole.
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] Range<T>);
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] RangeInclusive<T>);
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] RangeFrom<T>);
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] RangeTo<T>);
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] RangeToInclusive<T>);
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] Cell<T>);
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] RefCell<T>);
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] Mutex<T>);
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] RwLock<T>);
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] Pin<T>);
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] RcWeak<T>);
+crate::impl_strategic_merge_whole!([T: OptionableStrategicMerge] ArcWeak<T>);
+crate::impl_strategic_merge_whole!([T: ?Sized] PhantomData<T>);
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_patch, create_patch, FieldPatchMeta, PatchError, PatchMeta, PatchStrategy};
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn meta(field: &str, strategy: PatchStrategy) -> PatchMeta {
+        PatchMeta {
+            fields: BTreeMap::from([(
+                field.to_owned(),
+                FieldPatchMeta {
+                    strategy,
+                    ..FieldPatchMeta::default()
+                },
+            )]),
+            elements: None,
+        }
+    }
+
+    #[test]
+    /// Check that lists of values without merge key are merged as sets keeping the order of the modified list.
+    fn primitive_list() {
+        let meta = meta(
+            "finalizers",
+            PatchStrategy {
+                merge: true,
+                ..PatchStrategy::default()
+            },
+        );
+        let original = json!({"finalizers": ["a", "b", "c"]});
+        let modified = json!({"finalizers": ["d", "c", "a"]});
+        let patch = create_patch(&original, &modified, &meta);
+        assert_eq!(
+            patch,
+            json!({
+                "finalizers": ["d"],
+                "$deleteFromPrimitiveList/finalizers": ["b"],
+                "$setElementOrder/finalizers": ["d", "c", "a"],
+            })
+        );
+        assert_eq!(apply_patch(&original, &patch, &meta).unwrap(), modified);
+    }
+
+    #[test]
+    /// Check that objects are replaced with `replace` and cleared except for the `$retainKeys` with `retainKeys`.
+    fn replace_and_retain_keys() {
+        let original = json!({"strategy": {"type": "RollingUpdate", "rollingUpdate": {"maxSurge": 1}}});
+        let modified = json!({"strategy": {"type": "Recreate"}});
+        let replace = meta(
+            "strategy",
+            PatchStrategy {
+                replace: true,
+                ..PatchStrategy::default()
+            },
+        );
+        let patch = create_patch(&original, &modified, &replace);
+        assert_eq!(
+            patch,
+            json!({"strategy": {"type": "Recreate", "$patch": "replace"}})
+        );
+        assert_eq!(apply_patch(&original, &patch, &replace).unwrap(), modified);
+
+        let retain_keys = meta(
+            "strategy",
+            PatchStrategy {
+                retain_keys: true,
+                ..PatchStrategy::default()
+            },
+        );
+        let patch = json!({"strategy": {"type": "Recreate", "$retainKeys": ["type"]}});
+        assert_eq!(apply_patch(&original, &patch, &retain_keys).unwrap(), modified);
+    }
+
+    #[test]
+    /// Check that unknown directives and elements without merge key are rejected with their path.
+    fn invalid_patches() {
+        let mut meta = meta(
+            "ports",
+            PatchStrategy {
+                merge: true,
+                ..PatchStrategy::default()
+            },
+        );
+        meta.fields.get_mut("ports").unwrap().merge_key = Some("name".to_owned());
+        let original = json!({"ports": [{"name": "http"}]});
+        let Err(PatchError::Invalid { path, .. }) =
+            apply_patch(&original, &json!({"ports": [{"port": 80}]}), &meta)
+        else {
+            panic!("expected an invalid patch");
+        };
+        assert_eq!(path, "ports");
+        let Err(PatchError::Invalid { path, message }) =
+            apply_patch(&original, &json!({"spec": {"$unknown": 1}}), &meta)
+        else {
+            panic!("expected an invalid patch");
+        };
+        assert_eq!(
+            (path.as_str(), message.as_str()),
+            ("spec", "unknown directive `$unknown`")
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Three-way merge of optioned values to reconcile concurrent changes, e.g. like `kubectl apply`:
+//! `base` is the last applied value, `ours` the live value and `theirs` the desired value.
+//!
+//! Changes of only one side relative to `base` are kept, changes of both sides to different values are conflicts
+//! which are reported with their field paths (see [`crate::OptionableFieldPaths`] for the path format) and resolved
+//! in favour of `theirs`. Fields set in `base` but unset in `theirs` are unset in the result unless `ours` changed them,
+//! fields set only by `ours` (e.g. defaulted by a server) are kept.
+//! ```rust
+//! # use optionable::{Optionable, OptionableConvert};
+//! # use optionable::three_way::OptionableThreeWayMerge;
+//! #[derive(Optionable, Debug, PartialEq)]
+//! #[optionable(three_way, derive(Debug, PartialEq))]
+//! struct Deployment {
+//!     replicas: Option<u32>,
+//!     image: Option<String>,
+//!     paused: Option<bool>,
+//! }
+//!
+//! let last_applied = DeploymentOpt { replicas: Some(2), image: Some("nginx".to_owned()), paused: None };
+//! let live = Deployment { replicas: Some(5), image: Some("nginx".to_owned()), paused: Some(true) };
+//! let desired = DeploymentOpt { replicas: Some(3), image: None, paused: None };
+//! let merged = Deployment::three_way_merge(Some(&last_applied), live.into_optioned(), desired);
+//! assert_eq!(merged.conflicts, vec!["replicas"]);
+//! assert_eq!(
+//!     Deployment::try_from_optioned(merged.value).unwrap(),
+//!     Deployment { replicas: Some(3), image: None, paused: Some(true) }
+//! );
+//! ```
+use crate::__private::{entry_path, three_way_merge_value};
+use crate::Optionable;
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, LinkedList, VecDeque};
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::cmp::Reverse;
+use core::fmt::Display;
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+use core::num::{Saturating, Wrapping};
+use core::ops::{Range, RangeFrom, RangeInclusive, RangeTo, RangeToInclusive};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+/// Extension trait to merge two optioned values that both derive from a common `base`. Derived via
+/// `#[optionable(three_way)]`, the optioned type has to implement `PartialEq`.
+pub trait OptionableThreeWayMerge: Optionable<Optioned: PartialEq> {
+    /// Merges the changes of `ours` and `theirs` relative to `base` for the optioned value located at `path`
+    /// and records the paths of conflicting changes in `conflicts`, `theirs` taking precedence for those.
+    /// `base` is `None` if there is no common ancestor.
+    /// Values without inner structure and collections without keys are merged as a whole, hence the default
+    /// keeps the value of the side that changed it and reports a conflict if both did.
+    fn three_way_merge_at(
+        base: Option<&Self::Optioned>,
+        ours: Self::Optioned,
+        theirs: Self::Optioned,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> Self::Optioned {
+        three_way_merge_value(base, ours, theirs, path, conflicts)
+    }
+
+    /// Merges the changes of `ours` and `theirs` relative to `base`.
+    fn three_way_merge(
+        base: Option<&Self::Optioned>,
+        ours: Self::Optioned,
+        theirs: Self::Optioned,
+    ) -> ThreeWayMerge<Self::Optioned> {
+        let mut conflicts = vec![];
+        let value = Self::three_way_merge_at(base, ours, theirs, "", &mut conflicts);
+        ThreeWayMerge { value, conflicts }
+    }
+}
+
+/// Result of a three-way merge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreeWayMerge<T> {
+    /// The merged value, conflicts are resolved in favour of `theirs`.
+    pub value: T,
+    /// The paths of the (sub-)fields changed differently by `ours` and `theirs`.
+    pub conflicts: Vec<String>,
+}
+
+impl<T> ThreeWayMerge<T> {
+    /// Whether both sides changed any (sub-)field differently.
+    #[must_use]
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
+
+// An unset value counts as a change like any other value, values set on both sides are merged recursively.
+impl<T: OptionableThreeWayMerge> OptionableThreeWayMerge for Option<T> {
+    fn three_way_merge_at(
+        base: Option<&Option<T::Optioned>>,
+        ours: Option<T::Optioned>,
+        theirs: Option<T::Optioned>,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> Option<T::Optioned> {
+        merge_entry::<T>(base.and_then(Option::as_ref), ours, theirs, path, conflicts)
+    }
+}
+
+impl<T: OptionableThreeWayMerge> OptionableThreeWayMerge for Box<T> {
+    fn three_way_merge_at(
+        base: Option<&Box<T::Optioned>>,
+        ours: Box<T::Optioned>,
+        theirs: Box<T::Optioned>,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> Box<T::Optioned> {
+        Box::new(T::three_way_merge_at(
+            base.map(|base| &**base),
+            *ours,
+            *theirs,
+            path,
+            conflicts,
+        ))
+    }
+}
+
+impl<B: ?Sized + ToOwned> OptionableThreeWayMerge for Cow<'_, B>
+where
+    B::Owned: OptionableThreeWayMerge,
+{
+    fn three_way_merge_at(
+        base: Option<&<B::Owned as Optionable>::Optioned>,
+        ours: <B::Owned as Optionable>::Optioned,
+        theirs: <B::Owned as Optionable>::Optioned,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> <B::Owned as Optionable>::Optioned {
+        B::Owned::three_way_merge_at(base, ours, theirs, path, conflicts)
+    }
+}
+
+// Arrays have a fixed length and are merged element-wise, hence the elements are addressed by their index.
+impl<T: OptionableThreeWayMerge, const N: usize> OptionableThreeWayMerge for [T; N] {
+    fn three_way_merge_at(
+        base: Option<&[T::Optioned; N]>,
+        ours: [T::Optioned; N],
+        theirs: [T::Optioned; N],
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> [T::Optioned; N] {
+        let mut theirs = theirs.into_iter().enumerate();
+        ours.map(|ours| {
+            let Some((i, theirs)) = theirs.next() else {
+                unreachable!("arrays of the same length")
+            };
+            let base = base.map(|base| &base[i]);
+            T::three_way_merge_at(base, ours, theirs, &entry_path(path, i), conflicts)
+        })
+    }
+}
+
+impl<K: Ord + Display, T: OptionableThreeWayMerge> OptionableThreeWayMerge for BTreeMap<K, T> {
+    fn three_way_merge_at(
+        base: Option<&BTreeMap<K, T::Optioned>>,
+        ours: BTreeMap<K, T::Optioned>,
+        theirs: BTreeMap<K, T::Optioned>,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> BTreeMap<K, T::Optioned> {
+        merge_entries::<K, T, _>(
+            base,
+            ours,
+            theirs,
+            (BTreeMap::get, BTreeMap::remove),
+            |key| entry_path(path, key),
+            conflicts,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash + Display, T: OptionableThreeWayMerge, S: BuildHasher + Default>
+    OptionableThreeWayMerge for HashMap<K, T, S>
+{
+    fn three_way_merge_at(
+        base: Option<&HashMap<K, T::Optioned, S>>,
+        ours: HashMap<K, T::Optioned, S>,
+        theirs: HashMap<K, T::Optioned, S>,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> HashMap<K, T::Optioned, S> {
+        merge_entries::<K, T, _>(
+            base,
+            ours,
+            theirs,
+            (HashMap::get, HashMap::remove),
+            |key| entry_path(path, key),
+            conflicts,
+        )
+    }
+}
+
+/// Merges the optional entries of a map analogously to an `Option`.
+pub(crate) fn merge_entry<T: OptionableThreeWayMerge + ?Sized>(
+    base: Option<&T::Optioned>,
+    ours: Option<T::Optioned>,
+    theirs: Option<T::Optioned>,
+    path: &str,
+    conflicts: &mut Vec<String>,
+) -> Option<T::Optioned> {
+    if ours == theirs || base == theirs.as_ref() {
+        return ours;
+    }
+    if base == ours.as_ref() {
+        return theirs;
+    }
+    match (ours, theirs) {
+        (Some(ours), Some(theirs)) => {
+            Some(T::three_way_merge_at(base, ours, theirs, path, conflicts))
+        }
+        (_, theirs) => {
+            conflicts.push(path.to_owned());
+            theirs
+        }
+    }
+}
+
+/// Lookup and removal of map entries by key.
+pub(crate) type MapAccess<M, K, V> = (
+    for<'a> fn(&'a M, &K) -> Option<&'a V>,
+    fn(&mut M, &K) -> Option<V>,
+);
+
+/// Merges the maps per key, the entries of `theirs` come first followed by the remaining ones of `ours`.
+/// `entry_path` returns the path of the entry with the given key.
+pub(crate) fn merge_entries<K, T: OptionableThreeWayMerge + ?Sized, M>(
+    base: Option<&M>,
+    mut ours: M,
+    theirs: M,
+    (get, remove): MapAccess<M, K, T::Optioned>,
+    entry_path: impl Fn(&K) -> String,
+    conflicts: &mut Vec<String>,
+) -> M
+where
+    M: IntoIterator<Item = (K, T::Optioned)> + FromIterator<(K, T::Optioned)>,
+{
+    let mut merged = vec![];
+    for (key, theirs) in theirs {
+        let ours = remove(&mut ours, &key);
+        let base = base.and_then(|base| get(base, &key));
+        let path = entry_path(&key);
+        merged.push((
+            key,
+            merge_entry::<T>(base, ours, Some(theirs), &path, conflicts),
+        ));
+    }
+    for (key, ours) in ours {
+        let base = base.and_then(|base| get(base, &key));
+        let path = entry_path(&key);
+        merged.push((
+            key,
+            merge_entry::<T>(base, Some(ours), None, &path, conflicts),
+        ));
+    }
+    merged
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| (key, value)))
+        .collect()
+}
+
+// Collections without keys, ranges, cells as well as shared pointers and wrappers are merged as a whole.
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] &T);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] Vec<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] VecDeque<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] LinkedList<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] BTreeSet<T>);
+#[cfg(feature = "std")]
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge<Optioned: Eq + Hash>, S: BuildHasher] HashSet<T, S>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] Range<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] RangeInclusive<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] RangeFrom<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] RangeTo<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] RangeToInclusive<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge<Optioned: Copy>] Cell<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] RefCell<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] Rc<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] Arc<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] Reverse<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] Wrapping<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge] Saturating<T>);
+crate::impl_three_way_whole!([T: OptionableThreeWayMerge, E: PartialEq] Result<T, E>);
+crate::impl_three_way_whole!([T: ?Sized] PhantomData<T>);
+
+#[cfg(test)]
+mod tests {
+    use crate::three_way::OptionableThreeWayMerge;
+    use alloc::collections::BTreeMap;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    /// Check that map entries are merged per key: removed, added and changed entries of either side are kept,
+    /// entries changed differently by both sides are conflicts.
+    fn map_entries() {
+        let base = BTreeMap::from([("a", 1), ("b", 2), ("c", 3)]);
+        let ours = BTreeMap::from([("a", 1), ("b", 5), ("c", 4), ("d", 6)]);
+        let theirs = BTreeMap::from([("b", 2), ("c", 7)]);
+        let merged = <BTreeMap<&str, i32>>::three_way_merge(Some(&base), ours, theirs);
+        assert_eq!(merged.value, BTreeMap::from([("b", 5), ("c", 7), ("d", 6)]));
+        assert_eq!(merged.conflicts, vec!["[c]"]);
+    }
+
+    #[test]
+    /// Check that arrays are merged element-wise and collections without keys as a whole.
+    fn arrays_and_whole_values() {
+        let merged = <[Option<i32>; 3]>::three_way_merge(
+            Some(&[Some(1), Some(2), None]),
+            [Some(1), Some(3), Some(4)],
+            [None, Some(2), Some(5)],
+        );
+        assert_eq!(merged.value, [None, Some(3), Some(5)]);
+        assert_eq!(merged.conflicts, vec!["[2]"]);
+
+        let merged = <Vec<i32>>::three_way_merge(Some(&vec![1]), vec![1, 2], vec![3]);
+        assert_eq!(merged.value, vec![3]);
+        assert_eq!(merged.conflicts, vec![""]);
+        let merged = <Option<Vec<i32>>>::three_way_merge(None, Some(vec![1]), None);
+        assert_eq!(merged.value, Some(vec![1]));
+        assert!(!merged.has_conflicts());
+    }
+}
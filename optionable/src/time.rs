@@ -0,0 +1,29 @@
+use crate::{impl_optional_self, Optionable};
+use time::{
+    Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcDateTime, UtcOffset, Weekday,
+};
+
+impl_optional_self!(
+    Date,
+    Duration,
+    Month,
+    OffsetDateTime,
+    PrimitiveDateTime,
+    Time,
+    UtcDateTime,
+    UtcOffset,
+    Weekday
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use time::{Duration, OffsetDateTime};
+
+    #[test]
+    /// Check that exemplary types from `time` implement optionable.
+    fn time() {
+        let _: <OffsetDateTime as Optionable>::Optioned = OffsetDateTime::UNIX_EPOCH;
+        let _: <Duration as Optionable>::Optioned = Duration::seconds(1);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::three_way::OptionableThreeWayMerge;
+use crate::{Error, Optionable, OptionableConvert, OptionableFieldPaths};
+use tinyvec::{ArrayVec, TinyVec};
+
+// `tinyvec` requires the items to implement `Default` as the inline storage is always initialized.
+impl<T: Optionable, const N: usize> Optionable for TinyVec<[T; N]>
+where
+    T: Default,
+    T::Optioned: Default,
+{
+    type Optioned = TinyVec<[T::Optioned; N]>;
+}
+
+impl<T: Optionable, const N: usize> Optionable for ArrayVec<[T; N]>
+where
+    T: Default,
+    T::Optioned: Default,
+{
+    type Optioned = ArrayVec<[T::Optioned; N]>;
+}
+
+/// Helper macro to generate an impl of `OptionableConvert` for the `tinyvec` collections.
+/// They are converted element-wise and replaced as a whole when merging.
+macro_rules! impl_convert_tinyvec {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableConvert + Default, const N: usize> OptionableConvert for $t<[T; N]>
+        where
+            T::Optioned: Default,
+        {
+            fn into_optioned(self) -> $t<[T::Optioned; N]> {
+                self.into_iter().map(T::into_optioned).collect()
+            }
+
+            fn try_from_optioned(value: $t<[T::Optioned; N]>) -> Result<Self, Error> {
+                value.into_iter().map(T::try_from_optioned).collect()
+            }
+
+            fn merge(&mut self, other: $t<[T::Optioned; N]>) -> Result<(), Error> {
+                *self = Self::try_from_optioned(other)?;
+                Ok(())
+            }
+        })*
+    };
+}
+
+impl_convert_tinyvec!(TinyVec, ArrayVec);
+
+impl<T: OptionableFieldPaths + Default, const N: usize> OptionableFieldPaths for TinyVec<[T; N]> where
+    T::Optioned: Default
+{
+}
+
+impl<T: OptionableFieldPaths + Default, const N: usize> OptionableFieldPaths for ArrayVec<[T; N]> where
+    T::Optioned: Default
+{
+}
+
+impl<T: OptionableThreeWayMerge + Default, const N: usize> OptionableThreeWayMerge
+    for TinyVec<[T; N]>
+where
+    T::Optioned: Default,
+{
+}
+
+impl<T: OptionableThreeWayMerge + Default, const N: usize> OptionableThreeWayMerge
+    for ArrayVec<[T; N]>
+where
+    T::Optioned: Default,
+{
+}
+
+#[cfg(feature = "serde_json")]
+impl<T: crate::fields_v1::OptionableFieldsV1 + Default, const N: usize>
+    crate::fields_v1::OptionableFieldsV1 for TinyVec<[T; N]>
+where
+    T::Optioned: Default,
+{
+}
+
+#[cfg(feature = "serde_json")]
+impl<T: crate::fields_v1::OptionableFieldsV1 + Default, const N: usize>
+    crate::fields_v1::OptionableFieldsV1 for ArrayVec<[T; N]>
+where
+    T::Optioned: Default,
+{
+}
+
+#[cfg(feature = "serde_json")]
+impl<T: crate::strategic_merge::OptionableStrategicMerge + Default, const N: usize> crate::strategic_merge::OptionableStrategicMerge
+    for TinyVec<[T; N]>
+where
+    T::Optioned: Default,
+{
+    fn patch_meta() -> crate::strategic_merge::PatchMeta {
+        crate::strategic_merge::PatchMeta::with_elements(T::patch_meta())
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl<T: crate::strategic_merge::OptionableStrategicMerge + Default, const N: usize> crate::strategic_merge::OptionableStrategicMerge
+    for ArrayVec<[T; N]>
+where
+    T::Optioned: Default,
+{
+    fn patch_meta() -> crate::strategic_merge::PatchMeta {
+        crate::strategic_merge::PatchMeta::with_elements(T::patch_meta())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use tinyvec::{tiny_vec, TinyVec};
+
+    #[test]
+    /// Check that `TinyVec` implements optionable.
+    fn tiny_vec() {
+        let a = tiny_vec!([i32; 2] => 1, 2);
+        let _: <TinyVec<[i32; 2]> as Optionable>::Optioned = a;
+    }
+}
@@ -0,0 +1,335 @@
+//! Optioned representation of the dynamic [`toml::Value`] that merges analogously to
+//! [RFC 7396](https://www.rfc-editor.org/rfc/rfc7396) (JSON Merge Patch).
+use crate::__private::{entry_path, set_value_path, three_way_merge_value};
+use crate::field_mask::{FieldMaskTree, PathSegment};
+use crate::field_paths::{is_entry_path, mask_entry};
+use crate::three_way::{merge_entries, OptionableThreeWayMerge};
+use crate::{Error, Optionable, OptionableConvert, OptionableFieldPaths};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use toml::value::{Datetime, Table};
+use toml::Value;
+
+/// Optioned type of [`toml::Value`], i.e. a TOML merge patch.
+///
+/// As TOML has no `null` value, the table entries are explicitly optional:
+/// absent entries are left untouched by a merge while entries set to `None` are removed from the target table.
+/// All other values (including arrays) replace the target value as a whole.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ValueOpt {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Datetime(Datetime),
+    /// Arrays are replaced as a whole and hence keep their full values.
+    Array(Vec<Value>),
+    Table(BTreeMap<String, Option<ValueOpt>>),
+}
+
+// Like the TOML values themselves, their patches can hold any value.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for ValueOpt {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "AnyValue".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        true.into()
+    }
+}
+
+// `ComposeSchema` instead of `PartialSchema` (provided via a blanket impl) to support containers like `Vec`.
+#[cfg(feature = "utoipa")]
+impl utoipa::__dev::ComposeSchema for ValueOpt {
+    fn compose(
+        _: Vec<utoipa::openapi::RefOr<utoipa::openapi::schema::Schema>>,
+    ) -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        utoipa::openapi::schema::ObjectBuilder::new()
+            .schema_type(utoipa::openapi::schema::SchemaType::AnyValue)
+            .into()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl utoipa::ToSchema for ValueOpt {}
+
+impl Optionable for Value {
+    type Optioned = ValueOpt;
+}
+
+impl Optionable for ValueOpt {
+    type Optioned = ValueOpt;
+}
+
+impl Optionable for Table {
+    type Optioned = BTreeMap<String, Option<ValueOpt>>;
+
+    fn is_empty_optioned(optioned: &BTreeMap<String, Option<ValueOpt>>) -> bool {
+        optioned.is_empty()
+    }
+}
+
+impl OptionableConvert for Value {
+    fn into_optioned(self) -> ValueOpt {
+        match self {
+            Value::String(value) => ValueOpt::String(value),
+            Value::Integer(value) => ValueOpt::Integer(value),
+            Value::Float(value) => ValueOpt::Float(value),
+            Value::Boolean(value) => ValueOpt::Boolean(value),
+            Value::Datetime(value) => ValueOpt::Datetime(value),
+            Value::Array(value) => ValueOpt::Array(value),
+            Value::Table(value) => ValueOpt::Table(value.into_optioned()),
+        }
+    }
+
+    fn try_from_optioned(value: ValueOpt) -> Result<Self, Error> {
+        Ok(match value {
+            ValueOpt::String(value) => Value::String(value),
+            ValueOpt::Integer(value) => Value::Integer(value),
+            ValueOpt::Float(value) => Value::Float(value),
+            ValueOpt::Boolean(value) => Value::Boolean(value),
+            ValueOpt::Datetime(value) => Value::Datetime(value),
+            ValueOpt::Array(value) => Value::Array(value),
+            ValueOpt::Table(value) => Value::Table(Table::try_from_optioned(value)?),
+        })
+    }
+
+    fn merge(&mut self, other: ValueOpt) -> Result<(), Error> {
+        match (self, other) {
+            (Value::Table(target), ValueOpt::Table(patch)) => target.merge(patch)?,
+            (target, other) => *target = Value::try_from_optioned(other)?,
+        }
+        Ok(())
+    }
+}
+
+// Merging two patches combines them, entries set to `None` are kept to remove them from the final target.
+impl OptionableConvert for ValueOpt {
+    fn into_optioned(self) -> ValueOpt {
+        self
+    }
+
+    fn try_from_optioned(value: ValueOpt) -> Result<Self, Error> {
+        Ok(value)
+    }
+
+    fn merge(&mut self, other: ValueOpt) -> Result<(), Error> {
+        match (self, other) {
+            (ValueOpt::Table(target), ValueOpt::Table(patch)) => {
+                for (k, v) in patch {
+                    match (target.get_mut(&k), v) {
+                        (Some(Some(value)), Some(v)) => value.merge(v)?,
+                        // the entry is removed first, hence a table is applied to an empty table
+                        (Some(None), Some(v)) => {
+                            target.insert(k, Some(Value::try_from_optioned(v)?.into_optioned()));
+                        }
+                        (_, v) => {
+                            target.insert(k, v);
+                        }
+                    }
+                }
+            }
+            // the table is applied to an empty table as the entry is no table, dropping its `None`s
+            (target, other) => *target = Value::try_from_optioned(other)?.into_optioned(),
+        }
+        Ok(())
+    }
+}
+
+impl OptionableConvert for Table {
+    fn into_optioned(self) -> BTreeMap<String, Option<ValueOpt>> {
+        self.into_iter()
+            .map(|(k, v)| (k, Some(v.into_optioned())))
+            .collect()
+    }
+
+    /// Entries set to `None` are dropped as TOML has no `null`, converting a table into its optioned type never sets them.
+    fn try_from_optioned(value: BTreeMap<String, Option<ValueOpt>>) -> Result<Self, Error> {
+        let mut table = Table::new();
+        table.merge(value)?;
+        Ok(table)
+    }
+
+    fn merge(&mut self, other: BTreeMap<String, Option<ValueOpt>>) -> Result<(), Error> {
+        for (k, v) in other {
+            match (self.get_mut(&k), v) {
+                (_, None) => {
+                    self.remove(&k);
+                }
+                (Some(value), Some(v)) => value.merge(v)?,
+                (None, Some(v)) => {
+                    self.insert(k, Value::try_from_optioned(v)?);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Table entries are addressed like map entries, all other values are set as a whole.
+impl OptionableFieldPaths for Value {
+    fn set_field_paths_optioned(optioned: &ValueOpt, prefix: &str, paths: &mut Vec<String>) {
+        match optioned {
+            ValueOpt::Table(optioned) => Table::set_field_paths_optioned(optioned, prefix, paths),
+            _ => paths.push(prefix.to_owned()),
+        }
+    }
+
+    fn mask_optioned(optioned: &mut ValueOpt, mask: &FieldMaskTree) {
+        if let ValueOpt::Table(optioned) = optioned {
+            Table::mask_optioned(optioned, mask);
+        }
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        is_entry_path::<Value>(segments)
+    }
+}
+
+impl OptionableFieldPaths for Table {
+    /// Entries set to `None` are recorded as well as they remove the entry.
+    fn set_field_paths_optioned(
+        optioned: &BTreeMap<String, Option<ValueOpt>>,
+        prefix: &str,
+        paths: &mut Vec<String>,
+    ) {
+        let len = paths.len();
+        for (k, v) in optioned {
+            let path = entry_path(prefix, k);
+            match v {
+                Some(v) => Value::set_field_paths_optioned(v, &path, paths),
+                None => paths.push(path),
+            }
+        }
+        set_value_path(prefix, len, paths);
+    }
+
+    fn mask_optioned(optioned: &mut BTreeMap<String, Option<ValueOpt>>, mask: &FieldMaskTree) {
+        optioned.retain(|k, v| mask_entry::<Option<Value>>(k, v, mask));
+    }
+
+    fn is_field_path(segments: &[PathSegment]) -> bool {
+        is_entry_path::<Value>(segments)
+    }
+}
+
+// Table entries are managed like map entries, all other values as a whole.
+#[cfg(feature = "serde_json")]
+impl crate::fields_v1::OptionableFieldsV1 for Value {
+    fn fields_v1_optioned(
+        optioned: &ValueOpt,
+        fields: &mut serde_json::Map<String, serde_json::Value>,
+    ) {
+        if let ValueOpt::Table(optioned) = optioned {
+            Table::fields_v1_optioned(optioned, fields);
+        }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl crate::fields_v1::OptionableFieldsV1 for Table {
+    /// Entries set to `None` are managed as well as they remove the entry.
+    fn fields_v1_optioned(
+        optioned: &BTreeMap<String, Option<ValueOpt>>,
+        fields: &mut serde_json::Map<String, serde_json::Value>,
+    ) {
+        crate::fields_v1::map_fields_v1::<String, Option<Value>>(optioned, fields);
+    }
+}
+
+// Objects are merged recursively by the strategic merge patches without any further strategies.
+#[cfg(feature = "serde_json")]
+crate::impl_strategic_merge_whole!([] Value);
+#[cfg(feature = "serde_json")]
+crate::impl_strategic_merge_whole!([] Table);
+
+// Table entries are merged per key, all other values are merged as a whole.
+impl OptionableThreeWayMerge for Value {
+    fn three_way_merge_at(
+        base: Option<&ValueOpt>,
+        ours: ValueOpt,
+        theirs: ValueOpt,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> ValueOpt {
+        match (ours, theirs) {
+            (ValueOpt::Table(ours), ValueOpt::Table(theirs)) => {
+                let base = match base {
+                    Some(ValueOpt::Table(base)) => Some(base),
+                    _ => None,
+                };
+                ValueOpt::Table(Table::three_way_merge_at(
+                    base, ours, theirs, path, conflicts,
+                ))
+            }
+            (ours, theirs) => three_way_merge_value(base, ours, theirs, path, conflicts),
+        }
+    }
+}
+
+// Entries set to `None` remove the entry and are merged like any other value.
+impl OptionableThreeWayMerge for Table {
+    fn three_way_merge_at(
+        base: Option<&BTreeMap<String, Option<ValueOpt>>>,
+        ours: BTreeMap<String, Option<ValueOpt>>,
+        theirs: BTreeMap<String, Option<ValueOpt>>,
+        path: &str,
+        conflicts: &mut Vec<String>,
+    ) -> BTreeMap<String, Option<ValueOpt>> {
+        merge_entries::<String, Option<Value>, _>(
+            base,
+            ours,
+            theirs,
+            (BTreeMap::get, BTreeMap::remove),
+            |key| entry_path(path, key),
+            conflicts,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::toml::ValueOpt;
+    use crate::OptionableConvert;
+    use serde_json::json;
+    use toml::Value;
+
+    #[test]
+    /// Check that a TOML merge patch is applied recursively and removes `None` entries.
+    fn merge() {
+        let mut target = Value::try_from(json!({"a": {"b": "c", "d": [1, 2]}, "e": "f"})).unwrap();
+        let patch: ValueOpt =
+            serde_json::from_value(json!({"a": {"d": [3], "g": "h"}, "e": null})).unwrap();
+        target.merge(patch).unwrap();
+        let expected = Value::try_from(json!({"a": {"b": "c", "d": [3], "g": "h"}})).unwrap();
+        assert_eq!(target, expected);
+    }
+
+    #[test]
+    /// Check that merging two patches equals applying them in sequence if the first one
+    /// sets an entry to a non-table and the second one to a table with `None` entries,
+    /// the latter is applied to an empty table and hence loses its `None` entries.
+    fn merge_patches_non_table() {
+        let first: ValueOpt = serde_json::from_value(json!({"a": 1, "b": null})).unwrap();
+        let second: ValueOpt =
+            serde_json::from_value(json!({"a": {"c": null, "d": 2}, "b": {"e": null}})).unwrap();
+        let mut patch = first.clone();
+        patch.merge(second.clone()).unwrap();
+        let expected: ValueOpt = serde_json::from_value(json!({"a": {"d": 2}, "b": {}})).unwrap();
+        assert_eq!(patch, expected);
+
+        let target = Value::try_from(json!({"a": [3], "b": 4, "f": 5})).unwrap();
+        let mut sequential = target.clone();
+        sequential.merge(first).unwrap();
+        sequential.merge(second).unwrap();
+        let mut composed = target;
+        composed.merge(patch).unwrap();
+        assert_eq!(composed, sequential);
+    }
+}
@@ -0,0 +1,16 @@
+use crate::{impl_optional_self, Optionable};
+use url::Url;
+
+impl_optional_self!(Url);
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use url::Url;
+
+    #[test]
+    /// Check that `Url` implements optionable.
+    fn url() {
+        let _: <Url as Optionable>::Optioned = Url::parse("https://example.com").unwrap();
+    }
+}
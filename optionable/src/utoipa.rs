@@ -0,0 +1,18 @@
+use crate::{impl_optional_self, Optionable};
+use utoipa::openapi::schema::Schema;
+
+// Schemas are dynamic documents without a fixed structure.
+impl_optional_self!(Schema);
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use utoipa::openapi::schema::{ObjectBuilder, Schema, Type};
+
+    #[test]
+    /// Check that `Schema` implements optionable.
+    fn schema() {
+        let _: <Schema as Optionable>::Optioned =
+            ObjectBuilder::new().schema_type(Type::String).into();
+    }
+}
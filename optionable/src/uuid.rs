@@ -0,0 +1,16 @@
+use crate::{impl_optional_self, Optionable};
+use uuid::{NonNilUuid, Uuid};
+
+impl_optional_self!(Uuid, NonNilUuid);
+
+#[cfg(test)]
+mod tests {
+    use crate::Optionable;
+    use uuid::Uuid;
+
+    #[test]
+    /// Check that `Uuid` implements optionable.
+    fn uuid() {
+        let _: <Uuid as Optionable>::Optioned = Uuid::nil();
+    }
+}
@@ -0,0 +1,236 @@
+//! Validation of optioned values (e.g. patches) against the constraints declared on the fields of the full type.
+//!
+//! Only the set (sub-)fields are validated, the errors are reported with their field paths,
+//! see [`crate::OptionableFieldPaths`] for the path format.
+//! ```rust
+//! # use optionable::Optionable;
+//! # use optionable::validate::OptionableValidate;
+//! #[derive(Optionable)]
+//! #[optionable(validate)]
+//! struct Deployment {
+//!     #[optionable(validate(length(min = 1, max = 63)))]
+//!     name: String,
+//!     #[optionable(validate(nested))]
+//!     spec: Spec,
+//! }
+//!
+//! #[derive(Optionable)]
+//! #[optionable(validate)]
+//! struct Spec {
+//!     #[optionable(validate(range(max = 10)))]
+//!     replicas: u32,
+//! }
+//!
+//! let patch = DeploymentOpt {
+//!     name: None,
+//!     spec: Some(SpecOpt { replicas: Some(20) }),
+//! };
+//! let err = Deployment::validate_optioned(&patch).unwrap_err();
+//! assert_eq!(err.errors[0].path, "spec.replicas");
+//! assert_eq!(err.errors[0].message, "must be at most 10");
+//! ```
+use crate::__private::entry_path;
+use crate::Optionable;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+/// Extension trait to validate the set (sub-)fields of optioned values. Derived via `#[optionable(validate)]`
+/// checking the constraints declared via `#[optionable(validate(...))]` on the fields.
+pub trait OptionableValidate: Optionable {
+    /// Validates the set (sub-)fields of the optioned value located at `path`
+    /// and records the violated constraints in `errors`.
+    fn validate_optioned_at(
+        optioned: &Self::Optioned,
+        path: &str,
+        errors: &mut Vec<ValidationError>,
+    );
+
+    /// Validates the set (sub-)fields of the optioned value.
+    ///
+    /// # Errors
+    /// - If any set (sub-)field violates its constraints.
+    fn validate_optioned(optioned: &Self::Optioned) -> Result<(), ValidationErrors> {
+        let mut errors = vec![];
+        Self::validate_optioned_at(optioned, "", &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors { errors })
+        }
+    }
+}
+
+impl<T: OptionableValidate> OptionableValidate for Option<T> {
+    fn validate_optioned_at(
+        optioned: &Option<T::Optioned>,
+        path: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(optioned) = optioned {
+            T::validate_optioned_at(optioned, path, errors);
+        }
+    }
+}
+
+/// Helper macro to generate an impl for `OptionableValidate` for smart pointers validating the pointed to value.
+macro_rules! impl_validate_pointer {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableValidate> OptionableValidate for $t<T> {
+            fn validate_optioned_at(optioned: &$t<T::Optioned>, path: &str, errors: &mut Vec<ValidationError>) {
+                T::validate_optioned_at(optioned, path, errors);
+            }
+        })*
+    };
+}
+
+impl_validate_pointer!(Box, Rc, Arc);
+
+/// Helper macro to generate an impl for `OptionableValidate` for lists validating the elements at their indices.
+macro_rules! impl_validate_list {
+    ($($t:ident),* $(,)?) => {
+        $(impl<T: OptionableValidate> OptionableValidate for $t<T> {
+            fn validate_optioned_at(optioned: &$t<T::Optioned>, path: &str, errors: &mut Vec<ValidationError>) {
+                for (i, optioned) in optioned.iter().enumerate() {
+                    T::validate_optioned_at(optioned, &entry_path(path, i), errors);
+                }
+            }
+        })*
+    };
+}
+
+impl_validate_list!(Vec, VecDeque, LinkedList);
+
+impl<T: OptionableValidate, const N: usize> OptionableValidate for [T; N] {
+    fn validate_optioned_at(
+        optioned: &[T::Optioned; N],
+        path: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for (i, optioned) in optioned.iter().enumerate() {
+            T::validate_optioned_at(optioned, &entry_path(path, i), errors);
+        }
+    }
+}
+
+impl<K: Display, T: OptionableValidate> OptionableValidate for BTreeMap<K, T> {
+    fn validate_optioned_at(
+        optioned: &BTreeMap<K, T::Optioned>,
+        path: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for (key, optioned) in optioned {
+            T::validate_optioned_at(optioned, &entry_path(path, key), errors);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Display, T: OptionableValidate, S> OptionableValidate for HashMap<K, T, S> {
+    fn validate_optioned_at(
+        optioned: &HashMap<K, T::Optioned, S>,
+        path: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for (key, optioned) in optioned {
+            T::validate_optioned_at(optioned, &entry_path(path, key), errors);
+        }
+    }
+}
+
+/// Length of a value for the `length` constraint, e.g. the number of characters of a string
+/// or the number of elements of a collection.
+pub trait Length {
+    /// Returns the length of the value.
+    fn length(&self) -> usize;
+}
+
+impl Length for str {
+    fn length(&self) -> usize {
+        self.chars().count()
+    }
+}
+
+impl Length for String {
+    fn length(&self) -> usize {
+        self.as_str().length()
+    }
+}
+
+impl<T> Length for [T] {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, const N: usize> Length for [T; N] {
+    fn length(&self) -> usize {
+        N
+    }
+}
+
+/// Helper macro to generate an impl for `Length` for collections with a `len` method.
+macro_rules! impl_length {
+    ($($t:ident<$($p:ident),*>),* $(,)?) => {
+        $(impl<$($p),*> Length for $t<$($p),*> {
+            fn length(&self) -> usize {
+                self.len()
+            }
+        })*
+    };
+}
+
+impl_length!(
+    Vec<T>,
+    VecDeque<T>,
+    LinkedList<T>,
+    BinaryHeap<T>,
+    BTreeSet<T>,
+    BTreeMap<K, T>,
+);
+#[cfg(feature = "std")]
+impl_length!(HashSet<T, S>, HashMap<K, T, S>);
+
+/// Error for optioned values with set (sub-)fields violating their constraints.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors {
+    /// The violated constraints.
+    pub errors: Vec<ValidationError>,
+}
+
+impl Display for ValidationErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid fields: ")?;
+        for (i, err) in self.errors.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{err}")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for ValidationErrors {}
+
+/// A constraint violated by a set (sub-)field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The path of the (sub-)field.
+    pub path: String,
+    /// The description of the violated constraint.
+    pub message: String,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} ({})", self.path, self.message)
+    }
+}
@@ -1,6 +1,11 @@
-use optionable::Optionable;
+use optionable::field_mask::FieldMask;
+use optionable::{Optionable, OptionableConvert};
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use utoipa::{PartialSchema, ToSchema};
 
 #[test]
 /// Check that the derive macro works with visibility modifier.
@@ -85,6 +90,57 @@ fn derive_generic() {
     };
 }
 
+#[test]
+/// Check that generic parameters only used in required fields or `PhantomData` need no `Optionable` impl.
+fn derive_generic_inferred_bounds() {
+    #[allow(dead_code)]
+    struct Marker;
+    #[allow(dead_code)]
+    struct NotOptionable;
+
+    #[derive(Optionable)]
+    #[allow(dead_code)]
+    struct DeriveExample<T, R, M> {
+        name: T,
+        #[optionable(required)]
+        id: R,
+        marker: PhantomData<M>,
+    }
+
+    let _ = DeriveExampleOpt::<i32, NotOptionable, Marker> {
+        name: Some(2),
+        id: NotOptionable,
+        marker: None,
+    };
+}
+
+#[test]
+/// Check that the inferred bounds can be replaced via the `bound` helper attribute.
+fn derive_generic_bound() {
+    #[derive(Optionable)]
+    #[optionable(bound = "")]
+    #[allow(dead_code)]
+    struct DeriveExample<M> {
+        name: String,
+        marker: Box<PhantomData<M>>,
+    }
+
+    #[derive(Optionable)]
+    #[allow(dead_code)]
+    enum DeriveEnum<T, M> {
+        Plain(T),
+        Marker(#[optionable(bound = "")] Box<PhantomData<M>>),
+    }
+
+    #[allow(dead_code)]
+    struct Marker;
+    let _ = DeriveExampleOpt::<Marker> {
+        name: Some("a".to_owned()),
+        marker: None,
+    };
+    let _ = DeriveEnumOpt::<i32, Marker>::Plain(Some(2));
+}
+
 type _String = <String as ::optionable::Optionable>::Optioned;
 
 #[test]
@@ -127,6 +183,464 @@ fn derive_nested() {
     };
 }
 
+#[test]
+/// Check the empty constructor and the recursive emptiness check.
+fn derive_empty() {
+    #[derive(Optionable)]
+    #[optionable(derive(Debug, PartialEq))]
+    #[allow(dead_code)]
+    struct DeriveExample {
+        name: String,
+        middle_name: Option<String>,
+        #[optionable(required)]
+        id: u32,
+        address: Address,
+        labels: BTreeMap<String, String>,
+    }
+    #[derive(Optionable)]
+    #[optionable(derive(Debug, PartialEq))]
+    #[allow(dead_code)]
+    struct Address {
+        street_name: String,
+        number: u8,
+    }
+    #[derive(Optionable)]
+    #[allow(dead_code)]
+    struct DeriveTuple(String, #[optionable(required)] u32);
+    #[derive(Optionable)]
+    #[allow(dead_code)]
+    struct DeriveContainers {
+        boxed: Box<Address>,
+        cell: std::cell::RefCell<Address>,
+        pair: [Address; 2],
+        list: Vec<Address>,
+    }
+
+    let empty = DeriveExampleOpt::empty(1);
+    assert_eq!(
+        empty,
+        DeriveExampleOpt {
+            name: None,
+            middle_name: None,
+            id: 1,
+            address: None,
+            labels: None,
+        }
+    );
+    assert!(empty.is_empty());
+    assert!(DeriveExampleOpt::default().is_empty());
+
+    let nested_empty = DeriveExampleOpt {
+        address: Some(AddressOpt::empty()),
+        labels: Some(BTreeMap::new()),
+        ..DeriveExampleOpt::empty(1)
+    };
+    assert!(nested_empty.is_empty());
+    assert!(<DeriveExample as Optionable>::is_empty_optioned(
+        &nested_empty
+    ));
+
+    let nested_set = DeriveExampleOpt {
+        address: Some(AddressOpt {
+            number: Some(2),
+            ..AddressOpt::empty()
+        }),
+        ..DeriveExampleOpt::empty(1)
+    };
+    assert!(!nested_set.is_empty());
+    assert!(!DeriveExampleOpt {
+        middle_name: Some("a".to_owned()),
+        ..DeriveExampleOpt::empty(1)
+    }
+    .is_empty());
+
+    assert!(DeriveTupleOpt::empty(1).is_empty());
+    assert!(!DeriveTupleOpt(Some("a".to_owned()), 1).is_empty());
+
+    let containers_empty = DeriveContainersOpt {
+        boxed: Some(Box::new(AddressOpt::empty())),
+        cell: Some(std::cell::RefCell::new(AddressOpt::empty())),
+        pair: Some([AddressOpt::empty(), AddressOpt::empty()]),
+        list: None,
+    };
+    assert!(containers_empty.is_empty());
+    assert!(!DeriveContainersOpt {
+        pair: Some([
+            AddressOpt::empty(),
+            AddressOpt {
+                number: Some(2),
+                ..AddressOpt::empty()
+            }
+        ]),
+        ..DeriveContainersOpt::empty()
+    }
+    .is_empty());
+    assert!(!DeriveContainersOpt {
+        list: Some(vec![AddressOpt::empty()]),
+        ..DeriveContainersOpt::empty()
+    }
+    .is_empty());
+}
+
+#[test]
+/// Check that nested optioned values can be built with the generated setters.
+fn derive_builder() {
+    #[derive(Optionable)]
+    #[optionable(derive(Debug, PartialEq), builder)]
+    #[allow(dead_code)]
+    struct DeriveExample {
+        name: String,
+        middle_name: Option<String>,
+        #[optionable(required)]
+        id: u32,
+        address: Address,
+        addresses: Vec<Address>,
+    }
+    #[derive(Optionable)]
+    #[optionable(derive(Debug, PartialEq), builder)]
+    #[allow(dead_code)]
+    struct Address {
+        street_name: String,
+        number: u8,
+    }
+
+    assert_eq!(
+        DeriveExampleOpt::default(),
+        DeriveExampleOpt {
+            name: None,
+            middle_name: None,
+            id: 0,
+            address: None,
+            addresses: None,
+        }
+    );
+    let optioned = DeriveExampleOpt::default()
+        .with_name("a".to_owned())
+        .with_middle_name("b".to_owned())
+        .with_id(1)
+        .with_address_fn(|address| address.with_number(2))
+        .with_address_fn(|address| address.with_street_name("B".to_owned()))
+        .with_addresses(vec![AddressOpt::default().with_number(3)]);
+    assert_eq!(
+        optioned,
+        DeriveExampleOpt {
+            name: Some("a".to_owned()),
+            middle_name: Some("b".to_owned()),
+            id: 1,
+            address: Some(AddressOpt {
+                street_name: Some("B".to_owned()),
+                number: Some(2),
+            }),
+            addresses: Some(vec![AddressOpt {
+                street_name: None,
+                number: Some(3),
+            }]),
+        }
+    );
+
+    // nested fields can be set from their full value as well
+    let optioned = DeriveExampleOpt::default()
+        .with_middle_name_value("c".to_owned())
+        .with_address_value(Address {
+            street_name: "C".to_owned(),
+            number: 4,
+        })
+        .with_addresses_value(vec![Address {
+            street_name: "D".to_owned(),
+            number: 5,
+        }]);
+    assert_eq!(
+        optioned,
+        DeriveExampleOpt {
+            name: None,
+            middle_name: Some("c".to_owned()),
+            id: 0,
+            address: Some(AddressOpt {
+                street_name: Some("C".to_owned()),
+                number: Some(4),
+            }),
+            addresses: Some(vec![AddressOpt {
+                street_name: Some("D".to_owned()),
+                number: Some(5),
+            }]),
+        }
+    );
+}
+
+#[test]
+/// Check that the set field paths are listed recursively with the serialized names.
+fn derive_field_paths() {
+    #[derive(Optionable, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    #[allow(dead_code)]
+    struct Deployment {
+        #[optionable(required)]
+        name: String,
+        spec: DeploymentSpec,
+        #[serde(rename = "meta")]
+        metadata: Option<Metadata>,
+    }
+    #[derive(Optionable, Serialize)]
+    #[allow(dead_code)]
+    struct DeploymentSpec {
+        replicas: u32,
+        #[serde(flatten)]
+        template: Metadata,
+        strategy: Strategy,
+        tolerations: Vec<String>,
+    }
+    #[derive(Optionable, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    #[allow(dead_code)]
+    struct Metadata {
+        labels: BTreeMap<String, String>,
+        owner_reference: Option<String>,
+    }
+    #[derive(Optionable, Serialize)]
+    #[serde(tag = "type", rename_all = "PascalCase")]
+    #[allow(dead_code)]
+    enum Strategy {
+        Recreate,
+        RollingUpdate { max_surge: u32 },
+    }
+
+    let optioned = DeploymentOpt {
+        name: "a".to_owned(),
+        spec: Some(DeploymentSpecOpt {
+            replicas: Some(2),
+            template: Some(MetadataOpt {
+                labels: Some(BTreeMap::from([
+                    ("app".to_owned(), "b".to_owned()),
+                    ("tier".to_owned(), "c".to_owned()),
+                ])),
+                owner_reference: None,
+            }),
+            strategy: Some(StrategyOpt::RollingUpdate { max_surge: Some(1) }),
+            tolerations: Some(vec![]),
+        }),
+        metadata: Some(MetadataOpt {
+            labels: None,
+            owner_reference: None,
+        }),
+    };
+    assert_eq!(
+        optioned.set_field_paths(),
+        vec![
+            "spec.replicas",
+            "spec.labels[app]",
+            "spec.labels[tier]",
+            "spec.strategy.type",
+            "spec.strategy.max_surge",
+            "spec.tolerations",
+            "meta",
+        ]
+    );
+    assert!(DeploymentOpt::empty("a".to_owned())
+        .set_field_paths()
+        .is_empty());
+}
+
+#[test]
+#[cfg(feature = "async-graphql")]
+/// Check that the optioned type is a GraphQL input object distinguishing undefined and null optional fields.
+fn derive_input_object() {
+    use async_graphql::{InputType, MaybeUndefined};
+
+    #[derive(Optionable)]
+    #[optionable(derive(Debug, async_graphql::InputObject), suffix = "Input")]
+    struct User {
+        #[optionable(required)]
+        id: u32,
+        name: String,
+        nickname: Option<String>,
+        address: Option<Address>,
+    }
+    #[derive(Optionable)]
+    #[optionable(derive(Debug, async_graphql::InputObject), suffix = "Input")]
+    struct Address {
+        street: String,
+        city: String,
+    }
+
+    assert_eq!(<UserInput as InputType>::type_name(), "UserInput");
+    let input = UserInput::parse(Some(async_graphql::value!({
+        "id": 1,
+        "nickname": null,
+        "address": {"city": "a"},
+    })))
+    .unwrap();
+    assert!(input.name.is_none());
+    assert_eq!(input.nickname, MaybeUndefined::Null);
+    assert_eq!(input.set_field_paths(), vec!["nickname", "address.city"]);
+
+    let mut user = User {
+        id: 1,
+        name: "b".to_owned(),
+        nickname: Some("c".to_owned()),
+        address: Some(Address {
+            street: "d".to_owned(),
+            city: "e".to_owned(),
+        }),
+    };
+    user.merge(input).unwrap();
+    assert_eq!(user.name, "b");
+    assert_eq!(user.nickname, None);
+    assert_eq!(user.address.as_ref().unwrap().street, "d");
+    assert_eq!(user.address.as_ref().unwrap().city, "a");
+
+    // undefined fields are left untouched, null unsets them
+    user.merge(UserInput::empty(1)).unwrap();
+    assert_eq!(user.address.as_ref().unwrap().city, "a");
+    let mut unset = UserInput::empty(1);
+    unset.address = MaybeUndefined::Null;
+    user.merge(unset).unwrap();
+    assert!(user.address.is_none());
+}
+
+#[test]
+/// Check that the (fully qualified) `Option` fields of `prost` messages are not wrapped again.
+fn derive_prost_message() {
+    #[derive(Clone, PartialEq, prost::Message, Optionable)]
+    #[optionable(derive(Debug, PartialEq))]
+    struct User {
+        #[prost(string, tag = "1")]
+        name: String,
+        #[prost(message, optional, tag = "2")]
+        address: ::core::option::Option<Address>,
+        #[prost(string, repeated, tag = "3")]
+        emails: Vec<String>,
+    }
+    #[derive(Clone, PartialEq, prost::Message, Optionable)]
+    #[optionable(derive(Debug, PartialEq))]
+    struct Address {
+        #[prost(string, tag = "1")]
+        street: String,
+    }
+
+    let patch = UserOpt {
+        name: None,
+        address: Some(AddressOpt {
+            street: Some("a".to_owned()),
+        }),
+        emails: None,
+    };
+    let mut user = User {
+        name: "b".to_owned(),
+        address: None,
+        emails: vec![],
+    };
+    assert_eq!(patch.field_mask(), FieldMask::new(["address.street"]));
+    user.merge(patch).unwrap();
+    assert_eq!(user.address.unwrap().street, "a");
+}
+
+#[test]
+/// Check that field masks are validated against the type structure and project full values
+/// onto the selected (sub-)fields.
+fn derive_field_mask() {
+    #[derive(Optionable, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    #[optionable(derive(Debug, PartialEq))]
+    struct Pod {
+        #[optionable(required)]
+        name: String,
+        service_account: String,
+        labels: BTreeMap<String, String>,
+        containers: Vec<String>,
+        #[serde(flatten)]
+        resources: Resources,
+        restart: Restart,
+    }
+    #[derive(Optionable, Serialize)]
+    #[optionable(derive(Debug, PartialEq))]
+    struct Resources {
+        cpu: u32,
+        memory: u32,
+    }
+    #[derive(Optionable, Serialize)]
+    #[serde(tag = "policy")]
+    #[optionable(derive(Debug, PartialEq))]
+    enum Restart {
+        Never,
+        OnFailure { backoff: u32, limit: u32 },
+    }
+
+    let pod = || Pod {
+        name: "a".to_owned(),
+        service_account: "default".to_owned(),
+        labels: BTreeMap::from([
+            ("app".to_owned(), "b".to_owned()),
+            ("tier".to_owned(), "c".to_owned()),
+        ]),
+        containers: vec!["nginx".to_owned()],
+        resources: Resources { cpu: 1, memory: 2 },
+        restart: Restart::OnFailure {
+            backoff: 3,
+            limit: 4,
+        },
+    };
+
+    let mask = FieldMask::new(["labels[app]", "memory", "restart.limit", "containers"]);
+    let projected = mask.project(pod()).unwrap();
+    assert_eq!(
+        projected,
+        PodOpt {
+            name: "a".to_owned(),
+            service_account: None,
+            labels: Some(BTreeMap::from([("app".to_owned(), "b".to_owned())])),
+            containers: Some(vec!["nginx".to_owned()]),
+            resources: Some(ResourcesOpt {
+                cpu: None,
+                memory: Some(2),
+            }),
+            restart: Some(RestartOpt::OnFailure {
+                backoff: None,
+                limit: Some(4),
+            }),
+        }
+    );
+    // the tag of the internally tagged enum is set as well
+    assert_eq!(
+        projected.field_mask(),
+        FieldMask::new([
+            "labels[app]",
+            "containers",
+            "memory",
+            "restart.policy",
+            "restart.limit"
+        ])
+    );
+    assert_eq!(
+        FieldMask::new([""]).project(pod()).unwrap(),
+        pod().into_optioned()
+    );
+
+    FieldMask::new(["name", "restart.policy", "labels[any]", "cpu"])
+        .validate::<Pod>()
+        .unwrap();
+    let err = FieldMask::new([
+        "name.first",
+        "resources",
+        "serviceAccount.name",
+        "containers[0]",
+        "labels..app",
+        "restart.backoff.value",
+    ])
+    .project(pod())
+    .unwrap_err();
+    assert_eq!(
+        err.invalid_paths,
+        vec![
+            "name.first",
+            "resources",
+            "serviceAccount.name",
+            "containers[0]",
+            "labels..app",
+            "restart.backoff.value",
+        ]
+    );
+}
+
 #[test]
 /// Check that the derive macro works for enums
 fn derive_enum() {
@@ -177,3 +691,1821 @@ fn derive_forward_other_derives() {
     let a_json = serde_json::to_string(&a).unwrap();
     assert_eq!(a_json, "{\"name\":\"a\",\"middle_name\":\"b\"}");
 }
+
+#[test]
+/// Check that the optioned type keeps the `serde` attributes naming the fields
+/// and hence is serialized like the original type.
+fn derive_forward_serde_naming() {
+    #[derive(Optionable, Deserialize, Serialize)]
+    #[optionable(derive(Debug, PartialEq, Deserialize, Serialize))]
+    #[serde(rename_all = "camelCase")]
+    struct DeriveExample {
+        max_surge: u32,
+        #[serde(rename = "fullName", alias = "name")]
+        display_name: String,
+        #[serde(flatten)]
+        nested: Nested,
+    }
+
+    #[derive(Optionable, Deserialize, Serialize)]
+    #[optionable(derive(Debug, PartialEq, Deserialize, Serialize))]
+    struct Nested {
+        extra_field: u8,
+    }
+
+    let value = DeriveExample {
+        max_surge: 1,
+        display_name: "a".to_owned(),
+        nested: Nested { extra_field: 2 },
+    };
+    let json = serde_json::json!({"maxSurge": 1, "fullName": "a", "extra_field": 2});
+    assert_eq!(serde_json::to_value(&value).unwrap(), json);
+    let optioned = value.into_optioned();
+    assert_eq!(serde_json::to_value(&optioned).unwrap(), json);
+    assert_eq!(
+        serde_json::from_value::<DeriveExampleOpt>(json).unwrap(),
+        optioned
+    );
+    assert_eq!(
+        serde_json::from_value::<DeriveExampleOpt>(serde_json::json!({"name": "b"})).unwrap(),
+        DeriveExampleOpt {
+            max_surge: None,
+            display_name: Some("b".to_owned()),
+            nested: Some(NestedOpt { extra_field: None }),
+        }
+    );
+}
+
+#[test]
+/// Check that the optioned type of a tagged enum keeps the enum representation and the variant names.
+fn derive_forward_serde_enum_representation() {
+    #[derive(Optionable, Deserialize, Serialize)]
+    #[optionable(derive(Debug, PartialEq, Deserialize, Serialize))]
+    #[serde(tag = "type", rename_all = "camelCase")]
+    enum Strategy {
+        RollingUpdate {
+            max_surge: u32,
+        },
+        #[serde(rename = "recreateAll")]
+        Recreate,
+    }
+
+    for (value, json) in [
+        (
+            Strategy::RollingUpdate { max_surge: 1 },
+            serde_json::json!({"type": "rollingUpdate", "max_surge": 1}),
+        ),
+        (
+            Strategy::Recreate,
+            serde_json::json!({"type": "recreateAll"}),
+        ),
+    ] {
+        assert_eq!(serde_json::to_value(&value).unwrap(), json);
+        let optioned = value.into_optioned();
+        assert_eq!(serde_json::to_value(&optioned).unwrap(), json);
+        assert_eq!(
+            serde_json::from_value::<StrategyOpt>(json).unwrap(),
+            optioned
+        );
+    }
+}
+
+#[test]
+/// Check the conversion between a nested struct and its optioned type.
+fn convert_nested() {
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    struct DeriveExample {
+        name: String,
+        middle_name: Option<String>,
+        address: Address,
+        #[optionable(required)]
+        id: u32,
+    }
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    struct Address {
+        street_name: String,
+        number: u8,
+    }
+
+    let full = DeriveExample {
+        name: "a".to_owned(),
+        middle_name: None,
+        address: Address {
+            street_name: "B".to_owned(),
+            number: 2,
+        },
+        id: 1,
+    };
+    let optioned = full.clone().into_optioned();
+    assert_eq!(DeriveExample::try_from_optioned(optioned).unwrap(), full);
+
+    let err = DeriveExample::try_from_optioned(DeriveExampleOpt {
+        name: None,
+        middle_name: None,
+        address: Some(AddressOpt {
+            street_name: None,
+            number: Some(2),
+        }),
+        id: 1,
+    })
+    .unwrap_err();
+    assert_eq!(err.missing_fields, vec!["name", "address.street_name"]);
+    assert_eq!(err.to_string(), "missing fields: name, address.street_name");
+}
+
+#[test]
+/// Check that merging only touches the set fields and merges maps per key.
+fn convert_merge() {
+    #[derive(Optionable, Debug, PartialEq)]
+    struct DeriveExample {
+        name: String,
+        tags: Vec<String>,
+        labels: BTreeMap<String, Address>,
+    }
+    #[derive(Optionable, Debug, PartialEq)]
+    struct Address {
+        street_name: String,
+        number: u8,
+    }
+
+    let mut value = DeriveExample {
+        name: "a".to_owned(),
+        tags: vec!["t1".to_owned(), "t2".to_owned()],
+        labels: BTreeMap::from([(
+            "home".to_owned(),
+            Address {
+                street_name: "B".to_owned(),
+                number: 2,
+            },
+        )]),
+    };
+    value
+        .merge(DeriveExampleOpt {
+            name: None,
+            tags: Some(vec!["t3".to_owned()]),
+            labels: Some(BTreeMap::from([(
+                "home".to_owned(),
+                AddressOpt {
+                    street_name: None,
+                    number: Some(3),
+                },
+            )])),
+        })
+        .unwrap();
+    assert_eq!(
+        value,
+        DeriveExample {
+            name: "a".to_owned(),
+            tags: vec!["t3".to_owned()],
+            labels: BTreeMap::from([(
+                "home".to_owned(),
+                Address {
+                    street_name: "B".to_owned(),
+                    number: 3,
+                },
+            )]),
+        }
+    );
+
+    // new map entries need all required fields
+    let err = value
+        .merge(DeriveExampleOpt {
+            name: Some("c".to_owned()),
+            tags: None,
+            labels: Some(BTreeMap::from([(
+                "work".to_owned(),
+                AddressOpt {
+                    street_name: None,
+                    number: Some(3),
+                },
+            )])),
+        })
+        .unwrap_err();
+    assert_eq!(err.missing_fields, vec!["labels.street_name"]);
+    assert_eq!(value.name, "c");
+}
+
+#[test]
+/// Check that arrays merge all elements and gather the missing fields of every failing element.
+fn convert_merge_array() {
+    #[derive(Optionable, Debug, PartialEq)]
+    struct Address {
+        street_name: String,
+        number: u8,
+    }
+    #[derive(Optionable, Debug, PartialEq)]
+    struct Item {
+        address: Option<Address>,
+    }
+
+    let item = |number| Item {
+        address: (number > 0).then(|| Address {
+            street_name: "A".to_owned(),
+            number,
+        }),
+    };
+    let patch = |number| ItemOpt {
+        address: Some(AddressOpt {
+            street_name: None,
+            number: Some(number),
+        }),
+    };
+    let mut value = [item(0), item(1), item(0)];
+    let err = value.merge([patch(4), patch(5), patch(6)]).unwrap_err();
+    assert_eq!(
+        err.missing_fields,
+        vec!["0.address.street_name", "2.address.street_name"]
+    );
+    assert_eq!(value[1], item(5));
+
+    let err = <[Address; 2]>::try_from_optioned([AddressOpt::default(), AddressOpt::default()])
+        .unwrap_err();
+    assert_eq!(
+        err.missing_fields,
+        vec!["0.street_name", "0.number", "1.street_name", "1.number"]
+    );
+}
+
+#[test]
+/// Check that enums are merged for matching variants and replaced otherwise.
+fn convert_merge_enum() {
+    #[derive(Optionable, Debug, PartialEq)]
+    enum DeriveExample {
+        Unit,
+        Address { street: String, number: u32 },
+    }
+
+    let mut value = DeriveExample::Address {
+        street: "a".to_owned(),
+        number: 1,
+    };
+    value
+        .merge(DeriveExampleOpt::Address {
+            street: None,
+            number: Some(2),
+        })
+        .unwrap();
+    assert_eq!(
+        value,
+        DeriveExample::Address {
+            street: "a".to_owned(),
+            number: 2,
+        }
+    );
+
+    value.merge(DeriveExampleOpt::Unit).unwrap();
+    assert_eq!(value, DeriveExample::Unit);
+
+    let err = value
+        .merge(DeriveExampleOpt::Address {
+            street: None,
+            number: Some(2),
+        })
+        .unwrap_err();
+    assert_eq!(err.missing_fields, vec!["street"]);
+    assert_eq!(value, DeriveExample::Unit);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+/// Check that a dynamic JSON field of a typed struct is merged as JSON merge patch.
+fn convert_merge_serde_json() {
+    #[derive(Optionable, Debug, PartialEq)]
+    #[optionable(derive(Deserialize, Serialize))]
+    struct DeriveExample {
+        name: String,
+        extra: serde_json::Value,
+    }
+
+    let mut value = DeriveExample {
+        name: "a".to_owned(),
+        extra: serde_json::json!({"b": {"c": 1, "d": 2}, "e": [1, 2]}),
+    };
+    let patch: DeriveExampleOpt =
+        serde_json::from_str(r#"{"extra": {"b": {"c": null, "f": 3}, "e": [3]}}"#).unwrap();
+    value.merge(patch).unwrap();
+    assert_eq!(
+        value,
+        DeriveExample {
+            name: "a".to_owned(),
+            extra: serde_json::json!({"b": {"d": 2, "f": 3}, "e": [3]}),
+        }
+    );
+}
+
+#[test]
+/// Check that the JSON schema of the optioned type has only optional, non-nullable properties
+/// apart from required fields and originally optional fields.
+fn derive_json_schema() {
+    #[derive(Optionable)]
+    #[optionable(derive(JsonSchema))]
+    #[allow(dead_code)]
+    struct DeriveExample {
+        name: String,
+        middle_name: Option<String>,
+        #[optionable(required)]
+        id: u32,
+        #[optionable(list_type = "map", list_map_key = "name")]
+        ports: Vec<Port>,
+    }
+
+    #[derive(Optionable)]
+    #[optionable(derive(JsonSchema, Serialize))]
+    #[allow(dead_code)]
+    struct Port {
+        name: String,
+        number: u16,
+    }
+
+    let schema = schemars::schema_for!(DeriveExampleOpt);
+    assert_eq!(
+        schema.as_value(),
+        &serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "DeriveExampleOpt",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "format": "uint32", "minimum": 0 },
+                "middle_name": { "type": ["string", "null"] },
+                "name": { "type": "string" },
+                "ports": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/PortOpt" },
+                    "x-kubernetes-list-map-keys": ["name"],
+                    "x-kubernetes-list-type": "map"
+                }
+            },
+            "required": ["id"],
+            "$defs": {
+                "PortOpt": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "number": { "type": "integer", "format": "uint16", "maximum": 65535, "minimum": 0 }
+                    }
+                }
+            }
+        })
+    );
+}
+
+#[test]
+/// Check that the utoipa schema of the optioned type has only optional properties
+/// apart from required fields and that only originally optional fields are nullable.
+fn derive_to_schema() {
+    #[derive(Optionable)]
+    #[optionable(derive(ToSchema))]
+    #[allow(dead_code)]
+    struct DeriveExample {
+        name: String,
+        middle_name: Option<String>,
+        #[optionable(required)]
+        id: u32,
+        ports: Vec<Port>,
+        protocol: Protocol,
+    }
+
+    #[derive(Optionable)]
+    #[optionable(derive(utoipa::ToSchema))]
+    #[allow(dead_code)]
+    struct Port {
+        name: String,
+        number: u16,
+    }
+
+    #[derive(Optionable)]
+    #[optionable(derive(ToSchema))]
+    #[allow(dead_code)]
+    enum Protocol {
+        Tcp,
+        Custom { name: String },
+    }
+
+    assert_eq!(DeriveExampleOpt::name(), "DeriveExampleOpt");
+    let schema = serde_json::to_value(DeriveExampleOpt::schema()).unwrap();
+    assert_eq!(
+        schema,
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "format": "int32", "minimum": 0 },
+                "middle_name": { "oneOf": [{ "type": "null" }, { "type": "string" }] },
+                "name": { "type": "string" },
+                "ports": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "number": { "type": "integer", "format": "int32", "minimum": 0 }
+                        }
+                    }
+                },
+                "protocol": {
+                    "oneOf": [
+                        { "type": "string", "enum": ["Tcp"] },
+                        {
+                            "type": "object",
+                            "properties": {
+                                "Custom": { "type": "object", "properties": { "name": { "type": "string" } } }
+                            },
+                            "required": ["Custom"]
+                        }
+                    ]
+                }
+            },
+            "required": ["id"]
+        })
+    );
+}
+
+#[test]
+/// Check that the diesel changeset only updates the columns of set fields, mapped via the `column` attribute.
+fn derive_diesel_changeset() {
+    use diesel::prelude::*;
+
+    diesel::table! {
+        users (id) {
+            id -> Integer,
+            user_name -> Text,
+            nickname -> Nullable<Text>,
+            age -> Integer,
+        }
+    }
+
+    #[derive(Optionable)]
+    #[optionable(derive(Debug, AsChangeset), table = "users")]
+    #[allow(dead_code)]
+    struct User {
+        #[optionable(required)]
+        id: i32,
+        #[optionable(column = "user_name")]
+        name: String,
+        nickname: Option<String>,
+        age: i32,
+    }
+
+    let conn = &mut SqliteConnection::establish(":memory:").unwrap();
+    diesel::sql_query(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, user_name TEXT NOT NULL, nickname TEXT, age INTEGER NOT NULL)",
+    )
+    .execute(conn)
+    .unwrap();
+    diesel::sql_query("INSERT INTO users VALUES (1, 'a', 'b', 30), (2, 'c', NULL, 40)")
+        .execute(conn)
+        .unwrap();
+
+    let query = diesel::update(users::table.find(1)).set(UserOpt {
+        name: Some("d".to_owned()),
+        ..UserOpt::empty(1)
+    });
+    assert_eq!(
+        diesel::debug_query::<diesel::sqlite::Sqlite, _>(&query).to_string(),
+        r#"UPDATE `users` SET `user_name` = ? WHERE (`users`.`id` = ?) -- binds: ["d", 1]"#
+    );
+
+    let patch = UserOpt {
+        id: 1,
+        name: Some("d".to_owned()),
+        nickname: None,
+        age: Some(31),
+    };
+    diesel::update(users::table.find(patch.id))
+        .set(patch)
+        .execute(conn)
+        .unwrap();
+    let rows = users::table
+        .order(users::id)
+        .load::<(i32, String, Option<String>, i32)>(conn)
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            (1, "d".to_owned(), Some("b".to_owned()), 31),
+            (2, "c".to_owned(), None, 40)
+        ]
+    );
+
+    let err = diesel::update(users::table.find(2))
+        .set(UserOpt::empty(2))
+        .execute(conn)
+        .unwrap_err();
+    assert!(
+        matches!(err, diesel::result::Error::QueryBuilderError(err) if err.is::<diesel::result::EmptyChangeset>())
+    );
+}
+
+#[cfg(feature = "sqlx")]
+#[tokio::test]
+/// Check that the sqlx update query only sets the columns of set fields and filters by the required fields.
+async fn derive_sqlx_update_query() {
+    #[derive(Optionable)]
+    #[optionable(sqlx, table = "users")]
+    #[allow(dead_code)]
+    struct User {
+        #[optionable(required)]
+        id: i32,
+        #[optionable(column = "user_name")]
+        name: String,
+        nickname: Option<String>,
+        age: i32,
+    }
+
+    let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+    sqlx::query(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, user_name TEXT NOT NULL, nickname TEXT, age INTEGER NOT NULL)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO users VALUES (1, 'a', 'b', 30), (2, 'c', NULL, 40)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let patch = UserOpt {
+        id: 1,
+        name: Some("d".to_owned()),
+        nickname: None,
+        age: Some(31),
+    };
+    let mut query = patch.update_query::<sqlx::Sqlite>().unwrap();
+    assert_eq!(
+        query.sql(),
+        "UPDATE users SET user_name = ?, age = ? WHERE id = ?"
+    );
+    query.build().execute(&pool).await.unwrap();
+    let rows: Vec<(i32, String, Option<String>, i32)> =
+        sqlx::query_as("SELECT id, user_name, nickname, age FROM users ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            (1, "d".to_owned(), Some("b".to_owned()), 31),
+            (2, "c".to_owned(), None, 40)
+        ]
+    );
+    assert!(UserOpt::empty(2).update_query::<sqlx::Sqlite>().is_none());
+}
+
+#[cfg(all(feature = "serde_json", feature = "serde_yaml", feature = "toml"))]
+#[test]
+/// Check that layers from files and in-memory values are merged by precedence
+/// and that the supplying layers as well as missing fields are reported.
+fn layered_config() {
+    use optionable::layered::Layered;
+
+    #[derive(Optionable, Debug, PartialEq)]
+    #[optionable(derive(Deserialize, Debug))]
+    struct Config {
+        name: String,
+        replicas: u32,
+        server: Server,
+        tags: Vec<String>,
+    }
+
+    #[derive(Optionable, Debug, PartialEq)]
+    #[optionable(derive(Deserialize, Debug))]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    let dir = std::env::temp_dir().join(format!("optionable-layered-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (toml, yaml, json) = (
+        dir.join("defaults.toml"),
+        dir.join("override.yaml"),
+        dir.join("local.json"),
+    );
+    std::fs::write(
+        &toml,
+        "name = \"app\"\nreplicas = 1\ntags = [\"a\"]\n[server]\nhost = \"localhost\"\nport = 80\n",
+    )
+    .unwrap();
+    std::fs::write(&yaml, "server:\n  port: 8080\ntags: [b, c]\n").unwrap();
+    std::fs::write(&json, r#"{"replicas": 3}"#).unwrap();
+
+    let config = Layered::<Config>::new()
+        .file(&toml)
+        .unwrap()
+        .file(&yaml)
+        .unwrap()
+        .file(&json)
+        .unwrap()
+        .layer(
+            "cli",
+            ConfigOpt {
+                server: Some(ServerOpt {
+                    host: Some("example.com".to_owned()),
+                    port: None,
+                }),
+                ..ConfigOpt::default()
+            },
+        )
+        .finalize()
+        .unwrap();
+    assert_eq!(
+        config.value,
+        Config {
+            name: "app".to_owned(),
+            replicas: 3,
+            server: Server {
+                host: "example.com".to_owned(),
+                port: 8080,
+            },
+            tags: vec!["b".to_owned(), "c".to_owned()],
+        }
+    );
+    let toml_layer = toml.display().to_string();
+    let yaml_layer = yaml.display().to_string();
+    let json_layer = json.display().to_string();
+    assert_eq!(config.source("name"), Some(toml_layer.as_str()));
+    assert_eq!(config.source("replicas"), Some(json_layer.as_str()));
+    assert_eq!(config.source("server.host"), Some("cli"));
+    assert_eq!(config.source("server.port"), Some(yaml_layer.as_str()));
+    assert_eq!(config.source("tags"), Some(yaml_layer.as_str()));
+
+    let err = Layered::<Config>::new()
+        .file(&yaml)
+        .unwrap()
+        .finalize()
+        .unwrap_err();
+    assert_eq!(err.missing_fields, vec!["name", "replicas", "server.host"]);
+
+    let err = Layered::<Config>::new()
+        .file(dir.join("missing.json"))
+        .err()
+        .unwrap();
+    assert!(err.layer.ends_with("missing.json"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "env")]
+#[test]
+/// Check that the optioned values are read from the environment variables of the (nested) fields,
+/// leaving unset variables unset and reporting invalid variables individually.
+fn derive_env() {
+    use optionable::env::{EnvVarError, OptionableEnv};
+    use std::ffi::OsString;
+
+    #[derive(Optionable, Debug)]
+    #[optionable(env, derive(Debug, PartialEq))]
+    struct App {
+        name: String,
+        nickname: Option<String>,
+        spec: Spec,
+    }
+
+    #[derive(Optionable, Debug)]
+    #[optionable(env, derive(Debug, PartialEq))]
+    struct Spec {
+        #[optionable(required)]
+        kind: String,
+        replicas: u32,
+        address: Box<std::net::IpAddr>,
+    }
+
+    let source = |vars: &'static [(&'static str, &'static str)]| {
+        move |var: &str| {
+            vars.iter()
+                .find(|(name, _)| *name == var)
+                .map(|(_, value)| OsString::from(value))
+        }
+    };
+
+    let app = App::from_env_with(
+        "APP",
+        source(&[
+            ("APP_NICKNAME", "b"),
+            ("APP_SPEC__KIND", "deployment"),
+            ("APP_SPEC__REPLICAS", "3"),
+            ("APP_REPLICAS", "4"),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(
+        app,
+        AppOpt {
+            name: None,
+            nickname: Some("b".to_owned()),
+            spec: Some(SpecOpt {
+                kind: "deployment".to_owned(),
+                replicas: Some(3),
+                address: None,
+            }),
+        }
+    );
+    assert_eq!(
+        App::from_env_with("APP", source(&[])).unwrap(),
+        AppOpt::default()
+    );
+
+    let err = App::from_env_with(
+        "APP",
+        source(&[
+            ("APP_SPEC__REPLICAS", "three"),
+            ("APP_SPEC__ADDRESS", "localhost"),
+        ]),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.errors,
+        vec![
+            EnvVarError {
+                var: "APP_SPEC__REPLICAS".to_owned(),
+                message: "invalid digit found in string".to_owned(),
+            },
+            EnvVarError {
+                var: "APP_SPEC__ADDRESS".to_owned(),
+                message: "invalid IP address syntax".to_owned(),
+            },
+            EnvVarError {
+                var: "APP_SPEC__KIND".to_owned(),
+                message: "required field is not set".to_owned(),
+            },
+        ]
+    );
+}
+
+#[cfg(feature = "clap")]
+#[test]
+/// Check that the flags of the optioned struct are optional, nested structs are flattened with prefixed flags
+/// and a parsed command line can be merged on top of another optioned value.
+fn derive_clap_args() {
+    use clap::{CommandFactory, Parser};
+
+    #[derive(Optionable, Debug)]
+    #[optionable(derive(Args, Debug, PartialEq))]
+    struct App {
+        /// The name of the app.
+        name: String,
+        nickname: Option<String>,
+        verbose: bool,
+        spec: Spec,
+    }
+
+    #[derive(Optionable, Debug)]
+    #[optionable(derive(Args, Debug, PartialEq))]
+    struct Spec {
+        #[optionable(required)]
+        kind: String,
+        max_replicas: u32,
+    }
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        app: AppOpt,
+    }
+
+    let cli = Cli::try_parse_from([
+        "app",
+        "--nickname",
+        "b",
+        "--verbose",
+        "--spec-kind",
+        "deployment",
+        "--spec-max-replicas",
+        "3",
+    ])
+    .unwrap();
+    assert_eq!(
+        cli.app,
+        AppOpt {
+            name: None,
+            nickname: Some("b".to_owned()),
+            verbose: Some(true),
+            spec: Some(SpecOpt {
+                kind: "deployment".to_owned(),
+                max_replicas: Some(3),
+            }),
+        }
+    );
+
+    let mut file = AppOpt {
+        name: Some("a".to_owned()),
+        nickname: None,
+        verbose: Some(false),
+        spec: None,
+    };
+    file.merge(cli.app).unwrap();
+    assert_eq!(file.name, Some("a".to_owned()));
+    assert_eq!(file.verbose, Some(true));
+    assert_eq!(file.spec.unwrap().max_replicas, Some(3));
+
+    let err = Cli::try_parse_from(["app", "--spec-max-replicas", "three"]).err();
+    assert!(err.is_some());
+    let err = Cli::try_parse_from(["app", "--max-replicas", "3"]).err();
+    assert!(err.is_some());
+    let help = Cli::command().render_help().to_string();
+    assert!(help.contains("--spec-max-replicas <SPEC_MAX_REPLICAS>"));
+    assert!(help.contains("The name of the app."));
+}
+
+#[test]
+/// Check that only the set (sub-)fields are validated against the constraints of the fields
+/// and that the errors are reported with the (renamed) field paths.
+fn derive_validate() {
+    use optionable::validate::{OptionableValidate, ValidationError};
+
+    // custom constraints take the value by reference
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn even(value: &u32) -> Result<(), String> {
+        if value.is_multiple_of(2) {
+            Ok(())
+        } else {
+            Err("must be even".to_owned())
+        }
+    }
+
+    #[derive(Optionable, Serialize)]
+    #[optionable(validate)]
+    #[serde(rename_all = "camelCase")]
+    #[allow(dead_code)]
+    struct Deployment {
+        #[optionable(required, validate(length(min = 1, max = 5)))]
+        name: String,
+        #[optionable(validate(length(max = 2)))]
+        labels: Option<Vec<String>>,
+        #[optionable(validate(nested))]
+        pod_specs: Vec<PodSpec>,
+        #[optionable(validate(nested))]
+        strategy: Option<Strategy>,
+    }
+
+    #[derive(Optionable, Serialize)]
+    #[optionable(validate)]
+    #[allow(dead_code)]
+    struct PodSpec {
+        #[optionable(validate(range(min = 1, max = 10), custom = even))]
+        replicas: u32,
+        image: String,
+    }
+
+    #[derive(Optionable, Serialize)]
+    #[optionable(validate)]
+    #[allow(dead_code)]
+    struct Strategy {
+        #[serde(rename = "surge")]
+        #[optionable(validate(range(max = 0.5)))]
+        max_surge: f64,
+    }
+
+    let valid = DeploymentOpt {
+        name: "a".to_owned(),
+        labels: None,
+        pod_specs: Some(vec![PodSpecOpt {
+            replicas: None,
+            image: Some(String::new()),
+        }]),
+        strategy: None,
+    };
+    assert_eq!(Deployment::validate_optioned(&valid), Ok(()));
+
+    let invalid = DeploymentOpt {
+        name: "abcdef".to_owned(),
+        labels: Some(vec!["a".to_owned(), "b".to_owned(), "c".to_owned()]),
+        pod_specs: Some(vec![
+            PodSpecOpt {
+                replicas: Some(4),
+                image: None,
+            },
+            PodSpecOpt {
+                replicas: Some(11),
+                image: None,
+            },
+        ]),
+        strategy: Some(StrategyOpt {
+            max_surge: Some(0.75),
+        }),
+    };
+    let err = Deployment::validate_optioned(&invalid).unwrap_err();
+    let error = |path: &str, message: &str| ValidationError {
+        path: path.to_owned(),
+        message: message.to_owned(),
+    };
+    assert_eq!(
+        err.errors,
+        vec![
+            error("name", "length must be at most 5"),
+            error("labels", "length must be at most 2"),
+            error("podSpecs[1].replicas", "must be at most 10"),
+            error("podSpecs[1].replicas", "must be even"),
+            error("strategy.surge", "must be at most 0.5"),
+        ]
+    );
+    assert_eq!(
+        err.to_string(),
+        "invalid fields: name (length must be at most 5), labels (length must be at most 2), \
+         podSpecs[1].replicas (must be at most 10), podSpecs[1].replicas (must be even), \
+         strategy.surge (must be at most 0.5)"
+    );
+}
+
+#[cfg(feature = "regex")]
+#[test]
+/// Check that the `regex` constraint is only checked for set fields.
+fn derive_validate_regex() {
+    use optionable::validate::OptionableValidate;
+
+    #[derive(Optionable)]
+    #[optionable(validate)]
+    #[allow(dead_code)]
+    struct Image {
+        #[optionable(validate(regex = "^[a-z]+:[0-9.]+$"))]
+        reference: String,
+    }
+
+    assert!(Image::validate_optioned(&ImageOpt { reference: None }).is_ok());
+    assert!(Image::validate_optioned(&ImageOpt {
+        reference: Some("nginx:1.29".to_owned())
+    })
+    .is_ok());
+    let err = Image::validate_optioned(&ImageOpt {
+        reference: Some("nginx".to_owned()),
+    })
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid fields: reference (must match ^[a-z]+:[0-9.]+$)"
+    );
+}
+
+#[test]
+/// Check that the `validator` field attributes are forwarded to the optioned struct
+/// only checking the set fields.
+fn derive_validator() {
+    use validator::Validate;
+
+    #[derive(Optionable, Validate)]
+    #[optionable(derive(Validate))]
+    #[allow(dead_code)]
+    struct App {
+        #[validate(length(min = 1))]
+        name: String,
+        #[validate(range(max = 10))]
+        replicas: Option<u32>,
+    }
+
+    assert!(AppOpt {
+        name: None,
+        replicas: None
+    }
+    .validate()
+    .is_ok());
+    let err = AppOpt {
+        name: Some(String::new()),
+        replicas: Some(11),
+    }
+    .validate()
+    .unwrap_err();
+    assert_eq!(err.field_errors().len(), 2);
+}
+
+#[cfg(feature = "proptest")]
+#[test]
+/// Check that completely set optioned values convert into the full type, that patches merge into their value
+/// and that the density controls how many fields are set.
+fn derive_proptest() {
+    use optionable::proptest::OptionableStrategy;
+    use proptest::prelude::*;
+
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    #[optionable(proptest, derive(Debug, PartialEq))]
+    struct Deployment {
+        #[optionable(required)]
+        id: u32,
+        name: String,
+        replicas: Option<u16>,
+        labels: BTreeMap<String, String>,
+        containers: Vec<Container>,
+        strategy: Option<Strategy>,
+    }
+
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    #[optionable(proptest, derive(Debug, PartialEq))]
+    struct Container {
+        image: String,
+        ports: Box<[u16; 2]>,
+    }
+
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    #[optionable(proptest, derive(Debug, PartialEq))]
+    enum Strategy {
+        Recreate,
+        RollingUpdate {
+            max_surge: u32,
+            max_unavailable: u32,
+        },
+    }
+
+    let deployment = Deployment {
+        id: 1,
+        name: "a".to_owned(),
+        replicas: None,
+        labels: BTreeMap::from([("app".to_owned(), "a".to_owned())]),
+        containers: vec![],
+        strategy: Some(Strategy::RollingUpdate {
+            max_surge: 1,
+            max_unavailable: 0,
+        }),
+    };
+
+    proptest!(|(optioned in Deployment::optioned_strategy(1.0))| {
+        prop_assert!(Deployment::try_from_optioned(optioned).is_ok());
+    });
+    proptest!(|(optioned in Deployment::optioned_strategy(0.0))| {
+        let id = optioned.id;
+        prop_assert_eq!(optioned, DeploymentOpt { id, ..Default::default() });
+    });
+    proptest!(|(patch in Deployment::patch_strategy(&deployment, 0.5))| {
+        prop_assert_eq!(patch.id, 1);
+        let mut patched = deployment.clone();
+        prop_assert!(patched.merge(patch).is_ok());
+    });
+    proptest!(|(optioned in any::<DeploymentOpt>())| {
+        let mut patched = deployment.clone();
+        let _ = patched.merge(optioned);
+    });
+}
+
+#[test]
+/// Check that `arbitrary::Arbitrary` can be forwarded to generate optioned values.
+fn derive_arbitrary() {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    #[derive(Optionable)]
+    #[optionable(derive(Arbitrary, Debug))]
+    #[allow(dead_code)]
+    struct Deployment {
+        name: String,
+        replicas: Option<u16>,
+        containers: Vec<Container>,
+    }
+
+    #[derive(Optionable)]
+    #[optionable(derive(Arbitrary, Debug))]
+    #[allow(dead_code)]
+    struct Container {
+        image: String,
+    }
+
+    let bytes = (0..=255).collect::<Vec<u8>>();
+    let optioned = DeploymentOpt::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+    assert!(
+        optioned.name.is_some() || optioned.replicas.is_some() || optioned.containers.is_none()
+    );
+    assert!(DeploymentOpt::arbitrary(&mut Unstructured::new(&[])).is_ok());
+}
+
+/// Returns pseudo-random bytes for `arbitrary::Unstructured` derived from `seed`.
+#[cfg(feature = "arbitrary")]
+fn unstructured_bytes(seed: u64) -> Vec<u8> {
+    let mut state = seed;
+    (0..512)
+        .flat_map(|_| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            state.to_le_bytes()
+        })
+        .collect()
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+/// Check that completely set optioned values convert into the full type, that patches merge into their value
+/// and that the density controls how many fields are set.
+fn derive_optionable_arbitrary() {
+    use arbitrary::{Arbitrary, Unstructured};
+    use optionable::arbitrary::OptionableArbitrary;
+
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    #[optionable(arbitrary, derive(Debug, PartialEq))]
+    struct Deployment {
+        #[optionable(required)]
+        id: u32,
+        name: String,
+        replicas: Option<u16>,
+        labels: BTreeMap<String, String>,
+        containers: Vec<Container>,
+        strategy: Option<Strategy>,
+    }
+
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    #[optionable(arbitrary, derive(Debug, PartialEq))]
+    struct Container(String, Box<[u16; 2]>);
+
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    #[optionable(arbitrary, derive(Debug, PartialEq))]
+    enum Strategy {
+        Recreate,
+        RollingUpdate {
+            max_surge: u32,
+            #[optionable(required)]
+            max_unavailable: u32,
+        },
+    }
+
+    let deployment = Deployment {
+        id: 1,
+        name: "a".to_owned(),
+        replicas: None,
+        labels: BTreeMap::from([("app".to_owned(), "a".to_owned())]),
+        containers: vec![Container("b".to_owned(), Box::new([80, 443]))],
+        strategy: Some(Strategy::RollingUpdate {
+            max_surge: 1,
+            max_unavailable: 0,
+        }),
+    };
+
+    for seed in 0..64 {
+        let bytes = unstructured_bytes(seed);
+        let u = &mut Unstructured::new(&bytes);
+        let optioned = Deployment::arbitrary_optioned(u, 1.0).unwrap();
+        assert!(Deployment::try_from_optioned(optioned).is_ok());
+
+        let optioned = Deployment::arbitrary_optioned(u, 0.0).unwrap();
+        let id = optioned.id;
+        assert_eq!(
+            optioned,
+            DeploymentOpt {
+                id,
+                ..Default::default()
+            }
+        );
+
+        let patch = Deployment::arbitrary_patch(&deployment, u, 0.5).unwrap();
+        assert_eq!(patch.id, 1);
+        let mut patched = deployment.clone();
+        assert!(patched.merge(patch).is_ok());
+
+        let optioned = DeploymentOpt::arbitrary(u).unwrap();
+        let mut patched = deployment.clone();
+        let _ = patched.merge(optioned);
+    }
+    // exhausted data leaves all fields unset
+    let optioned = Deployment::arbitrary_optioned(&mut Unstructured::new(&[]), 0.5).unwrap();
+    assert_eq!(optioned, DeploymentOpt::default());
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+/// Check that the patches of the std types merge into their value without error.
+fn arbitrary_std_types() {
+    use arbitrary::Unstructured;
+    use optionable::arbitrary::OptionableArbitrary;
+    use std::cell::{Cell, OnceCell, RefCell};
+    use std::cmp::{Ordering, Reverse};
+    use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+    use std::num::{NonZeroU8, Wrapping};
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    #[derive(Optionable, Debug)]
+    #[optionable(arbitrary)]
+    struct Types {
+        map: HashMap<u8, Inner>,
+        set: HashSet<u8>,
+        ordered: BTreeSet<u8>,
+        queue: VecDeque<u8>,
+        array: [Inner; 2],
+        rc: Rc<Inner>,
+        arc: Arc<Inner>,
+        cell: Cell<u8>,
+        ref_cell: RefCell<Inner>,
+        once_cell: OnceCell<Inner>,
+        mutex: Mutex<Inner>,
+        reverse: Reverse<u8>,
+        wrapping: Wrapping<u8>,
+        non_zero: NonZeroU8,
+        range: std::ops::Range<u8>,
+        result: Result<Inner, String>,
+        duration: Duration,
+        instant: Instant,
+        ordering: Ordering,
+        marker: PhantomData<u8>,
+    }
+
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    #[optionable(arbitrary, derive(Clone))]
+    struct Inner {
+        a: u8,
+        b: Option<u8>,
+    }
+
+    let inner = || Inner { a: 1, b: None };
+    let value = || Types {
+        map: HashMap::from([(1, inner())]),
+        set: HashSet::from([1]),
+        ordered: BTreeSet::from([1]),
+        queue: VecDeque::from([1]),
+        array: [inner(), inner()],
+        rc: Rc::new(inner()),
+        arc: Arc::new(inner()),
+        cell: Cell::new(1),
+        ref_cell: RefCell::new(inner()),
+        once_cell: OnceCell::from(inner()),
+        mutex: Mutex::new(inner()),
+        reverse: Reverse(1),
+        wrapping: Wrapping(1),
+        non_zero: NonZeroU8::MIN,
+        range: 0..1,
+        result: Ok(inner()),
+        duration: Duration::ZERO,
+        instant: Instant::now(),
+        ordering: Ordering::Equal,
+        marker: PhantomData,
+    };
+    for seed in 0..64 {
+        let bytes = unstructured_bytes(seed);
+        let u = &mut Unstructured::new(&bytes);
+        let mut patched = value();
+        let patch = Types::arbitrary_patch(&patched, u, 0.5).unwrap();
+        assert!(patched.merge(patch).is_ok());
+        let optioned = Types::arbitrary_optioned(u, 1.0).unwrap();
+        assert!(Types::try_from_optioned(optioned).is_ok());
+    }
+}
+
+#[cfg(feature = "laws")]
+#[test]
+/// Check that the laws hold for derived impls and that violations are detected.
+fn laws() {
+    use optionable::laws;
+
+    /// Type whose merge ignores the merged value.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Frozen(u32);
+
+    impl Optionable for Frozen {
+        type Optioned = Option<u32>;
+    }
+
+    impl OptionableConvert for Frozen {
+        fn into_optioned(self) -> Option<u32> {
+            Some(self.0)
+        }
+
+        fn try_from_optioned(value: Option<u32>) -> Result<Self, optionable::Error> {
+            Ok(Self(value.unwrap_or_default()))
+        }
+
+        fn merge(&mut self, _: Option<u32>) -> Result<(), optionable::Error> {
+            Ok(())
+        }
+    }
+
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    #[optionable(derive(Clone, Debug, PartialEq))]
+    struct Deployment {
+        #[optionable(required)]
+        id: u32,
+        name: String,
+        replicas: Option<u16>,
+        labels: BTreeMap<String, String>,
+        strategy: Strategy,
+    }
+
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    #[optionable(derive(Clone, Debug, PartialEq))]
+    enum Strategy {
+        Recreate,
+        RollingUpdate { max_surge: u32 },
+    }
+
+    let current = Deployment {
+        id: 1,
+        name: "a".to_owned(),
+        replicas: None,
+        labels: BTreeMap::from([("app".to_owned(), "a".to_owned())]),
+        strategy: Strategy::Recreate,
+    };
+    let desired = Deployment {
+        replicas: Some(2),
+        strategy: Strategy::RollingUpdate { max_surge: 1 },
+        ..current.clone()
+    };
+    let patch = DeploymentOpt {
+        id: 1,
+        name: None,
+        replicas: Some(3),
+        labels: Some(BTreeMap::from([("tier".to_owned(), "web".to_owned())])),
+        strategy: Some(StrategyOpt::RollingUpdate { max_surge: Some(2) }),
+    };
+    let other = DeploymentOpt {
+        id: 1,
+        name: Some("b".to_owned()),
+        replicas: None,
+        labels: None,
+        strategy: Some(StrategyOpt::RollingUpdate { max_surge: None }),
+    };
+    let empty = DeploymentOpt {
+        id: 1,
+        ..Default::default()
+    };
+    laws::check_round_trip(&current).unwrap();
+    laws::check_merge_empty(&current, &empty).unwrap();
+    let violation = laws::check_merge_empty(&current, &patch).unwrap_err();
+    assert_eq!(violation.law, "merge_empty");
+    laws::check_merge_associativity::<Deployment>(&patch, &other, &empty).unwrap();
+    laws::check_merge_associativity::<Deployment>(&empty, &other, &patch).unwrap();
+    laws::check_merge_composition(&current, &patch, &other).unwrap();
+    laws::check_merge_composition(&current, &other, &patch).unwrap();
+    laws::check_diff(&current, &desired, |_, target| {
+        target.clone().into_optioned()
+    })
+    .unwrap();
+
+    let violation = laws::check_diff(&current, &desired, |_, _| empty.clone()).unwrap_err();
+    assert_eq!(violation.law, "diff");
+    assert!(violation.to_string().starts_with("violated law diff: "));
+
+    laws::check_round_trip(&Frozen(1)).unwrap();
+    let violation = laws::check_diff(&Frozen(1), &Frozen(2), |_, target| {
+        target.clone().into_optioned()
+    });
+    assert_eq!(violation.unwrap_err().law, "diff");
+}
+
+#[cfg(all(feature = "laws", feature = "proptest"))]
+#[test]
+/// Check that the laws hold for generated values and patches.
+fn laws_proptest() {
+    use optionable::laws;
+    use optionable::proptest::OptionableStrategy;
+    use proptest::prelude::*;
+
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    #[optionable(proptest, derive(Clone, Debug, PartialEq))]
+    struct Deployment {
+        name: String,
+        replicas: Option<u16>,
+        labels: BTreeMap<String, String>,
+        containers: Vec<Container>,
+    }
+
+    #[derive(Optionable, Clone, Debug, PartialEq)]
+    #[optionable(proptest, derive(Clone, Debug, PartialEq))]
+    struct Container {
+        image: String,
+        ports: Option<[u16; 2]>,
+    }
+
+    let full = Deployment::optioned_strategy(1.0)
+        .prop_map(|optioned| Deployment::try_from_optioned(optioned).unwrap());
+    let sparse = || Deployment::optioned_strategy(0.3);
+    proptest!(|(current in full, first in sparse(), second in sparse(), third in sparse())| {
+        laws::check_round_trip(&current)?;
+        laws::check_merge_empty(&current, &DeploymentOpt::default())?;
+        laws::check_merge_associativity::<Deployment>(&first, &second, &third)?;
+        laws::check_merge_composition(&current, &first, &second)?;
+    });
+}
+
+#[test]
+/// Check that the three-way merge keeps the changes of either side, unsets the fields removed from `theirs`
+/// and reports the (renamed) paths of the fields changed differently by both sides.
+fn derive_three_way() {
+    use optionable::three_way::OptionableThreeWayMerge;
+
+    #[derive(Optionable, Debug, PartialEq, Serialize)]
+    #[optionable(three_way, builder, derive(Debug, PartialEq))]
+    #[serde(rename_all = "camelCase")]
+    struct Deployment {
+        #[optionable(required)]
+        name: String,
+        replicas: Option<u32>,
+        paused: Option<bool>,
+        labels: BTreeMap<String, String>,
+        rollout_strategy: Strategy,
+    }
+
+    #[derive(Optionable, Debug, PartialEq, Serialize)]
+    #[optionable(three_way, derive(Debug, PartialEq))]
+    enum Strategy {
+        Recreate,
+        RollingUpdate {
+            max_surge: u32,
+            max_unavailable: u32,
+        },
+    }
+
+    let last_applied = DeploymentOpt {
+        name: "a".to_owned(),
+        replicas: Some(2),
+        paused: Some(false),
+        labels: Some(BTreeMap::from([
+            ("app".to_owned(), "web".to_owned()),
+            ("tier".to_owned(), "frontend".to_owned()),
+        ])),
+        rollout_strategy: Some(StrategyOpt::RollingUpdate {
+            max_surge: Some(1),
+            max_unavailable: Some(1),
+        }),
+    };
+    let live = Deployment {
+        name: "a".to_owned(),
+        replicas: Some(5),
+        paused: Some(false),
+        labels: BTreeMap::from([
+            ("app".to_owned(), "web".to_owned()),
+            ("tier".to_owned(), "backend".to_owned()),
+            ("injected".to_owned(), "true".to_owned()),
+        ]),
+        rollout_strategy: Strategy::RollingUpdate {
+            max_surge: 2,
+            max_unavailable: 1,
+        },
+    };
+    let desired = DeploymentOpt {
+        name: "a".to_owned(),
+        replicas: Some(3),
+        paused: None,
+        labels: Some(BTreeMap::from([("app".to_owned(), "api".to_owned())])),
+        rollout_strategy: Some(StrategyOpt::RollingUpdate {
+            max_surge: Some(1),
+            max_unavailable: Some(3),
+        }),
+    };
+    let merged = Deployment::three_way_merge(Some(&last_applied), live.into_optioned(), desired);
+    assert_eq!(merged.conflicts, vec!["replicas", "labels[tier]"]);
+    assert_eq!(
+        merged.value,
+        DeploymentOpt {
+            name: "a".to_owned(),
+            replicas: Some(3),
+            paused: None,
+            labels: Some(BTreeMap::from([
+                ("app".to_owned(), "api".to_owned()),
+                ("injected".to_owned(), "true".to_owned()),
+            ])),
+            rollout_strategy: Some(StrategyOpt::RollingUpdate {
+                max_surge: Some(2),
+                max_unavailable: Some(3),
+            }),
+        }
+    );
+
+    // without a base, fields set by only one side are kept and different variants are merged as a whole
+    let merged = Deployment::three_way_merge(
+        None,
+        DeploymentOpt::empty("a".to_owned())
+            .with_replicas(1)
+            .with_rollout_strategy(StrategyOpt::Recreate),
+        DeploymentOpt::empty("b".to_owned()).with_rollout_strategy(StrategyOpt::RollingUpdate {
+            max_surge: Some(1),
+            max_unavailable: None,
+        }),
+    );
+    assert!(merged.has_conflicts());
+    assert_eq!(merged.conflicts, vec!["name", "rolloutStrategy"]);
+    assert_eq!(
+        (merged.value.name.as_str(), merged.value.replicas),
+        ("b", Some(1))
+    );
+}
+
+#[test]
+/// Check that the managers own the (renamed) nested fields they apply, that changing fields owned by another
+/// manager conflicts unless forced and that released fields are only unset if no other manager owns them.
+fn managed_fields() {
+    use optionable::managed_fields::{ApplyError, Conflict, ManagedFields};
+
+    #[derive(Optionable, Serialize)]
+    #[optionable(builder, derive(Clone, Debug, Default, PartialEq))]
+    #[serde(rename_all = "camelCase")]
+    struct Deployment {
+        #[optionable(required)]
+        name: String,
+        spec: Spec,
+        labels: BTreeMap<String, String>,
+    }
+
+    #[derive(Optionable, Serialize)]
+    #[optionable(builder, derive(Clone, Debug, Default, PartialEq))]
+    #[serde(rename_all = "camelCase")]
+    struct Spec {
+        replicas: u32,
+        min_ready_seconds: u32,
+    }
+
+    let labels = |entries: &[(&str, &str)]| {
+        entries
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let mut managed = ManagedFields::<Deployment>::new();
+    managed
+        .apply(
+            "kubectl",
+            DeploymentOpt::empty("a".to_owned())
+                .with_spec_fn(|spec| spec.with_replicas(2).with_min_ready_seconds(10))
+                .with_labels(labels(&[("app", "web")])),
+            false,
+        )
+        .unwrap();
+    // the same value shares the ownership
+    managed
+        .apply(
+            "ci",
+            DeploymentOpt::empty("a".to_owned())
+                .with_spec_fn(|spec| spec.with_min_ready_seconds(10))
+                .with_labels(labels(&[("app", "web"), ("commit", "abc")])),
+            false,
+        )
+        .unwrap();
+    assert_eq!(
+        managed.owners("spec.minReadySeconds"),
+        vec!["ci", "kubectl"]
+    );
+    assert_eq!(managed.owners("labels[commit]"), vec!["ci"]);
+
+    let scaled = DeploymentOpt::empty("a".to_owned()).with_spec_fn(|spec| spec.with_replicas(5));
+    assert_eq!(
+        managed.apply("hpa", scaled.clone(), false),
+        Err(ApplyError::Conflicts(vec![Conflict {
+            manager: "kubectl".to_owned(),
+            path: "spec.replicas".to_owned(),
+        }]))
+    );
+    assert_eq!(managed.value().spec.as_ref().unwrap().replicas, Some(2));
+    managed.apply("hpa", scaled, true).unwrap();
+    assert_eq!(managed.owners("spec.replicas"), vec!["hpa"]);
+    assert_eq!(
+        managed
+            .managed_fields("kubectl")
+            .unwrap()
+            .iter()
+            .collect::<Vec<_>>(),
+        vec!["labels[app]", "spec.minReadySeconds"]
+    );
+
+    // kubectl stops applying the shared and the taken over fields
+    managed
+        .apply(
+            "kubectl",
+            DeploymentOpt::empty("a".to_owned()).with_labels(labels(&[("app", "web")])),
+            false,
+        )
+        .unwrap();
+    assert_eq!(
+        managed.value(),
+        &DeploymentOpt::empty("a".to_owned())
+            .with_spec_fn(|spec| spec.with_replicas(5).with_min_ready_seconds(10))
+            .with_labels(labels(&[("app", "web"), ("commit", "abc")]))
+    );
+    managed
+        .apply("ci", DeploymentOpt::empty("a".to_owned()), false)
+        .unwrap();
+    assert_eq!(
+        managed.value(),
+        &DeploymentOpt::empty("a".to_owned())
+            .with_spec_fn(|spec| spec.with_replicas(5))
+            .with_labels(labels(&[("app", "web")]))
+    );
+    assert_eq!(
+        managed.managers().keys().collect::<Vec<_>>(),
+        vec!["hpa", "kubectl"]
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+/// Check that the `FieldsV1` of an optioned value follows the serde renames and list types
+/// and that it is parsed back into the set field paths.
+fn derive_fields_v1() {
+    use optionable::fields_v1::FieldsV1;
+
+    #[derive(Optionable, Serialize)]
+    #[optionable(fields_v1, derive(Default, Serialize))]
+    #[serde(rename_all = "camelCase")]
+    struct Spec {
+        #[optionable(required)]
+        name: String,
+        replicas: u32,
+        node_selector: BTreeMap<String, String>,
+        #[optionable(map_type = "atomic")]
+        annotations: BTreeMap<String, String>,
+        #[optionable(list_type = "set")]
+        finalizers: Vec<String>,
+        #[optionable(list_type = "map", list_map_key = "name", list_map_key = "protocol")]
+        ports: Option<Vec<Port>>,
+    }
+
+    #[derive(Optionable, Serialize)]
+    #[optionable(fields_v1, derive(Default, Serialize))]
+    struct Port {
+        name: String,
+        protocol: String,
+        number: u16,
+    }
+
+    let spec = SpecOpt {
+        name: "a".to_owned(),
+        replicas: Some(2),
+        node_selector: Some(BTreeMap::from([("zone".to_owned(), "a".to_owned())])),
+        annotations: Some(BTreeMap::from([("a".to_owned(), "b".to_owned())])),
+        finalizers: Some(vec!["x".to_owned()]),
+        ports: Some(vec![PortOpt {
+            name: Some("http".to_owned()),
+            protocol: Some("TCP".to_owned()),
+            number: Some(80),
+        }]),
+    };
+    let fields = spec.fields_v1();
+    assert_eq!(
+        serde_json::to_value(&fields).unwrap(),
+        serde_json::json!({
+            "f:annotations": {},
+            "f:finalizers": {"v:\"x\"": {}},
+            "f:nodeSelector": {"f:zone": {}},
+            "f:ports": {
+                "k:{\"name\":\"http\",\"protocol\":\"TCP\"}": {
+                    ".": {},
+                    "f:name": {},
+                    "f:number": {},
+                    "f:protocol": {},
+                },
+            },
+            "f:replicas": {},
+        })
+    );
+    assert_eq!(
+        fields
+            .field_paths::<Spec>()
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![
+            "annotations",
+            r#"finalizers[v:"x"]"#,
+            "nodeSelector[zone]",
+            r#"ports[k:{"name":"http","protocol":"TCP"}]"#,
+            r#"ports[k:{"name":"http","protocol":"TCP"}].name"#,
+            r#"ports[k:{"name":"http","protocol":"TCP"}].number"#,
+            r#"ports[k:{"name":"http","protocol":"TCP"}].protocol"#,
+            "replicas",
+        ]
+    );
+    assert_eq!(SpecOpt::default().fields_v1(), FieldsV1::default());
+
+    let fields: FieldsV1 = serde_json::from_str(r#"{"f:replicas":1}"#).unwrap();
+    assert_eq!(
+        fields.field_paths::<Spec>().unwrap_err().invalid_entries,
+        vec!["[f:replicas]"]
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+/// Check that the strategic merge patch follows the patch strategies of the fields
+/// and that applying it to the original value results in the modified value.
+fn derive_strategic_merge() {
+    use optionable::strategic_merge::OptionableStrategicMerge;
+
+    #[derive(Optionable)]
+    #[optionable(strategic_merge, derive(Debug, PartialEq, Deserialize, Serialize))]
+    struct Spec {
+        replicas: u32,
+        #[optionable(patch_strategy = "merge")]
+        finalizers: Vec<String>,
+        #[optionable(patch_strategy = "merge", patch_merge_key = "container_port")]
+        ports: Vec<Port>,
+        #[optionable(patch_strategy = "retainKeys")]
+        update_strategy: UpdateStrategy,
+        labels: BTreeMap<String, String>,
+    }
+
+    #[derive(Optionable)]
+    #[optionable(strategic_merge, derive(Debug, PartialEq, Deserialize, Serialize))]
+    struct Port {
+        container_port: u16,
+        protocol: String,
+    }
+
+    #[derive(Optionable)]
+    #[optionable(strategic_merge, derive(Debug, PartialEq, Deserialize, Serialize))]
+    struct UpdateStrategy {
+        r#type: String,
+        max_surge: u32,
+    }
+
+    let port = |container_port| PortOpt {
+        container_port: Some(container_port),
+        protocol: Some("TCP".to_owned()),
+    };
+    let original = SpecOpt {
+        replicas: Some(1),
+        finalizers: Some(vec!["a".to_owned(), "b".to_owned()]),
+        ports: Some(vec![port(80), port(443)]),
+        update_strategy: Some(UpdateStrategyOpt {
+            r#type: Some("RollingUpdate".to_owned()),
+            max_surge: Some(1),
+        }),
+        labels: Some(BTreeMap::from([
+            ("app".to_owned(), "web".to_owned()),
+            ("tier".to_owned(), "frontend".to_owned()),
+        ])),
+    };
+    let modified = SpecOpt {
+        replicas: Some(1),
+        finalizers: Some(vec!["b".to_owned(), "c".to_owned()]),
+        ports: Some(vec![port(443), port(8080)]),
+        update_strategy: Some(UpdateStrategyOpt {
+            r#type: Some("Recreate".to_owned()),
+            max_surge: None,
+        }),
+        labels: Some(BTreeMap::from([("app".to_owned(), "web".to_owned())])),
+    };
+    let patch = Spec::strategic_merge_patch(&original, &modified).unwrap();
+    assert_eq!(
+        patch,
+        serde_json::json!({
+            "$deleteFromPrimitiveList/finalizers": ["a"],
+            "$setElementOrder/finalizers": ["b", "c"],
+            "finalizers": ["c"],
+            "$setElementOrder/ports": [{"container_port": 443}, {"container_port": 8080}],
+            "ports": [
+                {"container_port": 8080, "protocol": "TCP"},
+                {"container_port": 80, "$patch": "delete"},
+            ],
+            "update_strategy": {"type": "Recreate", "max_surge": null, "$retainKeys": ["type"]},
+            "labels": {"tier": null},
+        })
+    );
+    assert_eq!(
+        Spec::apply_strategic_merge_patch(&original, &patch).unwrap(),
+        modified
+    );
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+/// Check that the patch strategies are named like the fields of the serialized optioned type,
+/// which follows the `serde` renames and flattened fields of the original type.
+fn derive_strategic_merge_serde_names() {
+    use optionable::strategic_merge::OptionableStrategicMerge;
+
+    #[derive(Optionable, Deserialize, Serialize)]
+    #[optionable(strategic_merge, derive(Debug, PartialEq, Deserialize, Serialize))]
+    #[serde(rename_all = "camelCase")]
+    struct Spec {
+        #[serde(rename = "ports")]
+        #[optionable(patch_strategy = "merge", patch_merge_key = "containerPort")]
+        container_ports: Vec<ContainerPort>,
+        #[serde(flatten)]
+        metadata: Metadata,
+    }
+
+    #[derive(Optionable, Deserialize, Serialize)]
+    #[optionable(strategic_merge, derive(Debug, PartialEq, Deserialize, Serialize))]
+    #[serde(rename_all = "camelCase")]
+    struct ContainerPort {
+        container_port: u16,
+        protocol: String,
+    }
+
+    #[derive(Optionable, Deserialize, Serialize)]
+    #[optionable(strategic_merge, derive(Debug, PartialEq, Deserialize, Serialize))]
+    struct Metadata {
+        #[optionable(patch_strategy = "merge")]
+        finalizers: Vec<String>,
+    }
+
+    let port = |container_port| ContainerPortOpt {
+        container_port: Some(container_port),
+        protocol: Some("TCP".to_owned()),
+    };
+    let original = SpecOpt {
+        container_ports: Some(vec![port(80), port(443)]),
+        metadata: Some(MetadataOpt {
+            finalizers: Some(vec!["a".to_owned()]),
+        }),
+    };
+    let modified = SpecOpt {
+        container_ports: Some(vec![port(443)]),
+        metadata: Some(MetadataOpt {
+            finalizers: Some(vec!["a".to_owned(), "b".to_owned()]),
+        }),
+    };
+    let patch = Spec::strategic_merge_patch(&original, &modified).unwrap();
+    assert_eq!(
+        patch,
+        serde_json::json!({
+            "$setElementOrder/ports": [{"containerPort": 443}],
+            "ports": [{"containerPort": 80, "$patch": "delete"}],
+            "$setElementOrder/finalizers": ["a", "b"],
+            "finalizers": ["b"],
+        })
+    );
+    assert_eq!(
+        Spec::apply_strategic_merge_patch(&original, &patch).unwrap(),
+        modified
+    );
+}
@@ -0,0 +1,60 @@
+//! Checks that the derived code only relies on the `core` prelude, the `alloc` types are named by their path
+//! to not bring them into scope for the derived code.
+#![no_std]
+
+extern crate alloc;
+// The test harness requires `std`, the derived code must not.
+extern crate std;
+
+use alloc::collections::BTreeMap;
+use optionable::{Optionable, OptionableConvert, OptionableFieldPaths};
+
+#[derive(Optionable, Debug, PartialEq)]
+#[optionable(builder, derive(Debug, PartialEq))]
+struct Deployment {
+    #[optionable(required)]
+    name: alloc::string::String,
+    replicas: Option<u32>,
+    labels: BTreeMap<alloc::string::String, alloc::string::String>,
+    containers: alloc::vec::Vec<Container>,
+    strategy: Strategy,
+}
+
+#[derive(Optionable, Debug, PartialEq)]
+#[optionable(derive(Debug, PartialEq))]
+struct Container(alloc::string::String, [u16; 2]);
+
+#[derive(Optionable, Debug, PartialEq)]
+#[optionable(derive(Debug, PartialEq))]
+enum Strategy {
+    Recreate,
+    RollingUpdate { max_surge: u32 },
+}
+
+#[test]
+/// Check converting, merging and listing the set field paths of derived types without the std prelude.
+fn derive_no_std() {
+    let mut deployment = Deployment {
+        name: "a".into(),
+        replicas: None,
+        labels: BTreeMap::new(),
+        containers: alloc::vec![],
+        strategy: Strategy::Recreate,
+    };
+    let patch = DeploymentOpt::empty("a".into())
+        .with_replicas(2)
+        .with_containers(alloc::vec![ContainerOpt(Some("b".into()), Some([80, 443]))])
+        .with_strategy(StrategyOpt::RollingUpdate { max_surge: Some(1) });
+    assert_eq!(
+        Deployment::set_field_paths(&patch),
+        ["replicas", "containers", "strategy.RollingUpdate.max_surge"]
+    );
+    deployment.merge(patch).unwrap();
+    assert_eq!(deployment.replicas, Some(2));
+    assert_eq!(deployment.containers, [Container("b".into(), [80, 443])]);
+    assert_eq!(
+        deployment.strategy,
+        Strategy::RollingUpdate { max_surge: 1 }
+    );
+    assert!(Deployment::try_from_optioned(DeploymentOpt::empty("a".into())).is_err());
+}
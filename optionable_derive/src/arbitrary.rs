@@ -0,0 +1,236 @@
+use crate::convert::{var, FieldKind, FieldsInfo};
+use crate::derive::push_deferred_bound;
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::quote;
+use syn::{parse_quote, Data, DataEnum, Generics, WherePredicate};
+
+/// Derives the `OptionableArbitrary` impl for the type and the `arbitrary::Arbitrary` impl for the
+/// optioned type.
+///
+/// The fields are generated in their order of declaration from the same unstructured data. Patches keep the
+/// required fields and replace enum values by a completely set variant as alternative to patching the
+/// present variant.
+pub(crate) fn derive_arbitrary(
+    type_ident: &Ident,
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    data: &Data,
+    input_object: bool,
+) -> syn::Result<TokenStream> {
+    let mut predicates: Vec<WherePredicate> = vec![];
+    let (optioned, patch, required_only, unstructured_used) = match data {
+        Data::Struct(s) => {
+            let fields = FieldsInfo::new(&s.fields, input_object)?;
+            let generated = FieldValues::new(&fields, &mut predicates);
+            let optioned =
+                fields.construct(&quote!(#type_ident_opt), generated.optioned.into_iter());
+            let patch = fields.construct(&quote!(#type_ident_opt), generated.patch.into_iter());
+            let value = fields.bind(&quote!(Self), "field");
+            (
+                quote!(Ok(#optioned)),
+                quote! {
+                    let #value = value;
+                    Ok(#patch)
+                },
+                fields.required_only(),
+                // the unstructured data is unused without fields resp. non-required fields
+                (!fields.fields.is_empty(), !fields.required_only()),
+            )
+        }
+        Data::Enum(e) => {
+            let (optioned, patch, required_only) =
+                enum_values(type_ident_opt, e, input_object, &mut predicates)?;
+            (optioned, patch, required_only, (true, true))
+        }
+        Data::Union(_) => {
+            return crate::error("#[optionable(arbitrary)] is not supported for unions")
+        }
+    };
+
+    // the density is unused without non-required fields
+    let density = if required_only {
+        quote!(_density)
+    } else {
+        quote!(density)
+    };
+    let unstructured = |used: bool| {
+        if used {
+            quote!(u)
+        } else {
+            quote!(_u)
+        }
+    };
+    let (u_optioned, u_patch) = (
+        unstructured(unstructured_used.0),
+        unstructured(unstructured_used.1),
+    );
+
+    let mut generics = generics.clone();
+    if !predicates.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // the lifetime of the unstructured data of `arbitrary::Arbitrary`
+    let mut arbitrary_generics = generics.clone();
+    arbitrary_generics.params.insert(0, parse_quote!('__u));
+    let (arbitrary_impl_generics, _, _) = arbitrary_generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::optionable::arbitrary::OptionableArbitrary for #type_ident #ty_generics #where_clause {
+            fn arbitrary_optioned(
+                #u_optioned: &mut ::optionable::__private::arbitrary::Unstructured<'_>,
+                #density: f64,
+            ) -> ::optionable::__private::arbitrary::Result<#type_ident_opt #ty_generics> {
+                #optioned
+            }
+
+            fn arbitrary_patch(
+                value: &Self,
+                #u_patch: &mut ::optionable::__private::arbitrary::Unstructured<'_>,
+                #density: f64,
+            ) -> ::optionable::__private::arbitrary::Result<#type_ident_opt #ty_generics> {
+                #patch
+            }
+        }
+
+        #[automatically_derived]
+        impl #arbitrary_impl_generics ::optionable::__private::arbitrary::Arbitrary<'__u> for #type_ident_opt #ty_generics #where_clause {
+            fn arbitrary(
+                u: &mut ::optionable::__private::arbitrary::Unstructured<'__u>,
+            ) -> ::optionable::__private::arbitrary::Result<Self> {
+                <#type_ident #ty_generics as ::optionable::arbitrary::OptionableArbitrary>::arbitrary_optioned(
+                    u,
+                    ::optionable::arbitrary::DEFAULT_DENSITY,
+                )
+            }
+        }
+    })
+}
+
+/// Returns the bodies of `arbitrary_optioned` and `arbitrary_patch` for an enum and whether all fields
+/// of all variants are required.
+fn enum_values(
+    type_ident_opt: &Ident,
+    e: &DataEnum,
+    input_object: bool,
+    predicates: &mut Vec<WherePredicate>,
+) -> syn::Result<(TokenStream, TokenStream, bool)> {
+    if e.variants.is_empty() {
+        return crate::error(
+            "#[optionable(arbitrary)] is not supported for enums without variants",
+        );
+    }
+    let mut variants = vec![];
+    let mut arms = vec![];
+    let mut required_only = true;
+    for (i, v) in e.variants.iter().enumerate() {
+        let ident = &v.ident;
+        let fields = FieldsInfo::new(&v.fields, input_object)?;
+        required_only &= fields.required_only();
+        let generated = FieldValues::new(&fields, predicates);
+        let optioned = fields.construct(
+            &quote!(#type_ident_opt::#ident),
+            generated.optioned.into_iter(),
+        );
+        // the last variant is chosen for all other indices to keep the match exhaustive
+        let index = if i + 1 == e.variants.len() {
+            quote!(_)
+        } else {
+            let i = Literal::usize_unsuffixed(i);
+            quote!(#i)
+        };
+        variants.push(quote!(#index => Ok(#optioned)));
+        let patch = fields.construct(
+            &quote!(#type_ident_opt::#ident),
+            generated.patch.into_iter(),
+        );
+        let value = fields.bind(&quote!(Self::#ident), "field");
+        arms.push(quote! {
+            #value if ::optionable::__private::arbitrary::Unstructured::arbitrary::<bool>(u)? => Ok(#patch)
+        });
+    }
+    let len = e.variants.len();
+    Ok((
+        quote! {
+            match ::optionable::__private::arbitrary::Unstructured::choose_index(u, #len)? {
+                #(#variants),*
+            }
+        },
+        quote! {
+            match value {
+                #(#arms,)*
+                _ => <Self as ::optionable::arbitrary::OptionableArbitrary>::arbitrary_optioned(u, 1.0),
+            }
+        },
+        required_only,
+    ))
+}
+
+/// The generated values of the fields of a struct or enum variant, the patches refer to the present
+/// values bound to `field_<index>`.
+struct FieldValues {
+    optioned: Vec<TokenStream>,
+    patch: Vec<TokenStream>,
+}
+
+impl FieldValues {
+    /// Collects the field values and adds the bounds of the field types to `predicates`.
+    fn new(fields: &FieldsInfo, predicates: &mut Vec<WherePredicate>) -> Self {
+        let mut values = Self {
+            optioned: vec![],
+            patch: vec![],
+        };
+        for (i, f) in fields.fields.iter().enumerate() {
+            let (ty, field) = (&f.ty, var("field", i));
+            let (bounded_ty, bound) = match &f.kind {
+                FieldKind::Required => {
+                    values.optioned.push(quote!(
+                        ::optionable::__private::arbitrary::Arbitrary::arbitrary(u)?
+                    ));
+                    values
+                        .patch
+                        .push(quote!(::core::clone::Clone::clone(#field)));
+                    (
+                        ty,
+                        quote!(
+                            ::optionable::__private::arbitrary::Arbitrary<'__a>
+                                + ::core::clone::Clone
+                        ),
+                    )
+                }
+                FieldKind::Optioned => {
+                    values.optioned.push(quote! {
+                        <#ty as ::optionable::arbitrary::OptionableArbitrary>::arbitrary_optioned(u, density)?
+                    });
+                    values.patch.push(quote! {
+                        <#ty as ::optionable::arbitrary::OptionableArbitrary>::arbitrary_patch(#field, u, density)?
+                    });
+                    (ty, quote!(::optionable::arbitrary::OptionableArbitrary))
+                }
+                FieldKind::Wrapped => {
+                    values.optioned.push(quote! {
+                        <Option<#ty> as ::optionable::arbitrary::OptionableArbitrary>::arbitrary_optioned(u, density)?
+                    });
+                    values.patch.push(quote! {
+                        ::optionable::__private::arbitrary_patch_field::<#ty>(#field, u, density)?
+                    });
+                    (ty, quote!(::optionable::arbitrary::OptionableArbitrary))
+                }
+                FieldKind::TriState(inner_ty) => {
+                    values.optioned.push(
+                        quote!(::optionable::__private::arbitrary_tri_state::<#inner_ty>(u, density)?),
+                    );
+                    values.patch.push(quote! {
+                        ::optionable::__private::arbitrary_patch_tri_state::<#inner_ty>(#field, u, density)?
+                    });
+                    (
+                        &**inner_ty,
+                        quote!(::optionable::arbitrary::OptionableArbitrary),
+                    )
+                }
+            };
+            push_deferred_bound(predicates, bounded_ty, &bound);
+        }
+        values
+    }
+}
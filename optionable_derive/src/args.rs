@@ -0,0 +1,196 @@
+use crate::attributes::FieldHelperAttributes;
+use crate::derive::{is_option, option_inner_type, push_deferred_bound, tri_state_inner_type};
+use darling::FromAttributes;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{
+    Attribute, Data, Expr, ExprLit, Field, Fields, Generics, Lit, Meta, WherePredicate,
+};
+
+/// Derives the `clap::Args` impl for the optioned type (together with `clap::FromArgMatches`) and the
+/// `OptionableArgs` impl flattening the flags of the fields into the flags of a parent struct.
+///
+/// Every field becomes a flag named by the kebab-case field name, prefixed for nested structs.
+/// Only the flags of required fields are required. The first paragraph of the field docs is the help text.
+pub(crate) fn derive_args(
+    type_ident: &Ident,
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    data: &Data,
+    input_object: bool,
+) -> syn::Result<TokenStream> {
+    let Data::Struct(s) = data else {
+        return crate::error("`clap::Args` is only supported for structs with named fields");
+    };
+    let Fields::Named(fields) = &s.fields else {
+        return crate::error("`clap::Args` is only supported for structs with named fields");
+    };
+
+    let mut args = FieldArgs::default();
+    for f in &fields.named {
+        args.push(f, input_object)?;
+    }
+    let FieldArgs {
+        augments,
+        values,
+        updates,
+        predicates,
+    } = args;
+
+    // the parameters are unused without fields
+    let (name, matches) = if fields.named.is_empty() {
+        (quote!(_name), quote!(_matches))
+    } else {
+        (quote!(name), quote!(matches))
+    };
+    let root_name = (!fields.named.is_empty()).then(|| quote!(let name = "";));
+    let other = if fields.named.is_empty() {
+        quote!(_)
+    } else {
+        quote!(other)
+    };
+
+    let mut generics = generics.clone();
+    if !predicates.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::optionable::clap::OptionableArgs for #type_ident #ty_generics #where_clause {
+            fn augment_args_optioned(cmd: ::clap::Command, #name: &str, _help: Option<&str>) -> ::clap::Command {
+                #(#augments)*
+                cmd
+            }
+
+            fn from_arg_matches_optioned(
+                #matches: &::clap::ArgMatches,
+                #name: &str,
+            ) -> ::core::result::Result<Option<Self::Optioned>, ::clap::Error> {
+                let value = #type_ident_opt {
+                    #(#values),*
+                };
+                Ok((!<Self as ::optionable::Optionable>::is_empty_optioned(&value)).then_some(value))
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::clap::FromArgMatches for #type_ident_opt #ty_generics #where_clause {
+            fn from_arg_matches(#matches: &::clap::ArgMatches) -> ::core::result::Result<Self, ::clap::Error> {
+                #root_name
+                Ok(#type_ident_opt {
+                    #(#values),*
+                })
+            }
+
+            fn update_from_arg_matches(&mut self, matches: &::clap::ArgMatches) -> ::core::result::Result<(), ::clap::Error> {
+                let #other = <Self as ::clap::FromArgMatches>::from_arg_matches(matches)?;
+                #(#updates)*
+                Ok(())
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::clap::Args for #type_ident_opt #ty_generics #where_clause {
+            fn augment_args(cmd: ::clap::Command) -> ::clap::Command {
+                <#type_ident #ty_generics as ::optionable::clap::OptionableArgs>::augment_args_optioned(cmd, "", None)
+            }
+
+            fn augment_args_for_update(cmd: ::clap::Command) -> ::clap::Command {
+                <#type_ident #ty_generics as ::optionable::clap::OptionableArgs>::augment_args_optioned(cmd, "", None)
+            }
+        }
+    })
+}
+
+/// The generated code per field for the impls.
+#[derive(Default)]
+struct FieldArgs {
+    /// Statements adding the flags of the fields to `cmd`.
+    augments: Vec<TokenStream>,
+    /// Field values read from `matches`.
+    values: Vec<TokenStream>,
+    /// Statements replacing the fields of `self` by the set fields of `other`.
+    updates: Vec<TokenStream>,
+    /// Bounds of the field types.
+    predicates: Vec<WherePredicate>,
+}
+
+impl FieldArgs {
+    /// Adds the generated code for the field, the flag names are prefixed by the variable `name`.
+    fn push(&mut self, f: &Field, input_object: bool) -> syn::Result<()> {
+        let attrs = FieldHelperAttributes::from_attributes(&f.attrs)?;
+        let (ident, ty) = (&f.ident, &f.ty);
+        let flag = ident
+            .as_ref()
+            .map(|ident| ident.to_string().trim_start_matches("r#").replace('_', "-"))
+            .unwrap_or_default();
+        let name = quote!(&::optionable::__private::arg_name(name, #flag));
+        let help = help(&f.attrs).map_or_else(|| quote!(None), |help| quote!(Some(#help)));
+        if attrs.required.is_some() {
+            self.augments.push(quote! {
+                let cmd = cmd.arg(
+                    ::optionable::__private::arg(#name, #help, true).value_parser(::clap::value_parser!(#ty))
+                );
+            });
+            self.values.push(
+                quote!(#ident: ::optionable::__private::required_arg_value::<#ty>(matches, #name)?),
+            );
+            self.updates.push(quote!(self.#ident = other.#ident;));
+            push_deferred_bound(
+                &mut self.predicates,
+                ty,
+                &quote!(::core::clone::Clone + ::core::marker::Send + ::core::marker::Sync + 'static),
+            );
+            return Ok(());
+        }
+        // the optioned type of `Option<T>` is `Option<T::Optioned>`, the flags hold the inner value
+        let inner_ty = option_inner_type(ty)
+            .filter(|_| is_option(ty))
+            .unwrap_or(ty);
+        self.augments.push(quote! {
+            let cmd = <#inner_ty as ::optionable::clap::OptionableArgs>::augment_args_optioned(cmd, #name, #help);
+        });
+        let value = quote!(<#inner_ty as ::optionable::clap::OptionableArgs>::from_arg_matches_optioned(matches, #name)?);
+        if tri_state_inner_type(f, input_object)?.is_some() {
+            self.values
+                .push(quote!(#ident: ::async_graphql::MaybeUndefined::from(#value.map(Some))));
+            self.updates.push(quote! {
+                if !other.#ident.is_undefined() {
+                    self.#ident = other.#ident;
+                }
+            });
+        } else {
+            self.values.push(quote!(#ident: #value));
+            self.updates.push(quote! {
+                if other.#ident.is_some() {
+                    self.#ident = other.#ident;
+                }
+            });
+        }
+        push_deferred_bound(
+            &mut self.predicates,
+            inner_ty,
+            &quote!(::optionable::clap::OptionableArgs),
+        );
+        Ok(())
+    }
+}
+
+/// Returns the first paragraph of the doc comments as help text.
+fn help(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(doc), ..
+                }) => Some(doc.value().trim().to_owned()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .take_while(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    (!lines.is_empty()).then(|| lines.join(" "))
+}
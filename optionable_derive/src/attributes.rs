@@ -0,0 +1,103 @@
+use crate::validate::Validations;
+use darling::util::PathList;
+use darling::{FromAttributes, FromDeriveInput};
+use proc_macro2::Span;
+use syn::LitStr;
+
+#[derive(FromDeriveInput)]
+#[darling(attributes(optionable))]
+/// Helper attributes on the type definition level (attached to the `struct` or `enum` itself).
+pub(crate) struct TypeHelperAttributes {
+    pub(crate) derive: Option<PathList>,
+    #[darling(default=default_suffix)]
+    pub(crate) suffix: LitStr,
+    pub(crate) bound: Option<LitStr>,
+    pub(crate) no_convert: Option<()>,
+    pub(crate) no_field_paths: Option<()>,
+    pub(crate) builder: Option<()>,
+    pub(crate) table: Option<LitStr>,
+    pub(crate) sqlx: Option<()>,
+    pub(crate) env: Option<()>,
+    pub(crate) validate: Option<()>,
+    pub(crate) proptest: Option<()>,
+    pub(crate) arbitrary: Option<()>,
+    pub(crate) three_way: Option<()>,
+    pub(crate) fields_v1: Option<()>,
+    pub(crate) strategic_merge: Option<()>,
+}
+
+#[derive(FromAttributes)]
+#[darling(attributes(optionable))]
+/// Helper attributes on the field level (attached to the fields of a `struct` or `enum` variant).
+pub(crate) struct FieldHelperAttributes {
+    pub(crate) required: Option<()>,
+    pub(crate) bound: Option<LitStr>,
+    pub(crate) list_type: Option<LitStr>,
+    #[darling(multiple)]
+    pub(crate) list_map_key: Vec<LitStr>,
+    pub(crate) map_type: Option<LitStr>,
+    pub(crate) patch_strategy: Option<LitStr>,
+    pub(crate) patch_merge_key: Option<LitStr>,
+    pub(crate) column: Option<LitStr>,
+    pub(crate) validate: Option<Validations>,
+}
+
+impl FieldHelperAttributes {
+    /// Checks the Kubernetes list/map types of the field.
+    pub(crate) fn check_merge_strategy(&self) -> syn::Result<()> {
+        if let Some(list_type) = &self.list_type
+            && !["atomic", "set", "map"].contains(&list_type.value().as_str())
+        {
+            return Err(syn::Error::new_spanned(
+                list_type,
+                r#"list_type has to be one of "atomic", "set" or "map""#,
+            ));
+        }
+        if !self.list_map_key.is_empty()
+            && self.list_type.as_ref().is_none_or(|t| t.value() != "map")
+        {
+            return Err(syn::Error::new_spanned(
+                &self.list_map_key[0],
+                r#"list_map_key requires list_type = "map""#,
+            ));
+        }
+        if let Some(map_type) = &self.map_type
+            && !["atomic", "granular"].contains(&map_type.value().as_str())
+        {
+            return Err(syn::Error::new_spanned(
+                map_type,
+                r#"map_type has to be one of "atomic" or "granular""#,
+            ));
+        }
+        if let Some(patch_strategy) = &self.patch_strategy {
+            let value = patch_strategy.value();
+            let strategies = value.split(',').collect::<Vec<_>>();
+            if !strategies
+                .iter()
+                .all(|s| ["merge", "replace", "retainKeys"].contains(s))
+                || (strategies.contains(&"merge") && strategies.contains(&"replace"))
+            {
+                return Err(syn::Error::new_spanned(
+                    patch_strategy,
+                    r#"patch_strategy has to be one of "merge", "replace", "retainKeys" or "merge,retainKeys""#,
+                ));
+            }
+        }
+        if let Some(patch_merge_key) = &self.patch_merge_key
+            && self
+                .patch_strategy
+                .as_ref()
+                .is_none_or(|s| !s.value().split(',').any(|s| s == "merge"))
+        {
+            return Err(syn::Error::new_spanned(
+                patch_merge_key,
+                r#"patch_merge_key requires patch_strategy = "merge""#,
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn default_suffix() -> LitStr {
+    LitStr::new("Opt", Span::call_site())
+}
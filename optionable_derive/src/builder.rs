@@ -0,0 +1,119 @@
+use crate::attributes::FieldHelperAttributes;
+use crate::derive::{deferred_bound, is_option, option_inner_type, tri_state_inner_type};
+use darling::FromAttributes;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Data, Fields, Generics};
+
+/// Derives the fluent `with_<field>` setters for the optioned type. If `OptionableConvert` is derived (`convert`),
+/// the `with_<field>_value` setters take the full value of a field and convert it into its optioned value.
+///
+/// The `Default` bound of the closure setters and the `OptionableConvert` bound of the value setters are
+/// expressed as higher-ranked trait bounds so that they are only checked when used and don't prevent
+/// compilation if they don't hold.
+pub(crate) fn derive_builder(
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    data: &Data,
+    input_object: bool,
+    convert: bool,
+) -> syn::Result<TokenStream> {
+    let Data::Struct(s) = data else {
+        return crate::error(
+            "#[optionable(builder)] is only supported for structs with named fields",
+        );
+    };
+    let Fields::Named(fields) = &s.fields else {
+        return crate::error(
+            "#[optionable(builder)] is only supported for structs with named fields",
+        );
+    };
+
+    let mut setters = vec![];
+    for f in &fields.named {
+        let attrs = FieldHelperAttributes::from_attributes(&f.attrs)?;
+        let (vis, ident, ty) = (&f.vis, &f.ident, &f.ty);
+        let name = ident
+            .as_ref()
+            .map(|ident| ident.to_string().trim_start_matches("r#").to_owned())
+            .unwrap_or_default();
+        let (setter, setter_fn, setter_value) = (
+            format_ident!("with_{name}"),
+            format_ident!("with_{name}_fn"),
+            format_ident!("with_{name}_value"),
+        );
+        if attrs.required.is_some() {
+            let doc = format!("Sets the required `{name}` field.");
+            setters.push(quote! {
+                #[doc = #doc]
+                #[must_use]
+                #vis fn #setter(mut self, value: #ty) -> Self {
+                    self.#ident = value;
+                    self
+                }
+            });
+            continue;
+        }
+        // the optioned type of `Option<T>` is `Option<T::Optioned>`, the setters take the inner value
+        let inner_ty = option_inner_type(ty)
+            .filter(|_| is_option(ty))
+            .unwrap_or(ty);
+        let optioned_ty = quote!(<#inner_ty as ::optionable::Optionable>::Optioned);
+        let (set, take) = if tri_state_inner_type(f, input_object)?.is_some() {
+            (
+                quote!(::async_graphql::MaybeUndefined::Value),
+                quote!(::core::mem::take(&mut self.#ident).take()),
+            )
+        } else {
+            (quote!(Some), quote!(self.#ident.take()))
+        };
+        let default_bound = deferred_bound(&optioned_ty, &quote!(::core::default::Default));
+        let doc = format!("Sets the `{name}` field.");
+        let doc_fn = format!(
+            "Sets the `{name}` field to the result of the closure which receives the current value or the default if unset."
+        );
+        setters.push(quote! {
+            #[doc = #doc]
+            #[must_use]
+            #vis fn #setter(mut self, value: #optioned_ty) -> Self {
+                self.#ident = #set(value);
+                self
+            }
+
+            #[doc = #doc_fn]
+            #[must_use]
+            #vis fn #setter_fn(mut self, f: impl FnOnce(#optioned_ty) -> #optioned_ty) -> Self
+            where
+                #default_bound,
+            {
+                self.#ident = #set(f(#take.unwrap_or_default()));
+                self
+            }
+        });
+        if convert {
+            let convert_bound = deferred_bound(inner_ty, &quote!(::optionable::OptionableConvert));
+            let doc_value =
+                format!("Sets the `{name}` field to the optioned value converted from the full value.");
+            setters.push(quote! {
+                #[doc = #doc_value]
+                #[must_use]
+                #vis fn #setter_value(mut self, value: #inner_ty) -> Self
+                where
+                    #convert_bound,
+                {
+                    self.#ident = #set(::optionable::OptionableConvert::into_optioned(value));
+                    self
+                }
+            });
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #type_ident_opt #ty_generics #where_clause {
+            #(#setters)*
+        }
+    })
+}
@@ -0,0 +1,169 @@
+use crate::attributes::FieldHelperAttributes;
+use crate::derive::{is_option, option_inner_type, tri_state_inner_type};
+use darling::FromAttributes;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Fields, Generics, LitStr, Path, Visibility};
+
+/// The column of a field of the optioned type.
+struct Column {
+    /// Name of the column, the field name unless mapped via `#[optionable(column = "...")]`.
+    name: Ident,
+    /// The type of the value bound to the column.
+    ty: TokenStream,
+    /// Expression for the value of a required field, for all other fields for the `Option`
+    /// of the value which is `None` if the field is not set.
+    value: TokenStream,
+}
+
+/// Derives the SQL `UPDATE` support for the optioned type: the `diesel::query_builder::AsChangeset` impl
+/// (if `diesel` is set) and the `update_query` method building the statement via `sqlx` (if `sqlx` is set).
+///
+/// Only set fields are part of the changeset. Required fields identify the updated rows instead,
+/// for `sqlx` they are added as `WHERE` conditions, for `diesel` they are skipped.
+pub(crate) fn derive_changeset(
+    type_ident_opt: &Ident,
+    input: &DeriveInput,
+    table: Option<&LitStr>,
+    diesel: bool,
+    sqlx: bool,
+    input_object: bool,
+) -> syn::Result<TokenStream> {
+    let (vis, generics) = (&input.vis, &input.generics);
+    let (Data::Struct(s), Some(table)) = (&input.data, table) else {
+        return crate::error(
+            "`AsChangeset` and #[optionable(sqlx)] are only supported for structs with named fields and a `table`",
+        );
+    };
+    let Fields::Named(fields) = &s.fields else {
+        return crate::error(
+            "`AsChangeset` and #[optionable(sqlx)] are only supported for structs with named fields and a `table`",
+        );
+    };
+
+    let mut key_columns = vec![];
+    let mut columns = vec![];
+    for f in &fields.named {
+        let attrs = FieldHelperAttributes::from_attributes(&f.attrs)?;
+        let (ident, ty) = (&f.ident, &f.ty);
+        let name = match &attrs.column {
+            Some(column) => column.parse_with(Ident::parse_any)?,
+            None => ident.clone().unwrap_or_else(|| unreachable!("named field")),
+        };
+        let (ty, value) = if attrs.required.is_some() {
+            (quote!(#ty), quote!(self.#ident))
+        } else if let Some(inner_ty) = tri_state_inner_type(f, input_object)? {
+            // an explicit null sets the column to `NULL`
+            let ty = quote!(Option<<#inner_ty as ::optionable::Optionable>::Optioned>);
+            (ty.clone(), quote!(Option::<#ty>::from(self.#ident)))
+        } else {
+            // the optioned type of `Option<T>` is `Option<T::Optioned>`, the columns take the inner value
+            let inner_ty = option_inner_type(ty)
+                .filter(|_| is_option(ty))
+                .unwrap_or(ty);
+            (
+                quote!(<#inner_ty as ::optionable::Optionable>::Optioned),
+                quote!(self.#ident),
+            )
+        };
+        let column = Column { name, ty, value };
+        if attrs.required.is_some() {
+            key_columns.push(column);
+        } else {
+            columns.push(column);
+        }
+    }
+
+    let diesel_impl = diesel
+        .then(|| {
+            let table = table.parse()?;
+            Ok::<_, syn::Error>(derive_as_changeset(
+                type_ident_opt,
+                generics,
+                &table,
+                &columns,
+            ))
+        })
+        .transpose()?;
+    let sqlx_impl = sqlx.then(|| {
+        derive_update_query(
+            vis,
+            type_ident_opt,
+            generics,
+            &table.value(),
+            &columns,
+            &key_columns,
+        )
+    });
+    Ok(quote! {
+        #diesel_impl
+        #sqlx_impl
+    })
+}
+
+/// Derives the `diesel::query_builder::AsChangeset` impl. The `table` is the path of the module
+/// generated by `diesel::table!`, the column names are the identifiers of its columns.
+fn derive_as_changeset(
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    table: &Path,
+    columns: &[Column],
+) -> TokenStream {
+    let names = columns.iter().map(|c| &c.name).collect::<Vec<_>>();
+    let tys = columns.iter().map(|c| &c.ty);
+    let values = columns.iter().map(|c| &c.value);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::diesel::query_builder::AsChangeset for #type_ident_opt #ty_generics #where_clause {
+            type Target = #table::table;
+            type Changeset = <(#(Option<::diesel::dsl::Eq<#table::#names, #tys>>,)*) as ::diesel::query_builder::AsChangeset>::Changeset;
+
+            fn as_changeset(self) -> Self::Changeset {
+                ::diesel::query_builder::AsChangeset::as_changeset((
+                    #(#values.map(|value| ::diesel::ExpressionMethods::eq(#table::#names, value)),)*
+                ))
+            }
+        }
+    }
+}
+
+/// Derives the `update_query` method building the `UPDATE` statement of the `table` via `sqlx`.
+fn derive_update_query(
+    vis: &Visibility,
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    table: &str,
+    columns: &[Column],
+    key_columns: &[Column],
+) -> TokenStream {
+    let column_name = |c: &Column| c.name.unraw().to_string();
+    let set_columns = columns.iter().map(|c| {
+        let (name, value) = (column_name(c), &c.value);
+        quote!(query.set(#name, #value);)
+    });
+    let filters = key_columns.iter().map(|c| {
+        let (name, value) = (column_name(c), &c.value);
+        quote!(query.filter(#name, #value);)
+    });
+    let tys = columns.iter().chain(key_columns).map(|c| &c.ty);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #type_ident_opt #ty_generics #where_clause {
+            /// Returns the `UPDATE` statement setting the columns of the set fields for the rows matching
+            /// the required fields, `None` if no field is set.
+            #[must_use]
+            #vis fn update_query<'args, DB: ::sqlx::Database>(self) -> Option<::sqlx::QueryBuilder<'args, DB>>
+            where
+                #(#tys: 'args + ::sqlx::Encode<'args, DB> + ::sqlx::Type<DB>,)*
+            {
+                let mut query = ::optionable::__private::UpdateQuery::new(#table);
+                #(#set_columns)*
+                #(#filters)*
+                query.finish()
+            }
+        }
+    }
+}
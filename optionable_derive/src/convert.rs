@@ -0,0 +1,487 @@
+use crate::attributes::FieldHelperAttributes;
+use crate::derive::{is_option, option_inner_type, push_deferred_bound, tri_state_inner_type};
+use darling::FromAttributes;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{Data, DataEnum, Fields, Generics, Member, Type, WherePredicate};
+
+/// How a field of the original type is represented in the optioned type.
+pub(crate) enum FieldKind {
+    /// Kept as is.
+    Required,
+    /// The optioned type already expresses an unset value (the field type is an `Option`).
+    Optioned,
+    /// Wrapped in an `Option` of the optioned type.
+    Wrapped,
+    /// The field type is `Option<T>` and represented as `async_graphql::MaybeUndefined<T::Optioned>`.
+    TriState(Box<Type>),
+}
+
+/// The relevant information of a field to generate the conversion code.
+pub(crate) struct FieldInfo {
+    pub(crate) member: Member,
+    pub(crate) ty: Type,
+    pub(crate) kind: FieldKind,
+}
+
+/// The fields of a struct or enum variant.
+pub(crate) struct FieldsInfo {
+    pub(crate) fields: Vec<FieldInfo>,
+    named: bool,
+    unit: bool,
+}
+
+impl FieldsInfo {
+    pub(crate) fn new(fields: &Fields, input_object: bool) -> syn::Result<Self> {
+        let named = matches!(fields, Fields::Named(_));
+        let unit = matches!(fields, Fields::Unit);
+        let fields = fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let attrs = FieldHelperAttributes::from_attributes(&f.attrs)?;
+                let kind = if attrs.required.is_some() {
+                    FieldKind::Required
+                } else if let Some(inner_ty) = tri_state_inner_type(f, input_object)? {
+                    FieldKind::TriState(Box::new(inner_ty.clone()))
+                } else if named && is_option(&f.ty) {
+                    FieldKind::Optioned
+                } else {
+                    FieldKind::Wrapped
+                };
+                Ok::<_, syn::Error>(FieldInfo {
+                    member: f
+                        .ident
+                        .clone()
+                        .map_or_else(|| Member::from(i), Member::from),
+                    ty: f.ty.clone(),
+                    kind,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            fields,
+            named,
+            unit,
+        })
+    }
+
+    /// Returns whether all fields are required, i.e. there is no field of the optioned type that may be unset.
+    pub(crate) fn required_only(&self) -> bool {
+        self.fields
+            .iter()
+            .all(|f| matches!(f.kind, FieldKind::Required))
+    }
+
+    /// Returns a pattern or constructor for the given path with the fields bound to/taken from `<prefix>_<index>`.
+    pub(crate) fn bind(&self, path: &TokenStream, prefix: &str) -> TokenStream {
+        let vars = (0..self.fields.len()).map(|i| var(prefix, i).to_token_stream());
+        self.construct(path, vars)
+    }
+
+    /// Returns a constructor for the given path with the fields set to the given values.
+    pub(crate) fn construct(
+        &self,
+        path: &TokenStream,
+        values: impl Iterator<Item = TokenStream>,
+    ) -> TokenStream {
+        if self.unit {
+            return path.clone();
+        }
+        if self.named {
+            let members = self.fields.iter().map(|f| &f.member);
+            quote!(#path { #(#members: #values),* })
+        } else {
+            quote!(#path ( #(#values),* ))
+        }
+    }
+
+    /// Returns the statements to convert the fields bound to `field_<index>` into the full type,
+    /// rebinding them as `field_<index>`. Returns early with an error if fields are missing.
+    fn try_from_optioned(&self) -> TokenStream {
+        let converted = self
+            .fields
+            .iter()
+            .enumerate()
+            .filter_map(|(i, f)| {
+                let (var, ty, name) = (var("field", i), &f.ty, field_name(&f.member));
+                match f.kind {
+                    FieldKind::Required => None,
+                    FieldKind::Optioned => Some((var.clone(), quote! {
+                        let #var = ::optionable::__private::try_from_optioned_value::<#ty>(#var, #name, &mut missing_fields);
+                    })),
+                    FieldKind::Wrapped => Some((var.clone(), quote! {
+                        let #var = ::optionable::__private::try_from_optioned_field::<#ty>(#var, #name, &mut missing_fields);
+                    })),
+                    FieldKind::TriState(ref inner_ty) => Some((var.clone(), quote! {
+                        let #var = ::optionable::__private::try_from_tri_state::<#inner_ty>(#var, #name, &mut missing_fields);
+                    })),
+                }
+            })
+            .collect::<Vec<_>>();
+        if converted.is_empty() {
+            return TokenStream::new();
+        }
+        let (vars, statements): (Vec<_>, Vec<_>) = converted.into_iter().unzip();
+        let (pattern, values) = if let [var] = vars.as_slice() {
+            (quote!(Some(#var)), var.to_token_stream())
+        } else {
+            (quote!((#(Some(#vars)),*)), quote!((#(#vars),*)))
+        };
+        quote! {
+            let mut missing_fields = ::optionable::__private::Vec::new();
+            #(#statements)*
+            let #pattern = #values else {
+                return Err(::optionable::Error { missing_fields });
+            };
+        }
+    }
+
+    /// Returns the expression to merge the fields bound to `other_<index>` into the fields bound to `self_<index>`.
+    fn merge(&self) -> TokenStream {
+        let mut has_optioned_fields = false;
+        let statements = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let (target, value, name) = (var("self", i), var("other", i), field_name(&f.member));
+                match f.kind {
+                    FieldKind::Required => quote! {
+                        *#target = #value;
+                    },
+                    FieldKind::Optioned => {
+                        has_optioned_fields = true;
+                        quote! {
+                            ::optionable::__private::merge_value(#target, #value, #name, &mut missing_fields);
+                        }
+                    }
+                    FieldKind::Wrapped => {
+                        has_optioned_fields = true;
+                        quote! {
+                            ::optionable::__private::merge_field(#target, #value, #name, &mut missing_fields);
+                        }
+                    }
+                    FieldKind::TriState(_) => {
+                        has_optioned_fields = true;
+                        quote! {
+                            ::optionable::__private::merge_tri_state(#target, #value, #name, &mut missing_fields);
+                        }
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        if has_optioned_fields {
+            quote! {
+                let mut missing_fields = ::optionable::__private::Vec::new();
+                #(#statements)*
+                ::optionable::__private::result(missing_fields)
+            }
+        } else {
+            quote! {
+                #(#statements)*
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the statements to merge the optioned fields bound to `other_<index>` into the optioned fields
+    /// bound to `self_<index>`, i.e. to combine two optioned values where the set fields of `other` take precedence.
+    fn merge_optioned(&self) -> TokenStream {
+        let statements = self.fields.iter().enumerate().map(|(i, f)| {
+            let (target, value) = (var("self", i), var("other", i));
+            match f.kind {
+                FieldKind::Required => quote! {
+                    *#target = #value;
+                },
+                FieldKind::Optioned | FieldKind::Wrapped => quote! {
+                    ::optionable::OptionableConvert::merge(#target, #value)?;
+                },
+                FieldKind::TriState(_) => quote! {
+                    ::optionable::__private::merge_tri_state_optioned(#target, #value)?;
+                },
+            }
+        });
+        quote! {
+            #(#statements)*
+            Ok(())
+        }
+    }
+
+    /// Adds the bounds required to merge the optioned fields: the optioned type of every non-required field
+    /// has to be its own optioned type.
+    fn push_merge_optioned_bounds(&self, predicates: &mut Vec<WherePredicate>) {
+        for f in &self.fields {
+            let ty = match &f.kind {
+                FieldKind::Required => continue,
+                FieldKind::Optioned => option_inner_type(&f.ty).unwrap_or(&f.ty),
+                FieldKind::Wrapped => &f.ty,
+                FieldKind::TriState(inner_ty) => inner_ty,
+            };
+            let optioned = quote!(<#ty as ::optionable::Optionable>::Optioned);
+            push_deferred_bound(
+                predicates,
+                &optioned,
+                &quote!(::optionable::OptionableConvert<Optioned = #optioned>),
+            );
+        }
+    }
+
+    /// Returns the expressions converting the fields bound to `field_<index>` into their optioned representation.
+    fn optioned_values(&self) -> impl Iterator<Item = TokenStream> + '_ {
+        self.fields.iter().enumerate().map(|(i, f)| {
+            let var = var("field", i);
+            match f.kind {
+                FieldKind::Required => var.to_token_stream(),
+                FieldKind::Optioned => quote!(::optionable::OptionableConvert::into_optioned(#var)),
+                FieldKind::Wrapped => {
+                    quote!(Some(::optionable::OptionableConvert::into_optioned(#var)))
+                }
+                FieldKind::TriState(_) => quote!(::optionable::__private::into_tri_state(#var)),
+            }
+        })
+    }
+}
+
+/// Derives the `OptionableConvert` impl for the given type.
+pub(crate) fn derive_convert(
+    type_ident: &Ident,
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    data: &Data,
+    input_object: bool,
+) -> syn::Result<TokenStream> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (into_optioned, try_from_optioned, merge) = match data {
+        Data::Struct(s) => struct_bodies(type_ident_opt, &s.fields, input_object)?,
+        Data::Enum(e) => enum_bodies(type_ident_opt, e, input_object)?,
+        Data::Union(_) => return crate::error("#[derive(Optionable)] not supported for unions"),
+    };
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::optionable::OptionableConvert for #type_ident #ty_generics #where_clause {
+            fn into_optioned(self) -> #type_ident_opt #ty_generics {
+                #into_optioned
+            }
+
+            fn try_from_optioned(value: #type_ident_opt #ty_generics) -> ::core::result::Result<Self, ::optionable::Error> {
+                #try_from_optioned
+            }
+
+            fn merge(&mut self, other: #type_ident_opt #ty_generics) -> ::core::result::Result<(), ::optionable::Error> {
+                #merge
+            }
+        }
+    })
+}
+
+/// Derives the `OptionableConvert` impl for the optioned type itself. The conversions are the identity,
+/// merging combines two optioned values field by field with the set fields of the merged value taking precedence.
+pub(crate) fn derive_convert_optioned(
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    data: &Data,
+    input_object: bool,
+) -> syn::Result<TokenStream> {
+    let mut predicates = vec![];
+    let merge = match data {
+        Data::Struct(s) => {
+            let fields = FieldsInfo::new(&s.fields, input_object)?;
+            let opt = type_ident_opt.to_token_stream();
+            let (self_merge, other_merge) = (fields.bind(&opt, "self"), fields.bind(&opt, "other"));
+            let merge = fields.merge_optioned();
+            fields.push_merge_optioned_bounds(&mut predicates);
+            quote! {
+                let #self_merge = self;
+                let #other_merge = other;
+                #merge
+            }
+        }
+        Data::Enum(e) => {
+            let mut arms = vec![];
+            for v in &e.variants {
+                let fields = FieldsInfo::new(&v.fields, input_object)?;
+                let ident = &v.ident;
+                let opt = quote!(#type_ident_opt::#ident);
+                let (self_merge, other_merge) =
+                    (fields.bind(&opt, "self"), fields.bind(&opt, "other"));
+                let merge = fields.merge_optioned();
+                fields.push_merge_optioned_bounds(&mut predicates);
+                arms.push(if fields.fields.is_empty() {
+                    quote!((#self_merge, #other_merge) => #merge)
+                } else {
+                    quote!((#self_merge, #other_merge) => {
+                        #merge
+                    })
+                });
+            }
+            // different variants can't be merged, the variant is replaced as a whole in this case
+            if e.variants.len() > 1 {
+                arms.push(quote! {
+                    (_, other) => {
+                        *self = other;
+                        Ok(())
+                    }
+                });
+            }
+            if e.variants.is_empty() {
+                quote!(match *self {})
+            } else {
+                quote! {
+                    match (&mut *self, other) {
+                        #(#arms),*
+                    }
+                }
+            }
+        }
+        Data::Union(_) => return crate::error("#[derive(Optionable)] not supported for unions"),
+    };
+    let mut generics = generics.clone();
+    if !predicates.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::optionable::OptionableConvert for #type_ident_opt #ty_generics #where_clause {
+            fn into_optioned(self) -> Self {
+                self
+            }
+
+            fn try_from_optioned(value: Self) -> ::core::result::Result<Self, ::optionable::Error> {
+                Ok(value)
+            }
+
+            fn merge(&mut self, other: Self) -> ::core::result::Result<(), ::optionable::Error> {
+                #merge
+            }
+        }
+    })
+}
+
+/// Returns the bodies of `into_optioned`, `try_from_optioned` and `merge` for a struct.
+fn struct_bodies(
+    type_ident_opt: &Ident,
+    fields: &Fields,
+    input_object: bool,
+) -> syn::Result<(TokenStream, TokenStream, TokenStream)> {
+    let fields = FieldsInfo::new(fields, input_object)?;
+    let (this, opt) = (quote!(Self), type_ident_opt.to_token_stream());
+    let (self_fields, opt_fields) = (fields.bind(&this, "field"), fields.bind(&opt, "field"));
+    let into_optioned = fields.construct(&opt, fields.optioned_values());
+    let try_from_optioned = fields.try_from_optioned();
+    let (self_merge, other_merge) = (fields.bind(&this, "self"), fields.bind(&opt, "other"));
+    let merge = fields.merge();
+    Ok((
+        quote! {
+            let #self_fields = self;
+            #into_optioned
+        },
+        quote! {
+            let #opt_fields = value;
+            #try_from_optioned
+            Ok(#self_fields)
+        },
+        quote! {
+            let #self_merge = self;
+            let #other_merge = other;
+            #merge
+        },
+    ))
+}
+
+/// Returns the bodies of `into_optioned`, `try_from_optioned` and `merge` for an enum.
+fn enum_bodies(
+    type_ident_opt: &Ident,
+    e: &DataEnum,
+    input_object: bool,
+) -> syn::Result<(TokenStream, TokenStream, TokenStream)> {
+    let variants = e
+        .variants
+        .iter()
+        .map(|v| Ok::<_, syn::Error>((&v.ident, FieldsInfo::new(&v.fields, input_object)?)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (into_optioned, try_from_optioned, merge): (Vec<_>, Vec<_>, Vec<_>) = variants
+        .iter()
+        .map(|(ident, fields)| {
+            let (this, opt) = (quote!(Self::#ident), quote!(#type_ident_opt::#ident));
+            let (self_fields, opt_fields) =
+                (fields.bind(&this, "field"), fields.bind(&opt, "field"));
+            let into_optioned = fields.construct(&opt, fields.optioned_values());
+            let try_from_optioned = fields.try_from_optioned();
+            let (self_merge, other_merge) =
+                (fields.bind(&this, "self"), fields.bind(&opt, "other"));
+            let merge = fields.merge();
+            let try_from_optioned = if try_from_optioned.is_empty() {
+                quote!(#opt_fields => Ok(#self_fields))
+            } else {
+                quote!(#opt_fields => {
+                    #try_from_optioned
+                    Ok(#self_fields)
+                })
+            };
+            let merge = if fields.fields.is_empty() {
+                quote!((#self_merge, #other_merge) => #merge)
+            } else {
+                quote!((#self_merge, #other_merge) => {
+                    #merge
+                })
+            };
+            (
+                quote!(#self_fields => #into_optioned),
+                try_from_optioned,
+                merge,
+            )
+        })
+        .fold((vec![], vec![], vec![]), |mut acc, (a, b, c)| {
+            acc.0.push(a);
+            acc.1.push(b);
+            acc.2.push(c);
+            acc
+        });
+    // different variants can't be merged, the variant is replaced as a whole in this case
+    let merge_other_variant = (variants.len() > 1).then(|| {
+        quote! {
+            (_, other) => {
+                *self = Self::try_from_optioned(other)?;
+                Ok(())
+            }
+        }
+    });
+    let merge = merge.into_iter().chain(merge_other_variant);
+    let merge = if variants.is_empty() {
+        quote!(match *self {})
+    } else {
+        quote! {
+            match (&mut *self, other) {
+                #(#merge),*
+            }
+        }
+    };
+    Ok((
+        quote! {
+            match self {
+                #(#into_optioned),*
+            }
+        },
+        quote! {
+            match value {
+                #(#try_from_optioned),*
+            }
+        },
+        merge,
+    ))
+}
+
+/// Variable identifier `<prefix>_<index>` used to bind the fields.
+pub(crate) fn var(prefix: &str, i: usize) -> Ident {
+    Ident::new(&format!("{prefix}_{i}"), Span::call_site())
+}
+
+/// The name of the field as used in the error messages.
+fn field_name(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => ident.to_string().trim_start_matches("r#").to_owned(),
+        Member::Unnamed(index) => index.index.to_string(),
+    }
+}
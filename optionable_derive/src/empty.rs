@@ -0,0 +1,125 @@
+use crate::attributes::FieldHelperAttributes;
+use crate::derive::{is_option, push_deferred_bound, tri_state_inner_type};
+use darling::FromAttributes;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{Fields, Generics, Visibility, WherePredicate};
+
+/// Derives the `Default` impl (unless forwarded) as well as the `empty` constructor
+/// and the `is_empty` predicate for an optioned struct.
+/// Returns the impls and the `is_empty_optioned` function of the `Optionable` impls.
+///
+/// `Default` requires the types of the required fields to implement `Default`. These bounds are expressed
+/// as higher-ranked trait bounds so that they are only checked when used and don't prevent compilation if they don't hold.
+pub(crate) fn derive_empty(
+    vis: &Visibility,
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    fields: &Fields,
+    default_forwarded: bool,
+    input_object: bool,
+) -> syn::Result<(TokenStream, TokenStream)> {
+    let mut params = vec![];
+    let mut values = vec![];
+    let mut defaults = vec![];
+    let mut default_predicates: Vec<WherePredicate> = vec![];
+    let mut is_empty = vec![];
+    let mut is_empty_reads = vec![];
+    for (i, f) in fields.iter().enumerate() {
+        let attrs = FieldHelperAttributes::from_attributes(&f.attrs)?;
+        let ty = &f.ty;
+        let var = Ident::new(&format!("field_{i}"), Span::call_site());
+        if attrs.required.is_some() {
+            let param = f.ident.clone().unwrap_or_else(|| var.clone());
+            params.push(quote!(#param: #ty));
+            values.push(quote!(#param));
+            defaults.push(quote!(::core::default::Default::default()));
+            push_deferred_bound(&mut default_predicates, ty, &quote!(::core::default::Default));
+            // bound and discarded so that the field counts as read for the dead code analysis
+            is_empty_reads.push(quote!(let _ = #var;));
+        } else if tri_state_inner_type(f, input_object)?.is_some() {
+            values.push(quote!(::async_graphql::MaybeUndefined::Undefined));
+            defaults.push(quote!(::async_graphql::MaybeUndefined::Undefined));
+            is_empty.push(quote!(#var.is_undefined()));
+        } else {
+            values.push(quote!(None));
+            defaults.push(quote!(None));
+            if f.ident.is_some() && is_option(ty) {
+                is_empty.push(quote!(<#ty as ::optionable::Optionable>::is_empty_optioned(#var)));
+            } else {
+                is_empty.push(quote! {
+                    #var.as_ref().is_none_or(<#ty as ::optionable::Optionable>::is_empty_optioned)
+                });
+            }
+        }
+    }
+    let vars = (0..fields.len()).map(|i| Ident::new(&format!("field_{i}"), Span::call_site()));
+    let (values, defaults, pattern) = match fields {
+        Fields::Named(_) => {
+            let members = fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+            (
+                quote!(Self { #(#members: #values),* }),
+                quote!(Self { #(#members: #defaults),* }),
+                quote!(#type_ident_opt { #(#members: #vars),* }),
+            )
+        }
+        Fields::Unnamed(_) => (
+            quote!(Self(#(#values),*)),
+            quote!(Self(#(#defaults),*)),
+            quote!(#type_ident_opt(#(#vars),*)),
+        ),
+        Fields::Unit => (quote!(Self), quote!(Self), quote!(#type_ident_opt)),
+    };
+    let is_empty = if is_empty.is_empty() {
+        quote!(true)
+    } else {
+        quote!(#(#is_empty)&&*)
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let default_impl = (!default_forwarded).then(|| {
+        let mut default_generics = generics.clone();
+        if !default_predicates.is_empty() {
+            default_generics
+                .make_where_clause()
+                .predicates
+                .extend(default_predicates);
+        }
+        let default_where_clause = &default_generics.where_clause;
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics ::core::default::Default for #type_ident_opt #ty_generics #default_where_clause {
+                fn default() -> Self {
+                    #defaults
+                }
+            }
+        }
+    });
+
+    let is_empty_optioned = quote! {
+        fn is_empty_optioned(optioned: &Self::Optioned) -> bool {
+            let #pattern = optioned;
+            #(#is_empty_reads)*
+            #is_empty
+        }
+    };
+    let impls = quote! {
+        #default_impl
+
+        #[automatically_derived]
+        impl #impl_generics #type_ident_opt #ty_generics #where_clause {
+            /// Returns the optioned value with all non-required fields unset.
+            #[must_use]
+            #vis fn empty(#(#params),*) -> Self {
+                #values
+            }
+
+            /// Checks whether no non-required field is set (recursively).
+            #[must_use]
+            #vis fn is_empty(&self) -> bool {
+                <Self as ::optionable::Optionable>::is_empty_optioned(self)
+            }
+        }
+    };
+    Ok((impls, is_empty_optioned))
+}
@@ -0,0 +1,111 @@
+use crate::attributes::FieldHelperAttributes;
+use crate::derive::{is_option, option_inner_type, push_deferred_bound, tri_state_inner_type};
+use darling::FromAttributes;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Data, Fields, Generics, WherePredicate};
+
+/// Derives the `OptionableEnv` impl reading the fields of the optioned type from environment variables
+/// named by the upper case field names.
+///
+/// Required fields are parsed via `FromStr` and have to be set if any other field is set or invalid.
+/// The bounds of the field types are higher-ranked like for the builder (see [`crate::builder::derive_builder`]).
+pub(crate) fn derive_env(
+    type_ident: &Ident,
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    data: &Data,
+    input_object: bool,
+) -> syn::Result<TokenStream> {
+    let Data::Struct(s) = data else {
+        return crate::error("#[optionable(env)] is only supported for structs with named fields");
+    };
+    let Fields::Named(fields) = &s.fields else {
+        return crate::error("#[optionable(env)] is only supported for structs with named fields");
+    };
+
+    let mut reads = vec![];
+    let mut checks = vec![];
+    let mut requires = vec![];
+    let mut values = vec![];
+    let mut predicates: Vec<WherePredicate> = vec![];
+    for (i, f) in fields.named.iter().enumerate() {
+        let attrs = FieldHelperAttributes::from_attributes(&f.attrs)?;
+        let (ident, ty) = (&f.ident, &f.ty);
+        let name = ident
+            .as_ref()
+            .map(|ident| ident.to_string().trim_start_matches("r#").to_uppercase())
+            .unwrap_or_default();
+        let (var, field) = (format_ident!("var_{i}"), format_ident!("field_{i}"));
+        reads.push(quote!(let #var = ::optionable::__private::env_var(prefix, #name);));
+        checks.push(quote!(#field.is_none()));
+        if attrs.required.is_some() {
+            reads.push(
+                quote!(let #field = ::optionable::__private::parse_env::<#ty>(&#var, env, errors);),
+            );
+            requires.push(
+                quote!(let #field = ::optionable::__private::require_env(#field, &#var, env, errors);),
+            );
+            values.push(quote!(#ident: #field?));
+            push_deferred_bound(&mut predicates, ty, &quote!(::core::str::FromStr));
+            push_deferred_bound(
+                &mut predicates,
+                &quote!(<#ty as ::core::str::FromStr>::Err),
+                &quote!(::core::fmt::Display),
+            );
+            continue;
+        }
+        let tri_state = tri_state_inner_type(f, input_object)?;
+        // the optioned type of `Option<T>` is `Option<T::Optioned>`, the variable holds the inner value
+        let inner_ty = option_inner_type(ty)
+            .filter(|_| is_option(ty))
+            .unwrap_or(ty);
+        reads.push(quote! {
+            let #field = <#inner_ty as ::optionable::env::OptionableEnv>::from_env_optioned(&#var, env, errors);
+        });
+        values.push(if tri_state.is_some() {
+            quote!(#ident: ::async_graphql::MaybeUndefined::from(#field.map(Some)))
+        } else {
+            quote!(#ident: #field)
+        });
+        push_deferred_bound(
+            &mut predicates,
+            inner_ty,
+            &quote!(::optionable::env::OptionableEnv),
+        );
+    }
+    let none_set = if checks.is_empty() {
+        quote!(true)
+    } else {
+        quote!(#(#checks)&&*)
+    };
+
+    let mut generics = generics.clone();
+    if !predicates.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::optionable::env::OptionableEnv for #type_ident #ty_generics #where_clause {
+            fn fields_from_env(
+                prefix: &str,
+                env: ::optionable::env::EnvSource<'_>,
+                errors: &mut ::optionable::__private::Vec<::optionable::env::EnvVarError>,
+            ) -> Option<Self::Optioned> {
+                let errors_len = errors.len();
+                #(#reads)*
+                // invalid variables count as set
+                if #none_set && errors.len() == errors_len {
+                    return None;
+                }
+                // required fields only have to be set once any field is set
+                #(#requires)*
+                Some(#type_ident_opt {
+                    #(#values),*
+                })
+            }
+        }
+    })
+}
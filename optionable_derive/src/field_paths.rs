@@ -0,0 +1,355 @@
+use crate::attributes::FieldHelperAttributes;
+use crate::derive::{is_option, tri_state_inner_type};
+use crate::serde::{RenameRule, SerdeAttributes};
+use darling::FromAttributes;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{Data, DataEnum, DeriveInput, Fields, Generics, Visibility};
+
+/// Derives the `OptionableFieldPaths` impl for the given type as well as the `set_field_paths`
+/// and `field_mask` methods of the optioned type.
+/// The path segments are the serialized names according to the `serde` attributes of the given type,
+/// i.e. renames, flattened fields as well as the enum representations.
+pub(crate) fn derive_field_paths(
+    vis: &Visibility,
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    input: &DeriveInput,
+    input_object: bool,
+) -> syn::Result<TokenStream> {
+    let type_ident = &input.ident;
+    let serde = SerdeAttributes::from_attributes(&input.attrs)?;
+    let (body, mask_body, is_field_path) = match &input.data {
+        Data::Struct(s) => {
+            let FieldsPaths {
+                pattern,
+                set_fields,
+                mask_fields,
+                is_field_path,
+            } = fields_paths(
+                &quote!(#type_ident_opt),
+                &s.fields,
+                serde.rename_all,
+                serde.transparent,
+                input_object,
+            )?;
+            let mask_body = if mask_fields.is_empty() {
+                quote!(let _ = mask;)
+            } else {
+                quote! {
+                    let #pattern = optioned;
+                    #(#mask_fields)*
+                }
+            };
+            let body = quote! {
+                let #pattern = optioned;
+                let len = paths.len();
+                #(#set_fields)*
+                ::optionable::__private::set_value_path(prefix, len, paths);
+            };
+            (body, mask_body, is_field_path)
+        }
+        Data::Enum(e) => enum_bodies(type_ident_opt, e, &serde, input_object)?,
+        Data::Union(_) => return crate::error("#[derive(Optionable)] not supported for unions"),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::optionable::OptionableFieldPaths for #type_ident #ty_generics #where_clause {
+            fn set_field_paths_optioned(
+                optioned: &#type_ident_opt #ty_generics,
+                prefix: &str,
+                paths: &mut ::optionable::__private::Vec<::optionable::__private::String>,
+            ) {
+                #body
+            }
+
+            fn mask_optioned(
+                optioned: &mut #type_ident_opt #ty_generics,
+                mask: &::optionable::field_mask::FieldMaskTree,
+            ) {
+                #mask_body
+            }
+
+            fn is_field_path(segments: &[::optionable::field_mask::PathSegment]) -> bool {
+                #is_field_path
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #type_ident_opt #ty_generics #where_clause {
+            /// Returns the paths of the (sub-)fields set by this optioned value.
+            #[must_use]
+            #vis fn set_field_paths(&self) -> ::optionable::__private::Vec<::optionable::__private::String> {
+                <#type_ident #ty_generics as ::optionable::OptionableFieldPaths>::set_field_paths(self)
+            }
+
+            /// Returns the field mask selecting the (sub-)fields set by this optioned value.
+            #[must_use]
+            #vis fn field_mask(&self) -> ::optionable::field_mask::FieldMask {
+                ::optionable::field_mask::FieldMask::from_optioned::<#type_ident #ty_generics>(self)
+            }
+        }
+    })
+}
+
+/// Returns the bodies of `set_field_paths_optioned`, `mask_optioned` and `is_field_path` following
+/// the `serde` enum representation.
+fn enum_bodies(
+    type_ident_opt: &Ident,
+    e: &DataEnum,
+    serde: &SerdeAttributes,
+    input_object: bool,
+) -> syn::Result<(TokenStream, TokenStream, TokenStream)> {
+    if e.variants.is_empty() {
+        return Ok((
+            quote! {
+                let _ = (prefix, paths);
+                match *optioned {}
+            },
+            quote! {
+                let _ = mask;
+                match *optioned {}
+            },
+            quote!(segments.is_empty()),
+        ));
+    }
+    let mut arms = vec![];
+    let mut mask_arms = vec![];
+    let mut has_mask_fields = false;
+    let mut is_field_paths = vec![];
+    if let Some(tag) = serde.tag.as_ref().filter(|_| !serde.untagged) {
+        is_field_paths.push(quote! {
+            ::optionable::__private::strip_field(segments, #tag)
+                .is_some_and(<[::optionable::field_mask::PathSegment]>::is_empty)
+        });
+    }
+    for v in &e.variants {
+        let variant_serde = SerdeAttributes::from_attributes(&v.attrs)?;
+        let ident = &v.ident;
+        let name = variant_serde.rename.unwrap_or_else(|| {
+            serde.rename_all.map_or_else(
+                || ident.to_string(),
+                |rule| rule.apply_to_variant(&ident.to_string()),
+            )
+        });
+        let FieldsPaths {
+            pattern,
+            set_fields,
+            mask_fields,
+            is_field_path,
+        } = fields_paths(
+            &quote!(#type_ident_opt::#ident),
+            &v.fields,
+            variant_serde.rename_all.or(serde.rename_all_fields),
+            false,
+            input_object,
+        )?;
+        let unit = matches!(v.fields, Fields::Unit);
+        // the path segment of the variant content, if any
+        let content_field = match (&serde.tag, &serde.content) {
+            _ if serde.untagged => None,
+            (Some(_), content) => content.clone(),
+            (None, _) => Some(name.clone()),
+        };
+        let body = set_variant_paths(serde, unit, &name, &set_fields);
+        arms.push(quote!(#pattern => { #body }));
+
+        if mask_fields.is_empty() {
+            mask_arms.push(quote!(#pattern => {}));
+        } else {
+            has_mask_fields = true;
+            let select_content = content_field.as_ref().map(
+                |content| quote!(let mask = ::optionable::__private::field_mask(mask, #content);),
+            );
+            mask_arms.push(quote! {
+                #pattern => {
+                    #select_content
+                    #(#mask_fields)*
+                }
+            });
+        }
+
+        if !unit {
+            is_field_paths.push(match content_field {
+                Some(content) => quote! {
+                    ::optionable::__private::strip_field(segments, #content)
+                        .is_some_and(|segments| { #is_field_path })
+                },
+                None => is_field_path,
+            });
+        }
+    }
+    let unused_mask = (!has_mask_fields).then(|| quote!(let _ = mask;));
+    Ok((
+        quote! {
+            match optioned {
+                #(#arms)*
+            }
+        },
+        quote! {
+            #unused_mask
+            match optioned {
+                #(#mask_arms)*
+            }
+        },
+        quote!(segments.is_empty() #(|| #is_field_paths)*),
+    ))
+}
+
+/// Returns the statements recording the set (sub-)fields of a variant according to the `serde` enum representation.
+fn set_variant_paths(
+    serde: &SerdeAttributes,
+    unit: bool,
+    name: &str,
+    set_fields: &[TokenStream],
+) -> TokenStream {
+    // the path of the variant itself is recorded if none of its fields is set,
+    // unless the tag is recorded at the same path anyway
+    let set_tag_fields = quote!(#(#set_fields)*);
+    let set_fields = quote! {
+        let len = paths.len();
+        #(#set_fields)*
+        ::optionable::__private::set_value_path(prefix, len, paths);
+    };
+    match (&serde.tag, &serde.content) {
+        // unit variants are represented by their name or `null` (untagged) at the path of the enum
+        _ if unit && (serde.untagged || serde.tag.is_none()) => {
+            quote!(paths.push(::optionable::__private::ToOwned::to_owned(prefix));)
+        }
+        _ if serde.untagged => set_fields,
+        (Some(tag), content) => {
+            let set_content = match content {
+                _ if unit => None,
+                Some(content) => Some(quote! {
+                    let prefix = &::optionable::__private::field_path(prefix, #content);
+                    #set_fields
+                }),
+                None => Some(set_tag_fields),
+            };
+            quote! {
+                paths.push(::optionable::__private::field_path(prefix, #tag));
+                #set_content
+            }
+        }
+        (None, _) => quote! {
+            let prefix = &::optionable::__private::field_path(prefix, #name);
+            #set_fields
+        },
+    }
+}
+
+/// The code generated for the fields of a struct or enum variant.
+struct FieldsPaths {
+    /// Pattern binding the non-required fields to `field_<index>`.
+    pattern: TokenStream,
+    /// Statements recording the set (sub-)fields.
+    set_fields: Vec<TokenStream>,
+    /// Statements applying the `mask` to the fields.
+    mask_fields: Vec<TokenStream>,
+    /// Expression checking whether the `segments` address the value itself or one of its (sub-)fields.
+    is_field_path: TokenStream,
+}
+
+/// Returns the code for the fields. Flattened fields and the field of a newtype are located at the path
+/// of their parent. Required fields are kept as a whole and can only be addressed themselves.
+fn fields_paths(
+    path: &TokenStream,
+    fields: &Fields,
+    rename_rule: Option<RenameRule>,
+    transparent: bool,
+    input_object: bool,
+) -> syn::Result<FieldsPaths> {
+    let newtype = matches!(fields, Fields::Unnamed(f) if f.unnamed.len() == 1);
+    let mut bindings = vec![];
+    let mut set_fields = vec![];
+    let mut mask_fields = vec![];
+    let mut is_field_paths = vec![];
+    for (i, f) in fields.iter().enumerate() {
+        let attrs = FieldHelperAttributes::from_attributes(&f.attrs)?;
+        let serde = SerdeAttributes::from_attributes(&f.attrs)?;
+        let inline = serde.flatten || transparent || newtype;
+        let name = match (&f.ident, serde.rename) {
+            (_, Some(rename)) => rename,
+            (Some(ident), None) => {
+                let ident = ident.to_string();
+                let ident = ident.trim_start_matches("r#");
+                rename_rule.map_or_else(|| ident.to_owned(), |rule| rule.apply_to_field(ident))
+            }
+            (None, None) => i.to_string(),
+        };
+        if attrs.required.is_some() {
+            if !inline {
+                is_field_paths.push(quote! {
+                    ::optionable::__private::strip_field(segments, #name)
+                        .is_some_and(<[::optionable::field_mask::PathSegment]>::is_empty)
+                });
+            }
+            bindings.push(None);
+            continue;
+        }
+        let var = Ident::new(&format!("field_{i}"), Span::call_site());
+        let ty = &f.ty;
+        // the optioned type of a non-required field is `Option<T::Optioned>` unless `T` is an `Option` itself
+        let ty = if f.ident.is_some() && is_option(ty) {
+            quote!(#ty)
+        } else {
+            quote!(Option<#ty>)
+        };
+        let (prefix, mask) = if inline {
+            is_field_paths.push(quote! {
+                <#ty as ::optionable::OptionableFieldPaths>::is_field_path(segments)
+            });
+            (quote!(prefix), quote!(Some(mask)))
+        } else {
+            is_field_paths.push(quote! {
+                ::optionable::__private::strip_field(segments, #name)
+                    .is_some_and(<#ty as ::optionable::OptionableFieldPaths>::is_field_path)
+            });
+            (
+                quote!(&::optionable::__private::field_path(prefix, #name)),
+                quote!(mask.field(#name)),
+            )
+        };
+        if let Some(inner_ty) = tri_state_inner_type(f, input_object)? {
+            set_fields.push(quote! {
+                ::optionable::__private::set_tri_state_paths::<#inner_ty>(#var, #prefix, paths);
+            });
+            mask_fields.push(quote! {
+                ::optionable::__private::mask_tri_state::<#inner_ty>(#var, #mask);
+            });
+        } else {
+            set_fields.push(quote! {
+                <#ty as ::optionable::OptionableFieldPaths>::set_field_paths_optioned(#var, #prefix, paths);
+            });
+            mask_fields.push(quote! {
+                ::optionable::__private::mask_field::<#ty, _>(#var, #mask);
+            });
+        }
+        bindings.push(Some(var));
+    }
+    let pattern = match fields {
+        Fields::Named(_) => {
+            let bindings = fields
+                .iter()
+                .zip(bindings)
+                .filter_map(|(f, var)| var.map(|var| (&f.ident, var)))
+                .map(|(ident, var)| quote!(#ident: #var));
+            quote!(#path { #(#bindings,)* .. })
+        }
+        Fields::Unnamed(_) => {
+            let bindings = bindings
+                .into_iter()
+                .map(|var| var.map_or_else(|| quote!(_), |var| quote!(#var)));
+            quote!(#path(#(#bindings),*))
+        }
+        Fields::Unit => quote!(#path),
+    };
+    Ok(FieldsPaths {
+        pattern,
+        set_fields,
+        mask_fields,
+        is_field_path: quote!(segments.is_empty() #(|| #is_field_paths)*),
+    })
+}
@@ -0,0 +1,167 @@
+use crate::attributes::FieldHelperAttributes;
+use crate::derive::{is_option, option_inner_type, push_deferred_bound, tri_state_inner_type};
+use crate::serde::SerdeAttributes;
+use crate::validate::field_name;
+use darling::FromAttributes;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Generics, Type, Visibility, WherePredicate};
+
+/// Derives the `OptionableFieldsV1` impl inserting the `FieldsV1` entries of the set fields.
+/// Lists are handled according to their `list_type`, fields with `list_type`/`map_type = "atomic"`
+/// are managed as a whole. Required fields are not recorded like for the field paths.
+pub(crate) fn derive_fields_v1(
+    vis: &Visibility,
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    input: &DeriveInput,
+    input_object: bool,
+) -> syn::Result<TokenStream> {
+    let type_ident = &input.ident;
+    let Data::Struct(s) = &input.data else {
+        return crate::error(
+            "#[optionable(fields_v1)] is only supported for structs with named fields",
+        );
+    };
+    let Fields::Named(fields) = &s.fields else {
+        return crate::error(
+            "#[optionable(fields_v1)] is only supported for structs with named fields",
+        );
+    };
+    let serde = SerdeAttributes::from_attributes(&input.attrs)?;
+
+    let mut bindings = vec![];
+    let mut entries = vec![];
+    let mut predicates: Vec<WherePredicate> = vec![];
+    for (i, f) in fields.named.iter().enumerate() {
+        let attrs = FieldHelperAttributes::from_attributes(&f.attrs)?;
+        attrs.check_merge_strategy()?;
+        if attrs.required.is_some() {
+            continue;
+        }
+        let (ident, ty) = (&f.ident, &f.ty);
+        let var = Ident::new(&format!("field_{i}"), Span::call_site());
+        // flattened fields and the field of a transparent struct are located at their parent
+        let (target, leaf) = match field_name(f, i, serde.rename_all)? {
+            Some(name) if !serde.transparent => (
+                quote!(::optionable::__private::fields_v1_field(fields, #name)),
+                quote!(::optionable::__private::fields_v1_field(fields, #name);),
+            ),
+            _ => (quote!(fields), quote!()),
+        };
+        let tri_state_ty = tri_state_inner_type(f, input_object)?;
+        // the optioned type of `Option<T>` is `Option<T::Optioned>`, the entries are those of the inner value
+        let inner_ty = tri_state_ty
+            .or_else(|| option_inner_type(ty).filter(|_| is_option(ty)))
+            .unwrap_or(ty);
+        let insert = insert_entries(&attrs, inner_ty, &target, &leaf, &mut predicates)?;
+        bindings.push(quote!(#ident: #var));
+        entries.push(if tri_state_ty.is_some() {
+            // fields explicitly set to `null` are managed as well as they unset the field
+            quote! {
+                match #var {
+                    ::async_graphql::MaybeUndefined::Value(value) => { #insert }
+                    ::async_graphql::MaybeUndefined::Null => { #leaf }
+                    ::async_graphql::MaybeUndefined::Undefined => {}
+                }
+            }
+        } else {
+            quote! {
+                if let Some(value) = #var {
+                    #insert
+                }
+            }
+        });
+    }
+    let body = if bindings.is_empty() {
+        quote!(let _ = (optioned, fields);)
+    } else {
+        quote! {
+            let #type_ident_opt { #(#bindings,)* .. } = optioned;
+            #(#entries)*
+        }
+    };
+
+    let mut generics = generics.clone();
+    if !predicates.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::optionable::fields_v1::OptionableFieldsV1 for #type_ident #ty_generics #where_clause {
+            fn fields_v1_optioned(
+                optioned: &#type_ident_opt #ty_generics,
+                fields: &mut ::optionable::__private::JsonMap,
+            ) {
+                #body
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #type_ident_opt #ty_generics #where_clause {
+            /// Returns the `FieldsV1` of the (sub-)fields set by this optioned value.
+            #[must_use]
+            #vis fn fields_v1(&self) -> ::optionable::fields_v1::FieldsV1 {
+                <#type_ident #ty_generics as ::optionable::fields_v1::OptionableFieldsV1>::fields_v1(self)
+            }
+        }
+    })
+}
+
+/// Returns the statements inserting the entries of the field value bound to `value` into `target`
+/// according to the list/map type of the field, `leaf` inserting the field itself.
+fn insert_entries(
+    attrs: &FieldHelperAttributes,
+    inner_ty: &Type,
+    target: &TokenStream,
+    leaf: &TokenStream,
+    predicates: &mut Vec<WherePredicate>,
+) -> syn::Result<TokenStream> {
+    let list_type = attrs.list_type.as_ref().map(syn::LitStr::value);
+    let atomic = list_type.as_deref() == Some("atomic")
+        || attrs.map_type.as_ref().is_some_and(|t| t.value() == "atomic");
+    Ok(match list_type.as_deref() {
+        _ if atomic => quote! {
+            let _ = value;
+            #leaf
+        },
+        Some(list_type @ ("set" | "map")) => {
+            let element_ty = list_element_type(inner_ty)?;
+            push_deferred_bound(
+                predicates,
+                &quote!(<#element_ty as ::optionable::Optionable>::Optioned),
+                &quote!(::optionable::__private::Serialize),
+            );
+            if list_type == "set" {
+                quote!(::optionable::__private::fields_v1_list_set::<#element_ty>(value, #target);)
+            } else {
+                push_deferred_bound(
+                    predicates,
+                    element_ty,
+                    &quote!(::optionable::fields_v1::OptionableFieldsV1),
+                );
+                let keys = &attrs.list_map_key;
+                quote!(::optionable::__private::fields_v1_list_map::<#element_ty>(value, &[#(#keys),*], #target);)
+            }
+        }
+        _ => {
+            push_deferred_bound(
+                predicates,
+                inner_ty,
+                &quote!(::optionable::fields_v1::OptionableFieldsV1),
+            );
+            quote!(<#inner_ty as ::optionable::fields_v1::OptionableFieldsV1>::fields_v1_optioned(value, #target);)
+        }
+    })
+}
+
+/// Returns the element type of a list field with `list_type = "set"` or `"map"`, e.g. `T` for `Vec<T>`.
+fn list_element_type(ty: &Type) -> syn::Result<&Type> {
+    option_inner_type(ty).ok_or_else(|| {
+        syn::Error::new_spanned(
+            ty,
+            r#"list_type = "set" and "map" require a list type like `Vec<T>`"#,
+        )
+    })
+}
@@ -6,14 +6,49 @@
 //! This is only a separate crate as derive macros have to be a separate crate.
 use proc_macro::TokenStream;
 use std::fmt;
+mod arbitrary;
+mod args;
+// the darling derives expand to a loop triggering `needless_continue`
+#[allow(clippy::needless_continue)]
+mod attributes;
+mod builder;
+mod changeset;
+mod convert;
 mod derive;
+mod empty;
+mod env;
+mod field_paths;
+mod fields_v1;
+mod proptest;
+mod serde;
+mod strategic_merge;
+mod three_way;
+mod utoipa;
+mod validate;
 
 /// Derive macro to derive the `Optionable` trait for structs/enums recursively. All non-required
 /// fields have to implement the `Optionable` trait. This trait is already implemented by this library
 /// for many primitive types, wrapper and container types.
+/// Also derives the `OptionableConvert` trait to convert between the type and its optioned type
+/// and to merge optioned values into it as well as the `OptionableFieldPaths` trait to list the set fields
+/// and to apply and validate field masks.
+/// For structs the optioned type implements `Default` (unless forwarded via `derive`) and gets an
+/// `empty` constructor (taking the required fields as parameters) and a recursive `is_empty` check.
 ///
 /// ### Type-level attributes (on the struct/enum level)
 /// - **`derive`**: Allows to specify derive attributes that should be attached to the generate optioned struct/enum.
+///   `utoipa::ToSchema` is not forwarded but implemented directly for the optioned type as the `utoipa` derive
+///   can't resolve the optioned field types.
+///   With `async_graphql::InputObject` forwarded, named `Option` fields are represented as
+///   `async_graphql::MaybeUndefined` to distinguish absent fields from fields explicitly set to `null`
+///   (requires the `async-graphql` feature of `optionable`).
+///   `diesel::AsChangeset` is not forwarded either but implemented directly (see `table`).
+///   `clap::Args` is implemented directly as well, flattening nested structs into flags prefixed by the
+///   parent field name (requires the `clap` feature of `optionable`).
+///   With `Validate` of `validator` or `garde` forwarded, the `validate`/`garde` field attributes are kept.
+///   With `serde::Serialize` or `serde::Deserialize` forwarded, the `serde` attributes naming the fields and variants
+///   (`rename`, `alias`, `rename_all`, `rename_all_fields`, `flatten`, `transparent` and the enum representation)
+///   are kept so that the optioned type is serialized with the names of the original type.
 ///   Example:
 ///   ```rust,ignore
 ///   #[derive(optionable)]
@@ -28,6 +63,175 @@ mod derive;
 ///   #[optionable(suffix="Ac")]
 ///   struct MyStruct{}
 ///   ```
+/// - **`bound`**: By default an `Optionable` type bound is added for every generic type parameter that is used in
+///   a non-required field (ignoring usages within `PhantomData`). The inferred bounds can be replaced by custom
+///   where clause predicates, e.g. `#[optionable(bound = "T: Optionable")]` or `#[optionable(bound = "")]`.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(bound = "")]
+///   struct MyStruct<M>{
+///     marker: Box<PhantomData<M>>, // `M` does not need to implement `Optionable`
+///   }
+///   ```
+/// - **`no_convert`**: Skips the implementation of the `OptionableConvert` trait which is derived by default.
+///   Needed if not all non-required fields implement `OptionableConvert` or without the `alloc` feature of `optionable`.
+///   With a `bound` attribute the types of the affected fields have to implement `OptionableConvert`
+///   for the conversion to be available.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(no_convert)]
+///   struct MyStruct<'a>{
+///     name: &'a str,
+///   }
+///   ```
+/// - **`no_field_paths`**: Skips the implementation of the `OptionableFieldPaths` trait (and hence the field mask support)
+///   which is derived by default.
+///   Needed if not all non-required fields implement `OptionableFieldPaths` or without the `alloc` feature of `optionable`.
+///   The path segments are taken from the
+///   `serde` attributes (`rename`, `rename_all`, `rename_all_fields`, `flatten`, `transparent` and the enum representation).
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(no_field_paths)]
+///   struct MyStruct{
+///     external: ExternalType,
+///   }
+///   ```
+/// - **`builder`**: Implements fluent `with_<field>` setters (plus `with_<field>_fn` closure variants
+///   for building nested values in place and, unless `no_convert` is set, `with_<field>_value` variants taking the
+///   full value) for the optioned type. Only supported for structs with named fields.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(builder)]
+///   struct MyStruct{
+///     name: String,
+///   }
+///   let patch = MyStructOpt::default().with_name("a".to_owned());
+///   ```
+/// - **`table`**: The table updated by the optioned type for `diesel::AsChangeset` (listed in `derive`) and `sqlx`.
+///   For `diesel` it is the path of the module generated by `diesel::table!`, for `sqlx` the SQL table name.
+///   Only set fields are part of the changeset, required fields are skipped as they identify the updated row.
+///   Only supported for structs with named fields.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(derive(AsChangeset), table = "users")]
+///   struct User{
+///     #[optionable(required)]
+///     id: i32,
+///     name: String,
+///   }
+///   diesel::update(users::table.find(patch.id)).set(patch).execute(conn)?;
+///   ```
+/// - **`sqlx`**: Implements the `update_query` method for the optioned type building the `UPDATE` statement
+///   of the `table` via `sqlx`. Only the columns of set fields are updated for the rows matching all required fields.
+///   Requires the `sqlx` feature of `optionable`.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(sqlx, table = "users")]
+///   struct User{
+///     #[optionable(required)]
+///     id: i32,
+///     name: String,
+///   }
+///   // UPDATE users SET name = ? WHERE id = ?
+///   patch.update_query::<Sqlite>().unwrap().build().execute(&pool).await?;
+///   ```
+/// - **`env`**: Implements the `OptionableEnv` trait to read the optioned type from environment variables named by
+///   the upper case field names, e.g. `APP_SPEC__REPLICAS` for the `replicas` field of the nested `spec` field
+///   with prefix `APP`. Required fields are parsed via `FromStr`. Only supported for structs with named fields.
+///   Requires the `env` feature of `optionable`.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(env)]
+///   struct Config{
+///     replicas: u32,
+///   }
+///   let config = Config::from_env("APP")?; // reads `APP_REPLICAS`
+///   ```
+/// - **`validate`**: Implements the `OptionableValidate` trait checking the constraints of the set fields declared
+///   via the field-level `validate` attribute. Only supported for structs with named fields.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(validate)]
+///   struct Config{
+///     #[optionable(validate(range(min = 1)))]
+///     replicas: u32,
+///   }
+///   Config::validate_optioned(&patch)?;
+///   ```
+/// - **`proptest`**: Implements the `OptionableStrategy` trait providing `proptest` strategies for sparse optioned
+///   values and for patches of a given value as well as `proptest::arbitrary::Arbitrary` for the optioned type
+///   (which needs to implement `Debug`). Requires the `proptest` feature of `optionable`.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(proptest, derive(Debug))]
+///   struct Config{
+///     replicas: u32,
+///   }
+///   proptest!(|(patch in Config::patch_strategy(&config, 0.5))| { ... });
+///   ```
+/// - **`arbitrary`**: Implements the `OptionableArbitrary` trait generating sparse optioned values and patches of a
+///   given value from unstructured bytes as well as `arbitrary::Arbitrary` for the optioned type, e.g. for fuzzing.
+///   Requires the `arbitrary` feature of `optionable`.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(arbitrary)]
+///   struct Config{
+///     replicas: u32,
+///   }
+///   let patch = Config::arbitrary_patch(&config, &mut Unstructured::new(data), 0.5)?;
+///   ```
+/// - **`three_way`**: Implements the `OptionableThreeWayMerge` trait merging the changes of two optioned values
+///   relative to a common base and reporting the paths of conflicting fields. The optioned type needs to implement
+///   `PartialEq`, e.g. via `derive(PartialEq)`. Requires the `alloc` feature of `optionable`.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(three_way, derive(PartialEq))]
+///   struct Config{
+///     replicas: u32,
+///   }
+///   let merged = Config::three_way_merge(Some(&last_applied), live.into_optioned(), desired);
+///   ```
+/// - **`fields_v1`**: Implements the `OptionableFieldsV1` trait computing the Kubernetes `FieldsV1` representation
+///   of the set fields, following the `serde` renames and the `list_type`, `list_map_key` and `map_type` field
+///   attributes. The element types of lists with `list_type = "set"` or `"map"` need to implement
+///   `serde::Serialize` for their optioned types. Only supported for structs with named fields.
+///   Requires the `serde_json` feature of `optionable`.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(fields_v1, derive(Serialize))]
+///   struct PodSpec{
+///     #[optionable(list_type = "map", list_map_key = "name")]
+///     containers: Vec<Container>,
+///   }
+///   let fields = spec.fields_v1(); // {"f:containers":{"k:{\"name\":\"nginx\"}":{".":{},...}}}
+///   ```
+/// - **`strategic_merge`**: Implements the `OptionableStrategicMerge` trait creating and applying Kubernetes
+///   strategic merge patches according to the `patch_strategy` and `patch_merge_key` field attributes. The fields
+///   are named according to the `serde` attributes, which are kept for the serialized optioned type.
+///   The optioned type needs to implement `serde::Serialize` (and `serde::Deserialize` to apply patches).
+///   Only supported for structs with named fields. Requires the `serde_json` feature of `optionable`.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(strategic_merge, derive(Serialize, Deserialize))]
+///   struct PodSpec{
+///     #[optionable(patch_strategy = "merge", patch_merge_key = "name")]
+///     containers: Vec<Container>,
+///   }
+///   let patch = PodSpec::strategic_merge_patch(&original, &modified)?;
+///   ```
 /// ### Field-level attributes (for structs and struct-typed enum variants)
 /// - **`required`**: The annotated field will be kept as is and won't be transformed into some optional variant
 ///   for the derived optioned Struct.
@@ -40,6 +244,71 @@ mod derive;
 ///     number: u32; // will also be a u32 in the derived `MyStructOpt`.
 ///   }
 ///   ```
+/// - **`bound`**: Replaces the inferred type bounds for the generic type parameters used by this field
+///   with the given where clause predicates, e.g. `#[optionable(bound = "T: Optionable")]`.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   struct MyStruct<T, M>{
+///     value: T, // `T: Optionable` is inferred
+///     #[optionable(bound = "")]
+///     marker: Box<PhantomData<M>>, // no bound is inferred for `M`
+///   }
+///   ```
+/// - **`list_type`**, **`list_map_key`**, **`map_type`**: Kubernetes server-side apply merge strategies of the field,
+///   added as `x-kubernetes-list-type`, `x-kubernetes-list-map-keys` and `x-kubernetes-map-type` extensions to the
+///   JSON schema if `JsonSchema` is part of the forwarded `derive`s and used for the `FieldsV1` of `fields_v1`.
+///   `list_type` is one of `"atomic"`, `"set"` or `"map"`, `list_map_key` (can be repeated) requires `list_type = "map"` and `map_type` is one of
+///   `"atomic"` or `"granular"`.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(derive(JsonSchema))]
+///   struct MyStruct{
+///     #[optionable(list_type = "map", list_map_key = "name")]
+///     ports: Vec<Port>,
+///   }
+///   ```
+/// - **`patch_strategy`**, **`patch_merge_key`**: Kubernetes strategic merge patch strategies of the field,
+///   added as `x-kubernetes-patch-strategy` and `x-kubernetes-patch-merge-key` extensions to the JSON schema if
+///   `JsonSchema` is part of the forwarded `derive`s and used for the patches of `strategic_merge`.
+///   `patch_strategy` is one of `"merge"`, `"replace"`, `"retainKeys"` or `"merge,retainKeys"`,
+///   `patch_merge_key` requires `patch_strategy = "merge"`.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(strategic_merge, derive(Serialize, Deserialize))]
+///   struct MyStruct{
+///     #[optionable(patch_strategy = "merge", patch_merge_key = "container_port")]
+///     ports: Vec<Port>,
+///   }
+///   ```
+/// - **`column`**: The column of the field for `diesel::AsChangeset` and `sqlx`, defaults to the field name.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(sqlx, table = "users")]
+///   struct User{
+///     #[optionable(column = "user_name")]
+///     name: String,
+///   }
+///   ```
+/// - **`validate`**: Constraints of the field checked by `OptionableValidate` if the field is set:
+///   `range(min = .., max = ..)` and `length(min = .., max = ..)` with inclusive bounds, `regex = ".."`
+///   (requires the `regex` feature of `optionable`), `custom = path` (can be repeated) for functions taking the value
+///   and returning `Result<(), String>` and `nested` to validate the optioned value of the field via its own
+///   `OptionableValidate` impl.
+///   Example:
+///   ```rust,ignore
+///   #[derive(optionable)]
+///   #[optionable(validate)]
+///   struct Config{
+///     #[optionable(validate(length(max = 63), regex = "^[a-z-]+$"))]
+///     name: String,
+///     #[optionable(validate(nested))]
+///     spec: Spec,
+///   }
+///   ```
 #[proc_macro_derive(Optionable, attributes(optionable))]
 pub fn derive_optionable(input: TokenStream) -> TokenStream {
     derive::derive_optionable(input.into())
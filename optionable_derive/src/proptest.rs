@@ -0,0 +1,237 @@
+use crate::convert::{var, FieldKind, FieldsInfo};
+use crate::derive::push_deferred_bound;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{parse_quote, Data, DataEnum, GenericParam, Generics, WherePredicate};
+
+/// Derives the `OptionableStrategy` impl for the type and the `proptest::arbitrary::Arbitrary` impl for the
+/// optioned type.
+///
+/// The field strategies are combined as nested pairs as `proptest` only implements `Strategy` for tuples up to
+/// a fixed length. Patches keep the required fields and replace enum values by a completely set variant
+/// as alternative to patching the present variant.
+pub(crate) fn derive_proptest(
+    type_ident: &Ident,
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    data: &Data,
+    input_object: bool,
+) -> syn::Result<TokenStream> {
+    let mut predicates: Vec<WherePredicate> = vec![];
+    let (optioned, patch, required_only) = match data {
+        Data::Struct(s) => {
+            let fields = FieldsInfo::new(&s.fields, input_object)?;
+            let strategies = FieldStrategies::new(&fields, &mut predicates);
+            let constructor = fields.bind(&quote!(#type_ident_opt), "field");
+            let (optioned, patch) = (
+                strategies.optioned(&constructor),
+                strategies.patch(&constructor),
+            );
+            let value = fields.bind(&quote!(Self), "field");
+            (
+                optioned,
+                quote! {
+                    let #value = value;
+                    #patch
+                },
+                fields.required_only(),
+            )
+        }
+        Data::Enum(e) => enum_strategies(type_ident_opt, e, input_object, &mut predicates)?,
+        Data::Union(_) => {
+            return crate::error("#[optionable(proptest)] is not supported for unions")
+        }
+    };
+
+    // the density is unused without non-required fields
+    let density = if required_only {
+        quote!(_density)
+    } else {
+        quote!(density)
+    };
+
+    let mut generics = generics.clone();
+    // the strategies are boxed and hence `'static`
+    for param in &generics.params {
+        if let GenericParam::Type(param) = param {
+            let ident = &param.ident;
+            predicates.push(parse_quote!(#ident: 'static));
+        }
+    }
+    if !predicates.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::optionable::proptest::OptionableStrategy for #type_ident #ty_generics #where_clause {
+            fn optioned_strategy(
+                #density: f64,
+            ) -> ::optionable::__private::proptest::strategy::BoxedStrategy<#type_ident_opt #ty_generics> {
+                #optioned
+            }
+
+            fn patch_strategy(
+                value: &Self,
+                #density: f64,
+            ) -> ::optionable::__private::proptest::strategy::BoxedStrategy<#type_ident_opt #ty_generics> {
+                #patch
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::optionable::__private::proptest::arbitrary::Arbitrary for #type_ident_opt #ty_generics #where_clause {
+            type Parameters = ();
+            type Strategy = ::optionable::__private::proptest::strategy::BoxedStrategy<Self>;
+
+            fn arbitrary_with((): ()) -> Self::Strategy {
+                <#type_ident #ty_generics as ::optionable::proptest::OptionableStrategy>::optioned_strategy(
+                    ::optionable::proptest::DEFAULT_DENSITY,
+                )
+            }
+        }
+    })
+}
+
+/// Returns the bodies of `optioned_strategy` and `patch_strategy` for an enum and whether all fields
+/// of all variants are required.
+fn enum_strategies(
+    type_ident_opt: &Ident,
+    e: &DataEnum,
+    input_object: bool,
+    predicates: &mut Vec<WherePredicate>,
+) -> syn::Result<(TokenStream, TokenStream, bool)> {
+    if e.variants.is_empty() {
+        return crate::error("#[optionable(proptest)] is not supported for enums without variants");
+    }
+    let mut variants = vec![];
+    let mut arms = vec![];
+    let mut required_only = true;
+    for v in &e.variants {
+        let ident = &v.ident;
+        let fields = FieldsInfo::new(&v.fields, input_object)?;
+        required_only &= fields.required_only();
+        let strategies = FieldStrategies::new(&fields, predicates);
+        let constructor = fields.bind(&quote!(#type_ident_opt::#ident), "field");
+        variants.push(strategies.optioned(&constructor));
+        let patch = strategies.patch(&constructor);
+        let value = fields.bind(&quote!(Self::#ident), "field");
+        arms.push(quote! {
+            #value => ::optionable::__private::proptest::strategy::Strategy::boxed(
+                ::optionable::__private::proptest::strategy::Union::new([
+                    #patch,
+                    <Self as ::optionable::proptest::OptionableStrategy>::optioned_strategy(1.0),
+                ])
+            )
+        });
+    }
+    Ok((
+        quote! {
+            ::optionable::__private::proptest::strategy::Strategy::boxed(
+                ::optionable::__private::proptest::strategy::Union::new([#(#variants),*])
+            )
+        },
+        quote! {
+            match value {
+                #(#arms),*
+            }
+        },
+        required_only,
+    ))
+}
+
+/// The strategies of the fields of a struct or enum variant, the patch strategies refer to the present
+/// values bound to `field_<index>`.
+struct FieldStrategies {
+    optioned: Vec<TokenStream>,
+    patch: Vec<TokenStream>,
+}
+
+impl FieldStrategies {
+    /// Collects the field strategies and adds the bounds of the field types to `predicates`.
+    fn new(fields: &FieldsInfo, predicates: &mut Vec<WherePredicate>) -> Self {
+        let mut strategies = Self {
+            optioned: vec![],
+            patch: vec![],
+        };
+        for (i, f) in fields.fields.iter().enumerate() {
+            let (ty, field) = (&f.ty, var("field", i));
+            let (bounded_ty, bound) = match &f.kind {
+                FieldKind::Required => {
+                    strategies
+                        .optioned
+                        .push(quote!(::optionable::__private::proptest::arbitrary::any::<#ty>()));
+                    strategies.patch.push(quote! {
+                        ::optionable::__private::proptest::strategy::Just(::core::clone::Clone::clone(#field))
+                    });
+                    (
+                        ty,
+                        quote!(::optionable::__private::proptest::arbitrary::Arbitrary + ::core::clone::Clone),
+                    )
+                }
+                FieldKind::Optioned => {
+                    strategies.optioned.push(quote! {
+                        <#ty as ::optionable::proptest::OptionableStrategy>::optioned_strategy(density)
+                    });
+                    strategies.patch.push(quote! {
+                        <#ty as ::optionable::proptest::OptionableStrategy>::patch_strategy(#field, density)
+                    });
+                    (ty, quote!(::optionable::proptest::OptionableStrategy))
+                }
+                FieldKind::Wrapped => {
+                    strategies.optioned.push(quote! {
+                        <Option<#ty> as ::optionable::proptest::OptionableStrategy>::optioned_strategy(density)
+                    });
+                    strategies.patch.push(
+                        quote!(::optionable::__private::patch_field_strategy::<#ty>(#field, density)),
+                    );
+                    (ty, quote!(::optionable::proptest::OptionableStrategy))
+                }
+                FieldKind::TriState(inner_ty) => {
+                    strategies.optioned.push(
+                        quote!(::optionable::__private::tri_state_strategy::<#inner_ty>(density)),
+                    );
+                    strategies.patch.push(quote! {
+                        ::optionable::__private::patch_tri_state_strategy::<#inner_ty>(#field, density)
+                    });
+                    (&**inner_ty, quote!(::optionable::proptest::OptionableStrategy))
+                }
+            };
+            push_deferred_bound(predicates, bounded_ty, &bound);
+        }
+        strategies
+    }
+
+    /// Returns the boxed strategy for optioned values built by `constructor`.
+    fn optioned(&self, constructor: &TokenStream) -> TokenStream {
+        Self::combine(&self.optioned, constructor)
+    }
+
+    /// Returns the boxed strategy for patches of the present values built by `constructor`.
+    fn patch(&self, constructor: &TokenStream) -> TokenStream {
+        Self::combine(&self.patch, constructor)
+    }
+
+    /// Combines the field strategies as nested pairs and maps them to the values built by `constructor`
+    /// from the fields bound to `field_<index>`.
+    fn combine(strategies: &[TokenStream], constructor: &TokenStream) -> TokenStream {
+        let (strategy, pattern) = strategies.iter().enumerate().rev().fold(
+            (
+                quote!(::optionable::__private::proptest::strategy::Just(())),
+                quote!(()),
+            ),
+            |(strategy, pattern), (i, field_strategy)| {
+                let field = var("field", i);
+                (
+                    quote!((#field_strategy, #strategy)),
+                    quote!((#field, #pattern)),
+                )
+            },
+        );
+        quote! {
+            ::optionable::__private::proptest::strategy::Strategy::boxed(
+                ::optionable::__private::proptest::strategy::Strategy::prop_map(#strategy, |#pattern| #constructor)
+            )
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, LitStr, Meta, Token};
+
+/// The `serde` attributes of a container naming and laying out the serialized optioned type like the original type.
+pub(crate) const CONTAINER_NAMING: &[&str] = &[
+    "rename_all",
+    "rename_all_fields",
+    "tag",
+    "content",
+    "untagged",
+    "transparent",
+];
+/// The `serde` attributes of an enum variant naming the serialized optioned variant like the original variant.
+pub(crate) const VARIANT_NAMING: &[&str] = &["rename", "rename_all", "alias", "untagged"];
+/// The `serde` attributes of a field naming the serialized optioned field like the original field.
+pub(crate) const FIELD_NAMING: &[&str] = &["rename", "alias", "flatten"];
+
+/// The `serde` attributes determining the serialized names and layout of a type, variant or field.
+/// All other `serde` attributes are ignored.
+#[derive(Default)]
+pub(crate) struct SerdeAttributes {
+    pub(crate) rename: Option<String>,
+    pub(crate) rename_all: Option<RenameRule>,
+    pub(crate) rename_all_fields: Option<RenameRule>,
+    pub(crate) flatten: bool,
+    pub(crate) transparent: bool,
+    pub(crate) untagged: bool,
+    pub(crate) tag: Option<String>,
+    pub(crate) content: Option<String>,
+}
+
+impl SerdeAttributes {
+    /// Parses the relevant `serde` attributes. Only the serialization names are considered
+    /// for renames that differ between serialization and deserialization.
+    pub(crate) fn from_attributes(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut serde = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    if let Some(rename) = serialize_name(&meta)? {
+                        serde.rename = Some(rename.value());
+                    }
+                } else if meta.path.is_ident("rename_all") {
+                    if let Some(rule) = serialize_name(&meta)? {
+                        serde.rename_all = Some(RenameRule::from_lit(&rule)?);
+                    }
+                } else if meta.path.is_ident("rename_all_fields") {
+                    if let Some(rule) = serialize_name(&meta)? {
+                        serde.rename_all_fields = Some(RenameRule::from_lit(&rule)?);
+                    }
+                } else if meta.path.is_ident("tag") {
+                    serde.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("content") {
+                    serde.content = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("flatten") {
+                    serde.flatten = true;
+                } else if meta.path.is_ident("transparent") {
+                    serde.transparent = true;
+                } else if meta.path.is_ident("untagged") {
+                    serde.untagged = true;
+                } else {
+                    skip(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(serde)
+    }
+}
+
+/// Returns the `serde` attributes with one of the given `keys` as a single `#[serde(...)]` attribute,
+/// or `None` if there are none.
+pub(crate) fn forwarded_attributes(
+    attrs: &[Attribute],
+    keys: &[&str],
+) -> syn::Result<Option<TokenStream>> {
+    let mut metas = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        let nested = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        metas.extend(
+            nested
+                .into_iter()
+                .filter(|meta| keys.iter().any(|key| meta.path().is_ident(key))),
+        );
+    }
+    Ok((!metas.is_empty()).then(|| quote!(#[serde(#(#metas),*)])))
+}
+
+/// Parses `<name> = "..."` or `<name>(serialize = "...", deserialize = "...")`
+/// and returns the serialization value.
+fn serialize_name(meta: &ParseNestedMeta) -> syn::Result<Option<LitStr>> {
+    if meta.input.peek(Token![=]) {
+        return Ok(Some(meta.value()?.parse()?));
+    }
+    let mut serialize = None;
+    meta.parse_nested_meta(|meta| {
+        if meta.path.is_ident("serialize") {
+            serialize = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            skip(&meta)
+        }
+    })?;
+    Ok(serialize)
+}
+
+/// Skips an irrelevant (possibly nested) `serde` attribute.
+fn skip(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|meta| skip(&meta))?;
+    }
+    Ok(())
+}
+
+/// The case conventions of `#[serde(rename_all = "...")]`.
+#[derive(Clone, Copy)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return Err(syn::Error::new_spanned(lit, "unknown serde rename rule")),
+        })
+    }
+
+    /// Applies the rule to a variant name in `PascalCase` like `serde` does.
+    pub(crate) fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            Self::Pascal => variant.to_owned(),
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Upper => variant.to_ascii_uppercase(),
+            Self::Camel => lowercase_first(variant),
+            Self::Snake => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            Self::ScreamingSnake => Self::Snake.apply_to_variant(variant).to_ascii_uppercase(),
+            Self::Kebab => Self::Snake.apply_to_variant(variant).replace('_', "-"),
+            Self::ScreamingKebab => Self::ScreamingSnake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+
+    /// Applies the rule to a field name in `snake_case` like `serde` does.
+    pub(crate) fn apply_to_field(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_owned(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            Self::Camel => lowercase_first(&Self::Pascal.apply_to_field(field)),
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+/// Lowercases the first character, e.g. to turn `PascalCase` into `camelCase`.
+fn lowercase_first(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
@@ -0,0 +1,114 @@
+use crate::attributes::FieldHelperAttributes;
+use crate::derive::push_deferred_bound;
+use crate::serde::SerdeAttributes;
+use crate::validate::field_name;
+use darling::FromAttributes;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Generics, WherePredicate};
+
+/// Derives the `OptionableStrategicMerge` impl returning the patch strategies of the fields named according to the
+/// `serde` attributes, which are kept for the serialized optioned type. The strategies of flattened fields and
+/// of the field of a transparent struct are located at their parent.
+/// Required fields are kept as is and hence have no strategies for their (sub-)fields.
+pub(crate) fn derive_strategic_merge(
+    generics: &Generics,
+    input: &DeriveInput,
+) -> syn::Result<TokenStream> {
+    let type_ident = &input.ident;
+    let Data::Struct(s) = &input.data else {
+        return crate::error(
+            "#[optionable(strategic_merge)] is only supported for structs with named fields",
+        );
+    };
+    let Fields::Named(fields) = &s.fields else {
+        return crate::error(
+            "#[optionable(strategic_merge)] is only supported for structs with named fields",
+        );
+    };
+
+    let serde = SerdeAttributes::from_attributes(&input.attrs)?;
+
+    let mut statements = vec![];
+    let mut predicates: Vec<WherePredicate> = vec![];
+    for (i, f) in fields.named.iter().enumerate() {
+        let attrs = FieldHelperAttributes::from_attributes(&f.attrs)?;
+        attrs.check_merge_strategy()?;
+        let ty = &f.ty;
+        let field_meta = if attrs.required.is_some() {
+            quote!(::optionable::strategic_merge::PatchMeta::default())
+        } else {
+            push_deferred_bound(
+                &mut predicates,
+                ty,
+                &quote!(::optionable::strategic_merge::OptionableStrategicMerge),
+            );
+            quote!(<#ty as ::optionable::strategic_merge::OptionableStrategicMerge>::patch_meta())
+        };
+        let name = match field_name(f, i, serde.rename_all)? {
+            Some(name) if !serde.transparent => name,
+            _ => {
+                if attrs.patch_strategy.is_some() || attrs.patch_merge_key.is_some() {
+                    return crate::error(
+                        "patch_strategy and patch_merge_key are not supported for flattened fields and transparent structs",
+                    );
+                }
+                statements.push(quote! {
+                    let field_meta = #field_meta;
+                    meta.fields.extend(field_meta.fields);
+                    meta.elements = meta.elements.or(field_meta.elements);
+                });
+                continue;
+            }
+        };
+        let strategies = attrs
+            .patch_strategy
+            .as_ref()
+            .map(syn::LitStr::value)
+            .unwrap_or_default();
+        let strategy = |strategy: &str| strategies.split(',').any(|s| s == strategy);
+        let (merge, replace, retain_keys) =
+            (strategy("merge"), strategy("replace"), strategy("retainKeys"));
+        let merge_key = attrs.patch_merge_key.as_ref().map_or_else(
+            || quote!(::core::option::Option::None),
+            |key| quote!(::core::option::Option::Some(::optionable::__private::String::from(#key))),
+        );
+        statements.push(quote! {
+            meta.fields.insert(
+                ::optionable::__private::String::from(#name),
+                ::optionable::strategic_merge::FieldPatchMeta {
+                    strategy: ::optionable::strategic_merge::PatchStrategy {
+                        merge: #merge,
+                        replace: #replace,
+                        retain_keys: #retain_keys,
+                    },
+                    merge_key: #merge_key,
+                    meta: #field_meta,
+                },
+            );
+        });
+    }
+    let body = if statements.is_empty() {
+        quote!(::optionable::strategic_merge::PatchMeta::default())
+    } else {
+        quote! {
+            let mut meta = ::optionable::strategic_merge::PatchMeta::default();
+            #(#statements)*
+            meta
+        }
+    };
+
+    let mut generics = generics.clone();
+    if !predicates.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::optionable::strategic_merge::OptionableStrategicMerge for #type_ident #ty_generics #where_clause {
+            fn patch_meta() -> ::optionable::strategic_merge::PatchMeta {
+                #body
+            }
+        }
+    })
+}
@@ -0,0 +1,206 @@
+use crate::convert::{var, FieldKind, FieldsInfo};
+use crate::derive::{deferred_bound, push_deferred_bound};
+use crate::serde::{RenameRule, SerdeAttributes};
+use crate::validate::field_path;
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{Data, DataEnum, DeriveInput, Fields, Generics, Index, WherePredicate};
+
+/// Derives the `OptionableThreeWayMerge` impl merging the fields of structs and of equal enum variants
+/// recursively. Required fields and different enum variants are merged as a whole.
+/// The paths of the conflicts follow the `serde` renames like the field paths.
+pub(crate) fn derive_three_way(
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    input: &DeriveInput,
+    input_object: bool,
+) -> syn::Result<TokenStream> {
+    let type_ident = &input.ident;
+    let serde = SerdeAttributes::from_attributes(&input.attrs)?;
+    let mut predicates: Vec<WherePredicate> = vec![];
+    let body = match &input.data {
+        Data::Struct(s) => {
+            let fields = FieldsInfo::new(&s.fields, input_object)?;
+            let opt = type_ident_opt.to_token_stream();
+            let (ours, theirs) = (fields.bind(&opt, "ours"), fields.bind(&opt, "theirs"));
+            let bases = fields.fields.iter().map(|f| {
+                let member = &f.member;
+                quote!(base.map(|base| &base.#member))
+            });
+            let merged = merge_fields(
+                &opt,
+                &fields,
+                &s.fields,
+                bases.collect(),
+                serde.rename_all,
+                serde.transparent,
+                &mut predicates,
+            )?;
+            let unused = fields
+                .fields
+                .is_empty()
+                .then(|| quote!(let _ = (base, prefix, conflicts);));
+            quote! {
+                #unused
+                let #ours = ours;
+                let #theirs = theirs;
+                #merged
+            }
+        }
+        Data::Enum(e) => enum_body(type_ident_opt, e, &serde, input_object, &mut predicates)?,
+        Data::Union(_) => return crate::error("#[derive(Optionable)] not supported for unions"),
+    };
+
+    let mut generics = generics.clone();
+    let (_, ty_generics, _) = generics.split_for_impl();
+    // the optioned type has to be comparable, e.g. via a forwarded `PartialEq` derive
+    let optioned_predicate = deferred_bound(
+        &quote!(#type_ident_opt #ty_generics),
+        &quote!(::core::cmp::PartialEq),
+    );
+    predicates.insert(0, optioned_predicate);
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::optionable::three_way::OptionableThreeWayMerge for #type_ident #ty_generics #where_clause {
+            fn three_way_merge_at(
+                base: ::core::option::Option<&#type_ident_opt #ty_generics>,
+                ours: #type_ident_opt #ty_generics,
+                theirs: #type_ident_opt #ty_generics,
+                prefix: &str,
+                conflicts: &mut ::optionable::__private::Vec<::optionable::__private::String>,
+            ) -> #type_ident_opt #ty_generics {
+                #body
+            }
+        }
+    })
+}
+
+/// Returns the body of `three_way_merge_at` for an enum. Equal variants are merged field by field
+/// below the path of the variant content according to the `serde` enum representation,
+/// the fields of `base` are only considered if it is the same variant. Different variants are merged as a whole.
+fn enum_body(
+    type_ident_opt: &Ident,
+    e: &DataEnum,
+    serde: &SerdeAttributes,
+    input_object: bool,
+    predicates: &mut Vec<WherePredicate>,
+) -> syn::Result<TokenStream> {
+    if e.variants.is_empty() {
+        return Ok(quote! {
+            let _ = (base, theirs, prefix, conflicts);
+            match ours {}
+        });
+    }
+    let mut arms = vec![];
+    for v in &e.variants {
+        let variant_serde = SerdeAttributes::from_attributes(&v.attrs)?;
+        let ident = &v.ident;
+        let path = quote!(#type_ident_opt::#ident);
+        let fields = FieldsInfo::new(&v.fields, input_object)?;
+        let (ours, theirs) = (fields.bind(&path, "ours"), fields.bind(&path, "theirs"));
+        if fields.fields.is_empty() {
+            arms.push(quote!((#ours, #theirs) => #path));
+            continue;
+        }
+        let base = fields.bind(&path, "base");
+        let base_vars = (0..fields.fields.len()).map(|i| var("base", i));
+        let bases = (0..fields.fields.len()).map(|i| {
+            let i = Index::from(i);
+            quote!(base.map(|base| base.#i))
+        });
+        let name = variant_serde.rename.unwrap_or_else(|| {
+            serde.rename_all.map_or_else(
+                || ident.to_string(),
+                |rule| rule.apply_to_variant(&ident.to_string()),
+            )
+        });
+        // the path segment of the variant content, if any
+        let content_field = match (&serde.tag, &serde.content) {
+            _ if serde.untagged => None,
+            (Some(_), content) => content.clone(),
+            (None, _) => Some(name),
+        };
+        let prefix = content_field.map(
+            |content| quote!(let prefix = &::optionable::__private::field_path(prefix, #content);),
+        );
+        let merged = merge_fields(
+            &path,
+            &fields,
+            &v.fields,
+            bases.collect(),
+            variant_serde.rename_all.or(serde.rename_all_fields),
+            false,
+            predicates,
+        )?;
+        arms.push(quote! {
+            (#ours, #theirs) => {
+                let base = match base {
+                    ::core::option::Option::Some(#base) => ::core::option::Option::Some((#(#base_vars,)*)),
+                    _ => ::core::option::Option::None,
+                };
+                #prefix
+                #merged
+            }
+        });
+    }
+    Ok(quote! {
+        match (ours, theirs) {
+            #(#arms,)*
+            #[allow(unreachable_patterns)]
+            (ours, theirs) => ::optionable::__private::three_way_merge_value(base, ours, theirs, prefix, conflicts),
+        }
+    })
+}
+
+/// Returns the constructor for the given path merging the fields bound to `ours_<index>` and `theirs_<index>`,
+/// `bases` being the expressions of the corresponding optional fields of `base`.
+/// Flattened fields and the field of a newtype are located at the path of their parent.
+fn merge_fields(
+    path: &TokenStream,
+    fields: &FieldsInfo,
+    syn_fields: &Fields,
+    bases: Vec<TokenStream>,
+    rename_rule: Option<RenameRule>,
+    transparent: bool,
+    predicates: &mut Vec<WherePredicate>,
+) -> syn::Result<TokenStream> {
+    let newtype = matches!(syn_fields, Fields::Unnamed(f) if f.unnamed.len() == 1);
+    let mut values = vec![];
+    for (i, ((f, syn_field), base)) in fields.fields.iter().zip(syn_fields).zip(bases).enumerate() {
+        let (ours, theirs) = (var("ours", i), var("theirs", i));
+        let field_path = if transparent || newtype {
+            quote!(prefix)
+        } else {
+            field_path(syn_field, i, rename_rule)?
+        };
+        let ty = &f.ty;
+        let (merge, bounded_ty, bound) = match &f.kind {
+            FieldKind::Required => (
+                quote!(::optionable::__private::three_way_merge_value),
+                quote!(#ty),
+                quote!(::core::cmp::PartialEq),
+            ),
+            FieldKind::Optioned => (
+                quote!(<#ty as ::optionable::three_way::OptionableThreeWayMerge>::three_way_merge_at),
+                quote!(#ty),
+                quote!(::optionable::three_way::OptionableThreeWayMerge),
+            ),
+            FieldKind::Wrapped => (
+                quote!(<::core::option::Option<#ty> as ::optionable::three_way::OptionableThreeWayMerge>::three_way_merge_at),
+                quote!(#ty),
+                quote!(::optionable::three_way::OptionableThreeWayMerge),
+            ),
+            // undefined, null and values are merged as a whole
+            FieldKind::TriState(inner_ty) => (
+                quote!(::optionable::__private::three_way_merge_value),
+                quote!(::async_graphql::MaybeUndefined<<#inner_ty as ::optionable::Optionable>::Optioned>),
+                quote!(::core::cmp::PartialEq),
+            ),
+        };
+        push_deferred_bound(predicates, &bounded_ty, &bound);
+        values.push(quote!(#merge(#base, #ours, #theirs, #field_path, conflicts)));
+    }
+    Ok(fields.construct(path, values.into_iter()))
+}
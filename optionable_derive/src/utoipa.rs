@@ -0,0 +1,147 @@
+use crate::attributes::FieldHelperAttributes;
+use crate::derive::TypeParamVisitor;
+use darling::FromAttributes;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::visit::Visit;
+use syn::{parse_quote, Data, Fields, Generics, Type, WherePredicate};
+
+/// Derives the `utoipa::ToSchema` impl for the optioned type.
+/// The derive macro from `utoipa` can't be forwarded as it does not resolve the associated
+/// `Optioned` types of the fields.
+/// Like the `utoipa` derive we implement `ComposeSchema` which provides `PartialSchema` via a blanket impl
+/// and is required by the `PartialSchema` impls of the containers (e.g. `Vec`).
+///
+/// All non-required properties are optional. They are only nullable if the original field type is an `Option`
+/// or if they are unnamed (positional) fields as the position has to be filled.
+/// Nested schemas are inlined.
+pub(crate) fn derive_to_schema(
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    data: &Data,
+) -> syn::Result<TokenStream> {
+    let mut schema_types = vec![];
+    let schema = match data {
+        Data::Struct(s) => fields_schema(&s.fields, &mut schema_types)?,
+        Data::Enum(e) => {
+            let variants = e
+                .variants
+                .iter()
+                .map(|v| {
+                    let name = v.ident.to_string();
+                    Ok::<_, syn::Error>(if let Fields::Unit = v.fields {
+                        quote! {
+                            ::utoipa::openapi::schema::ObjectBuilder::new()
+                                .schema_type(::utoipa::openapi::schema::Type::String)
+                                .enum_values(Some([#name]))
+                        }
+                    } else {
+                        let schema = fields_schema(&v.fields, &mut schema_types)?;
+                        quote! {
+                            ::utoipa::openapi::schema::ObjectBuilder::new()
+                                .property(#name, #schema)
+                                .required(#name)
+                        }
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            quote! {
+                ::utoipa::openapi::schema::OneOfBuilder::new()
+                    #(.item(#variants))*
+            }
+        }
+        Data::Union(_) => return crate::error("#[derive(Optionable)] not supported for unions"),
+    };
+
+    let mut generics = generics.clone();
+    let predicates = schema_types
+        .iter()
+        .filter(|ty| {
+            let mut visitor = TypeParamVisitor::new(&generics);
+            visitor.visit_type(ty);
+            !visitor.used.is_empty()
+        })
+        .map(|ty| -> WherePredicate { parse_quote!(#ty: ::utoipa::ToSchema) })
+        .collect::<Vec<_>>();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name = type_ident_opt.to_string();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::utoipa::__dev::ComposeSchema for #type_ident_opt #ty_generics #where_clause {
+            fn compose(
+                _: ::std::vec::Vec<::utoipa::openapi::RefOr<::utoipa::openapi::schema::Schema>>,
+            ) -> ::utoipa::openapi::RefOr<::utoipa::openapi::schema::Schema> {
+                #schema.into()
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::utoipa::ToSchema for #type_ident_opt #ty_generics #where_clause {
+            fn name() -> ::std::borrow::Cow<'static, str> {
+                ::std::borrow::Cow::Borrowed(#name)
+            }
+
+            fn schemas(schemas: &mut ::std::vec::Vec<(::std::string::String, ::utoipa::openapi::RefOr<::utoipa::openapi::schema::Schema>)>) {
+                #(<#schema_types as ::utoipa::ToSchema>::schemas(schemas);)*
+            }
+        }
+    })
+}
+
+/// Returns the schema expression for the fields of a struct or enum variant and collects the types
+/// of the field schemas in `schema_types`.
+fn fields_schema(fields: &Fields, schema_types: &mut Vec<Type>) -> syn::Result<TokenStream> {
+    let fields = fields
+        .iter()
+        .map(|f| {
+            let attrs = FieldHelperAttributes::from_attributes(&f.attrs)?;
+            let ty = &f.ty;
+            let schema_type: Type = if attrs.required.is_some() {
+                ty.clone()
+            } else if f.ident.is_some() {
+                // named fields may be absent, so only the originally optional ones are nullable
+                parse_quote!(<#ty as ::optionable::Optionable>::Optioned)
+            } else {
+                parse_quote!(Option<<#ty as ::optionable::Optionable>::Optioned>)
+            };
+            schema_types.push(schema_type.clone());
+            Ok::<_, syn::Error>((f.ident.as_ref(), schema_type, attrs.required.is_some()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(match fields.as_slice() {
+        [] => quote! {
+            ::utoipa::openapi::schema::ObjectBuilder::new()
+                .schema_type(::utoipa::openapi::schema::Type::Null)
+        },
+        [(Some(_), ..), ..] => {
+            let properties = fields.iter().map(|(ident, ty, required)| {
+                let name = ident.map(ToString::to_string).unwrap_or_default();
+                let name = name.trim_start_matches("r#");
+                let required = required.then(|| quote!(.required(#name)));
+                quote! {
+                    .property(#name, <#ty as ::utoipa::PartialSchema>::schema())
+                    #required
+                }
+            });
+            quote! {
+                ::utoipa::openapi::schema::ObjectBuilder::new()
+                    .schema_type(::utoipa::openapi::schema::Type::Object)
+                    #(#properties)*
+            }
+        }
+        [(None, ty, _)] => quote!(<#ty as ::utoipa::PartialSchema>::schema()),
+        // same as `utoipa`: serialized as array, but the item types can't be expressed in OpenAPI
+        _ => {
+            let len = fields.len();
+            quote! {
+                ::utoipa::openapi::schema::ObjectBuilder::new()
+                    .to_array_builder()
+                    .max_items(Some(#len))
+                    .min_items(Some(#len))
+            }
+        }
+    })
+}
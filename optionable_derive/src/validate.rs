@@ -0,0 +1,205 @@
+use crate::attributes::FieldHelperAttributes;
+use crate::derive::{is_option, option_inner_type, push_deferred_bound, tri_state_inner_type};
+use crate::serde::{RenameRule, SerdeAttributes};
+use darling::{FromAttributes, FromMeta};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Expr, Field, Fields, Generics, LitStr, Path, WherePredicate,
+};
+
+/// The constraints of a field declared via `#[optionable(validate(...))]`.
+#[derive(FromMeta, Default)]
+pub(crate) struct Validations {
+    range: Option<Bounds>,
+    length: Option<Bounds>,
+    regex: Option<LitStr>,
+    #[darling(multiple)]
+    custom: Vec<Path>,
+    nested: Option<()>,
+}
+
+/// Inclusive bounds of a `range` or `length` constraint.
+#[derive(FromMeta)]
+struct Bounds {
+    min: Option<Expr>,
+    max: Option<Expr>,
+}
+
+/// Derives the `OptionableValidate` impl checking the constraints of the set fields.
+/// Required fields are always set and hence always checked, `nested` validates the optioned value of the field
+/// via its own `OptionableValidate` impl. The paths of the fields follow the `serde` renames like the field paths.
+pub(crate) fn derive_validate(
+    type_ident_opt: &Ident,
+    generics: &Generics,
+    input: &DeriveInput,
+    input_object: bool,
+) -> syn::Result<TokenStream> {
+    let type_ident = &input.ident;
+    let Data::Struct(s) = &input.data else {
+        return crate::error(
+            "#[optionable(validate)] is only supported for structs with named fields",
+        );
+    };
+    let Fields::Named(fields) = &s.fields else {
+        return crate::error(
+            "#[optionable(validate)] is only supported for structs with named fields",
+        );
+    };
+    let rename_rule = SerdeAttributes::from_attributes(&input.attrs)?.rename_all;
+
+    let mut bindings = vec![];
+    let mut checks = vec![];
+    let mut predicates: Vec<WherePredicate> = vec![];
+    for (i, f) in fields.named.iter().enumerate() {
+        let attrs = FieldHelperAttributes::from_attributes(&f.attrs)?;
+        let Some(validations) = attrs.validate else {
+            continue;
+        };
+        let (ident, ty) = (&f.ident, &f.ty);
+        let path = field_path(f, i, rename_rule)?;
+        let var = Ident::new(&format!("field_{i}"), Span::call_site());
+        let value_checks = validations.checks();
+        if attrs.required.is_some() {
+            if validations.nested.is_some() {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "`nested` is not supported for required fields",
+                ));
+            }
+            if !value_checks.is_empty() {
+                bindings.push(quote!(#ident: #var));
+                checks.push(quote! {{
+                    let value = #var;
+                    let path = #path;
+                    #(#value_checks)*
+                }});
+            }
+            continue;
+        }
+        // the optioned type of `Option<T>` is `Option<T::Optioned>`, the constraints apply to the inner value
+        let inner_ty = option_inner_type(ty)
+            .filter(|_| is_option(ty))
+            .unwrap_or(ty);
+        let nested = validations.nested.map(|()| {
+            push_deferred_bound(
+                &mut predicates,
+                inner_ty,
+                &quote!(::optionable::validate::OptionableValidate),
+            );
+            quote! {
+                <#inner_ty as ::optionable::validate::OptionableValidate>::validate_optioned_at(value, path, errors);
+            }
+        });
+        if value_checks.is_empty() && nested.is_none() {
+            continue;
+        }
+        let pattern = if tri_state_inner_type(f, input_object)?.is_some() {
+            quote!(::async_graphql::MaybeUndefined::Value(value))
+        } else {
+            quote!(Some(value))
+        };
+        bindings.push(quote!(#ident: #var));
+        checks.push(quote! {
+            if let #pattern = #var {
+                let path = #path;
+                #(#value_checks)*
+                #nested
+            }
+        });
+    }
+    let body = if bindings.is_empty() {
+        quote!(let _ = (optioned, prefix, errors);)
+    } else {
+        quote! {
+            let #type_ident_opt { #(#bindings,)* .. } = optioned;
+            #(#checks)*
+        }
+    };
+
+    let mut generics = generics.clone();
+    if !predicates.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::optionable::validate::OptionableValidate for #type_ident #ty_generics #where_clause {
+            fn validate_optioned_at(
+                optioned: &#type_ident_opt #ty_generics,
+                prefix: &str,
+                errors: &mut ::optionable::__private::Vec<::optionable::validate::ValidationError>,
+            ) {
+                #body
+            }
+        }
+    })
+}
+
+/// Returns the path of the `i`-th field below `prefix`, named like for the field paths.
+pub(crate) fn field_path(
+    f: &Field,
+    i: usize,
+    rename_rule: Option<RenameRule>,
+) -> syn::Result<TokenStream> {
+    Ok(field_name(f, i, rename_rule)?.map_or_else(
+        || quote!(prefix),
+        |name| quote!(&::optionable::__private::field_path(prefix, #name)),
+    ))
+}
+
+/// Returns the serialized name of the `i`-th field, `None` if it is flattened into its parent.
+pub(crate) fn field_name(
+    f: &Field,
+    i: usize,
+    rename_rule: Option<RenameRule>,
+) -> syn::Result<Option<String>> {
+    let serde = SerdeAttributes::from_attributes(&f.attrs)?;
+    if serde.flatten {
+        return Ok(None);
+    }
+    Ok(Some(serde.rename.unwrap_or_else(|| {
+        let ident = f
+            .ident
+            .as_ref()
+            .map_or_else(|| i.to_string(), ToString::to_string);
+        let ident = ident.trim_start_matches("r#");
+        rename_rule.map_or_else(|| ident.to_owned(), |rule| rule.apply_to_field(ident))
+    })))
+}
+
+impl Validations {
+    /// Returns the statements checking the constraints for the field bound to `value` located at `path`.
+    fn checks(&self) -> Vec<TokenStream> {
+        let mut checks = vec![];
+        let bound =
+            |bound: Option<&Expr>| bound.map_or_else(|| quote!(None), |bound| quote!(Some(#bound)));
+        if let Some(range) = &self.range {
+            let (min, max) = (bound(range.min.as_ref()), bound(range.max.as_ref()));
+            checks.push(
+                quote!(::optionable::__private::validate_range(value, #min, #max, path, errors);),
+            );
+        }
+        if let Some(length) = &self.length {
+            let (min, max) = (bound(length.min.as_ref()), bound(length.max.as_ref()));
+            checks.push(
+                quote!(::optionable::__private::validate_length(value, #min, #max, path, errors);),
+            );
+        }
+        if let Some(regex) = &self.regex {
+            checks.push(quote! {
+                static REGEX: ::optionable::__private::LazyLock<::optionable::__private::Regex> =
+                    ::optionable::__private::LazyLock::new(|| {
+                        ::optionable::__private::Regex::new(#regex).expect("invalid regex of #[optionable(validate(regex))]")
+                    });
+                ::optionable::__private::validate_regex(value, &REGEX, path, errors);
+            });
+        }
+        for custom in &self.custom {
+            checks.push(
+                quote!(::optionable::__private::validate_custom(#custom(value), path, errors);),
+            );
+        }
+        checks
+    }
+}